@@ -1,19 +1,24 @@
-use std::fs::File;
 use std::io::BufReader;
 use std::io::BufRead;
+use std::process;
 
-fn load_from_file(file_path: &str) -> Vec<usize> {
-    let file = File::open(file_path).expect("file wasn't found.");
-    let reader = BufReader::new(file);
+fn load_from_file(file_path: &str) -> Result<Vec<usize>, String> {
+    let file = advent_vault::open(file_path).map_err(|err| format!("{}: {}", file_path, err))?;
+    parse_depths(BufReader::new(file))
+}
 
-    let numbers: Vec<usize> = reader
+fn parse_depths<R: BufRead>(reader: R) -> Result<Vec<usize>, String> {
+    reader
         .lines()
-        .map(|line| line.unwrap().parse::<usize>().unwrap())
-        .collect();
-    numbers 
+        .enumerate()
+        .map(|(index, line)| {
+            let line = line.map_err(|err| format!("line {}: {}", index + 1, err))?;
+            line.trim().parse::<usize>().map_err(|err| format!("line {}: '{}' is not a depth: {}", index + 1, line, err))
+        })
+        .collect()
 }
 
-fn sliding_averages(amount: usize, array: &Vec<usize>) -> Vec<usize> {
+fn sliding_averages(amount: usize, array: &[usize]) -> Vec<usize> {
     let mut averages = Vec::<usize>::new();
     for (index, _) in array.iter().enumerate() {
         // println!("{:} and {:}", index, value);
@@ -21,7 +26,7 @@ fn sliding_averages(amount: usize, array: &Vec<usize>) -> Vec<usize> {
             let end = index + 1;
             let start: usize = if (end - amount) > 0 { end - amount } else { 0 };
             let sliced = &array[start .. end];
-            let reduced = sliced.iter().fold(0, |a, b| a + b);
+            let reduced = sliced.iter().sum();
             averages.push(reduced);
         }
     }
@@ -48,7 +53,13 @@ fn count_increased(array: &Vec<usize>) -> usize {
 fn main() {
 
     let filename = "day1_input.txt";
-    let ocean_depths = load_from_file(filename);
+    let ocean_depths = match load_from_file(filename) {
+        Ok(depths) => depths,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    };
     // let ocean_depths = vec![199,200,208,210,200,207,240,269,260,263];
     println!("increased depths: {:?}", count_increased(&ocean_depths));
 
@@ -56,3 +67,22 @@ fn main() {
     let averages_increased = count_increased(&averages);
     println!("sliding averages increased: {:?}", averages_increased);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use propcheck::Fuzzer;
+
+    #[test]
+    fn test_parse_depths() {
+        let depths = parse_depths("199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n".as_bytes()).unwrap();
+        assert_eq!(count_increased(&depths), 7);
+        assert_eq!(count_increased(&sliding_averages(3, &depths)), 5);
+        assert!(parse_depths("199\n-2\n".as_bytes()).unwrap_err().starts_with("line 2: '-2' is not a depth"));
+    }
+
+    #[test]
+    fn test_fuzz_parse_depths() {
+        Fuzzer::new("parse_depths").seed_input("199\n200\n208\n210\n").run(|bytes| parse_depths(bytes));
+    }
+}
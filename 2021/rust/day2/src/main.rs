@@ -1,6 +1,6 @@
-use std::fs::File;
 use std::io::BufReader;
 use std::io::BufRead;
+use std::process;
 use std::str::FromStr;
 
 #[derive(Debug)]
@@ -10,36 +10,57 @@ enum Direction {
     Up(usize)
 }
 
+/// Depth and aim go negative when the submarine rises above the surface.
 #[derive(Debug, Default)]
 struct Location {
-    horizontal: usize,
-    depth: usize,
-    aim: usize
+    horizontal: i64,
+    depth: i64,
+    aim: i64
+}
+
+fn overflow(index: usize) -> String {
+    format!("line {}: the location no longer fits in 64 bits", index + 1)
+}
+
+/// Units as a signed step, `None` if they're too many to ever fit.
+fn signed(units: usize) -> Option<i64> {
+    i64::try_from(units).ok()
 }
 
 impl Location {
-    fn move_to(&mut self, directions: &Vec<Direction>) -> () {
-        for direction in directions {
-            match direction {
-                Direction::Forward(units) => self.horizontal = self.horizontal + units,
-                Direction::Down(units) => self.depth = self.depth + units,
-                Direction::Up(units) => self.depth = self.depth - units
-            }
+    fn move_to(&mut self, directions: &[Direction]) -> Result<(), String> {
+        for (index, direction) in directions.iter().enumerate() {
+            let moved = match *direction {
+                Direction::Forward(units) => signed(units).and_then(|units| self.horizontal.checked_add(units)).map(|horizontal| self.horizontal = horizontal),
+                Direction::Down(units) => signed(units).and_then(|units| self.depth.checked_add(units)).map(|depth| self.depth = depth),
+                Direction::Up(units) => signed(units).and_then(|units| self.depth.checked_sub(units)).map(|depth| self.depth = depth)
+            };
+            moved.ok_or_else(|| overflow(index))?;
         }
+        Ok(())
     }
 
-    fn aim_and_move(&mut self, directions: &Vec<Direction>) -> () {
-        for direction in directions {
-            match direction {
-                Direction::Forward(units) => {
-                    self.horizontal = self.horizontal + units;
-                    self.depth = self.depth + (self.aim * units);
-                },
-                Direction::Down(units) => self.aim = self.aim + units,
-                Direction::Up(units) => self.aim = self.aim - units
-            }
+    fn aim_and_move(&mut self, directions: &[Direction]) -> Result<(), String> {
+        for (index, direction) in directions.iter().enumerate() {
+            let moved = match *direction {
+                Direction::Forward(units) => signed(units)
+                    .and_then(|units| self.horizontal.checked_add(units)
+                        .zip(self.aim.checked_mul(units).and_then(|dive| self.depth.checked_add(dive))))
+                    .map(|(horizontal, depth)| {
+                        self.horizontal = horizontal;
+                        self.depth = depth;
+                    }),
+                Direction::Down(units) => signed(units).and_then(|units| self.aim.checked_add(units)).map(|aim| self.aim = aim),
+                Direction::Up(units) => signed(units).and_then(|units| self.aim.checked_sub(units)).map(|aim| self.aim = aim)
+            };
+            moved.ok_or_else(|| overflow(index))?;
         }
+        Ok(())
+    }
 
+    /// Horizontal position times depth, wide enough for any location.
+    fn product(&self) -> i128 {
+        self.horizontal as i128 * self.depth as i128
     }
 }
 
@@ -50,44 +71,130 @@ impl FromStr for Direction {
         let mut split = s.split_whitespace();
         let direction = split.next();
         let units = split.next()
-            .unwrap_or("0")
-            .parse::<usize>();
+            .map(str::parse::<usize>);
         let pair = (direction, units);
         match pair {
-            (Some("forward"), Ok(units)) => Ok(Direction::Forward(units)),
-            (Some("down"), Ok(units)) => Ok(Direction::Down(units)),
-            (Some("up"), Ok(units)) => Ok(Direction::Up(units)),
+            (Some("forward"), Some(Ok(units))) => Ok(Direction::Forward(units)),
+            (Some("down"), Some(Ok(units))) => Ok(Direction::Down(units)),
+            (Some("up"), Some(Ok(units))) => Ok(Direction::Up(units)),
             _ => Err(())
         }
     }
     
 }
 
-fn load_from_file(file_path: &str) -> Vec<Direction> {
-    let file = File::open(file_path).expect("file wasn't found.");
-    let reader = BufReader::new(file);
+fn load_from_file(file_path: &str) -> Result<Vec<Direction>, String> {
+    let file = advent_vault::open(file_path).map_err(|err| format!("{}: {}", file_path, err))?;
+    parse_directions(BufReader::new(file))
+}
 
-    let directions: Vec<Direction> = reader
+fn parse_directions<R: BufRead>(reader: R) -> Result<Vec<Direction>, String> {
+    reader
         .lines()
-        .map(|line| line.unwrap().parse::<Direction>().unwrap())
-        .collect();
-
-    directions
+        .enumerate()
+        .map(|(index, line)| {
+            let line = line.map_err(|err| format!("line {}: {}", index + 1, err))?;
+            line.parse::<Direction>()
+                .map_err(|_| format!("line {}: expected 'forward|down|up <units>', found '{}'", index + 1, line))
+        })
+        .collect()
 }
 
 fn main() {
+    if let Err(err) = run() {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), String> {
     const FILENAME: &str = "day2_input.txt";
-    let directions = load_from_file(FILENAME);
+    let directions = load_from_file(FILENAME)?;
     println!("{:?}", directions);
     let mut location = Location::default();
     println!("original location: {:?}", location);
-    location.move_to(&directions);
+    location.move_to(&directions)?;
     println!("moved location: {:?}", location);
-    println!("multiply coordinates: {}", location.depth * location.horizontal);
+    println!("multiply coordinates: {}", location.product());
 
     let mut location2 = Location::default();
-    location2.aim_and_move(&directions);
+    location2.aim_and_move(&directions)?;
     println!("moved another location: {:?}", location2);
-    println!("multiply coordinates: {}", location2.depth * location2.horizontal);
+    println!("multiply coordinates: {}", location2.product());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use propcheck::Fuzzer;
 
+    #[test]
+    fn test_parse_directions() {
+        let directions = parse_directions("forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2\n".as_bytes()).unwrap();
+        let mut location = Location::default();
+        location.move_to(&directions).unwrap();
+        assert_eq!(location.product(), 150);
+        let mut location = Location::default();
+        location.aim_and_move(&directions).unwrap();
+        assert_eq!(location.product(), 900);
+        assert_eq!(parse_directions("forward 5\nbackward 2\n".as_bytes()).unwrap_err(),
+            "line 2: expected 'forward|down|up <units>', found 'backward 2'");
+        assert_eq!(parse_directions("down 1\nforward\n".as_bytes()).unwrap_err(),
+            "line 2: expected 'forward|down|up <units>', found 'forward'");
+    }
+
+    #[test]
+    fn test_rising_above_the_surface() {
+        let directions = parse_directions("up 1\nforward 3\n".as_bytes()).unwrap();
+        let mut location = Location::default();
+        location.move_to(&directions).unwrap();
+        assert_eq!((location.horizontal, location.depth), (3, -1));
+        let mut location = Location::default();
+        location.aim_and_move(&directions).unwrap();
+        assert_eq!((location.horizontal, location.depth, location.aim), (3, -3, -1));
+    }
+
+    #[test]
+    fn test_overflow_is_an_error() {
+        let down = "down 4294967295\n".repeat(3);
+        let directions = parse_directions(format!("{}forward 4294967295\n", down).as_bytes()).unwrap();
+        let mut location = Location::default();
+        assert_eq!(location.aim_and_move(&directions).unwrap_err(), "line 4: the location no longer fits in 64 bits");
+    }
+
+    #[test]
+    fn test_units_wider_than_u32() {
+        let directions = parse_directions("forward 4294967296\ndown 3\n".as_bytes()).unwrap();
+        let mut location = Location::default();
+        location.move_to(&directions).unwrap();
+        assert_eq!(location.product(), 3 * 4294967296);
+
+        let directions = parse_directions("forward 18446744073709551615\n".as_bytes()).unwrap();
+        let mut location = Location::default();
+        assert_eq!(location.move_to(&directions).unwrap_err(), "line 1: the location no longer fits in 64 bits");
+    }
+
+    #[test]
+    fn test_fuzz_parse_directions() {
+        Fuzzer::new("parse_directions")
+            .seed_input("forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2\n")
+            .run(|bytes| parse_directions(bytes));
+    }
+
+    #[test]
+    fn test_fuzz_moves() {
+        Fuzzer::new("moves")
+            .seed_input("up 1\nforward 5\ndown 5\nforward 8\nup 3\n")
+            .seed_input("down 4294967295\ndown 4294967295\nforward 4294967295\nforward 4294967295\n")
+            .run(|bytes| {
+                let directions = parse_directions(bytes)?;
+                let mut location = Location::default();
+                location.move_to(&directions)?;
+                location.product();
+                let mut location = Location::default();
+                location.aim_and_move(&directions)?;
+                Ok::<_, String>(location.product())
+            });
+    }
 }
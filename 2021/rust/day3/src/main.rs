@@ -1,16 +1,15 @@
-use std::fs::File;
 use std::io::{self, BufRead};
 use std::path::Path;
 use std::num::ParseIntError;
 
-fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
+fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<Box<dyn io::Read>>>>
 where P: AsRef<Path>, {
-let file = File::open(filename)?;
+let file = advent_vault::open(filename)?;
 Ok(io::BufReader::new(file).lines())
 }
 
 fn file_to_string_vec(filename: impl AsRef<Path>) -> io::Result<Vec<String>> {
-    return io::BufReader::new(File::open(filename)?).lines().collect();
+    return io::BufReader::new(advent_vault::open(filename)?).lines().collect();
 }
 
 fn most_common_nth_bit(n: usize, lines: &Vec<String>) -> char {
@@ -31,7 +30,7 @@ fn least_common_nth_bit(n: usize, lines: &Vec<String>) -> char {
     return '1'
 }
 
-fn power_consuption(lines: io::Lines<io::BufReader<File>>) -> Result<(usize, usize), ParseIntError> {
+fn power_consuption(lines: io::Lines<io::BufReader<Box<dyn io::Read>>>) -> Result<(usize, usize), ParseIntError> {
 let mut g_value = String::default();
 let mut e_value = String::default();
 let mut one_counts: Vec<isize> = Vec::default();
@@ -1,4 +1,3 @@
-use std::fs::File;
 use std::io::{self, BufRead};
 use std::path::Path;
 use std::error::Error;
@@ -68,7 +67,7 @@ fn parse_line(line: &str) -> Result<LineType, ParseLineError> {
 
 pub fn read_elfs(file_name: &str) -> Result<Vec<Vec<i32>>, FileParsingError> {
     let path = Path::new(file_name);
-    let file = File::open(&path).map_err(FileParsingError::IoError)?;
+    let file = advent_vault::open(path).map_err(FileParsingError::IoError)?;
 
     let reader = io::BufReader::new(file);
 
@@ -0,0 +1,178 @@
+use std::collections::BTreeMap;
+use std::io::{self, BufRead};
+use thiserror::Error;
+
+use crate::rps_lib::{Choice, Game, Outcome, ParseLineError, Strategy};
+use crate::strategy_guide::StrategyGuide;
+
+#[derive(Debug, Error)]
+pub enum OptimizeError {
+    #[error("Failed to read input: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Line {line_number}: {source}")]
+    InvalidLine { line_number: usize, source: ParseLineError },
+
+    #[error("Cannot map {tokens} distinct tokens onto {targets} distinct values")]
+    TooManyTokens { tokens: usize, targets: usize },
+}
+
+/// Mapping of the second column that gives player 2 the highest total.
+#[derive(Debug)]
+pub struct BestResponse {
+    pub mapping: Vec<(String, String)>,
+    pub total: i32,
+}
+
+/// Tries every one-to-one mapping of the second-column tokens onto choices
+/// (`Strategy::PartOne`) or outcomes (`Strategy::PartTwo`) and keeps the one
+/// that maximises player 2's total score. The first column is read with `base`.
+///
+/// Ties are resolved in favour of the mapping found first.
+pub fn best_response<R: BufRead>(reader: R, game: &Game, base: &StrategyGuide, strategy: &Strategy) -> Result<BestResponse, OptimizeError> {
+    // Identical rounds only need to be scored once, errors point at the first one
+    let mut rounds: BTreeMap<(String, String), (i32, usize)> = BTreeMap::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let mut iter = line.split_whitespace();
+        match (iter.next(), iter.next(), iter.next()) {
+            (None, _, _) => continue,
+            (Some(p1), Some(p2), None) => rounds.entry((p1.to_owned(), p2.to_owned())).or_insert((0, index + 1)).0 += 1,
+            _ => {
+                let source = base.choices_from_line(game, &line, strategy).err()
+                    .unwrap_or(ParseLineError::InvalidLine("Expected two values".to_owned()));
+                return Err(OptimizeError::InvalidLine { line_number: index + 1, source });
+            }
+        }
+    }
+
+    let mut tokens: Vec<&str> = rounds.keys().map(|(_, p2)| p2.as_str()).collect();
+    tokens.sort();
+    tokens.dedup();
+
+    let targets = match strategy {
+        Strategy::PartOne => game.choice_count(),
+        Strategy::PartTwo => 3,
+    };
+    if tokens.len() > targets {
+        return Err(OptimizeError::TooManyTokens { tokens: tokens.len(), targets });
+    }
+
+    let mut best: Option<BestResponse> = None;
+    for assignment in injections(tokens.len(), targets) {
+        let mut guide = base.clone();
+        let mut mapping = Vec::new();
+        for (token, target) in tokens.iter().zip(&assignment) {
+            match strategy {
+                Strategy::PartOne => {
+                    let choice = Choice(*target);
+                    guide.set_player(token, choice);
+                    mapping.push((token.to_string(), game.name(choice).to_owned()));
+                }
+                Strategy::PartTwo => {
+                    let outcome = [Outcome::Lose, Outcome::Draw, Outcome::Win][*target];
+                    guide.set_outcome(token, outcome);
+                    mapping.push((token.to_string(), outcome.name().to_owned()));
+                }
+            }
+        }
+
+        let mut total = 0;
+        for ((p1, p2), &(count, line_number)) in &rounds {
+            let line = format!("{} {}", p1, p2);
+            let (_, p2_score) = guide.calculate_points_from_line(game, &line, strategy)
+                .map_err(|source| OptimizeError::InvalidLine { line_number, source })?;
+            total += p2_score * count;
+        }
+
+        if best.as_ref().is_none_or(|best| total > best.total) {
+            best = Some(BestResponse { mapping, total });
+        }
+    }
+
+    Ok(best.unwrap_or(BestResponse { mapping: Vec::new(), total: 0 }))
+}
+
+/// All ways to pick `k` distinct values out of `0..n`, in lexicographic order.
+fn injections(k: usize, n: usize) -> Vec<Vec<usize>> {
+    fn extend(current: &mut Vec<usize>, used: &mut Vec<bool>, k: usize, result: &mut Vec<Vec<usize>>) {
+        if current.len() == k {
+            result.push(current.clone());
+            return;
+        }
+        for value in 0..used.len() {
+            if !used[value] {
+                used[value] = true;
+                current.push(value);
+                extend(current, used, k, result);
+                current.pop();
+                used[value] = false;
+            }
+        }
+    }
+
+    let mut result = Vec::new();
+    extend(&mut Vec::with_capacity(k), &mut vec![false; n], k, &mut result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_injections() {
+        assert_eq!(injections(0, 3), vec![Vec::<usize>::new()]);
+        assert_eq!(injections(3, 3).len(), 6);
+        assert_eq!(injections(2, 5).len(), 20);
+        assert_eq!(injections(2, 3)[0], vec![0, 1]);
+    }
+
+    #[test]
+    fn test_best_response_example() {
+        let game = Game::rock_paper_scissors();
+        let input = "A Y\nB X\nC Z\n";
+
+        let best = best_response(Cursor::new(input), &game, &StrategyGuide::default(), &Strategy::PartOne).unwrap();
+        // Every round can be won with a different shape
+        assert_eq!(best.total, (6 + 3) + (6 + 2) + (6 + 1));
+        assert_eq!(best.mapping, vec![
+            ("X".to_owned(), "Scissors".to_owned()),
+            ("Y".to_owned(), "Paper".to_owned()),
+            ("Z".to_owned(), "Rock".to_owned()),
+        ]);
+
+        let best = best_response(Cursor::new(input), &game, &StrategyGuide::default(), &Strategy::PartTwo).unwrap();
+        // Losing to Rock with Scissors keeps the win for Paper and the draw with Scissors
+        assert_eq!(best.total, 9 + 3 + 6);
+        assert_eq!(best.mapping, vec![
+            ("X".to_owned(), "win".to_owned()),
+            ("Y".to_owned(), "lose".to_owned()),
+            ("Z".to_owned(), "draw".to_owned()),
+        ]);
+    }
+
+    #[test]
+    fn test_best_response_errors() {
+        let game = Game::rock_paper_scissors();
+        let too_many = "A V\nA W\nA X\nA Y\n";
+        assert!(matches!(
+            best_response(Cursor::new(too_many), &game, &StrategyGuide::default(), &Strategy::PartOne),
+            Err(OptimizeError::TooManyTokens { tokens: 4, targets: 3 })
+        ));
+        assert!(matches!(
+            best_response(Cursor::new("A X\nA X C\n"), &game, &StrategyGuide::default(), &Strategy::PartOne),
+            Err(OptimizeError::InvalidLine { line_number: 2, .. })
+        ));
+        assert!(matches!(
+            best_response(Cursor::new("A X
+
+B Y
+Q X
+Q X
+"), &game, &StrategyGuide::default(), &Strategy::PartOne),
+            Err(OptimizeError::InvalidLine { line_number: 4, source: ParseLineError::InvalidChoice(_) })
+        ));
+    }
+}
@@ -1,56 +1,135 @@
-use std::fs::File;
+use std::env;
 use std::io::{self, BufRead};
 use std::path::Path;
+use std::process;
 
+mod best_response;
 mod rps_lib;
-use rps_lib::{calculate_points_from_line, Strategy};
+mod strategy_guide;
+mod tournament;
+use best_response::best_response;
+use rps_lib::{Game, Strategy};
+use strategy_guide::StrategyGuide;
+use tournament::{format_standings, round_robin, Entrant};
 
 fn main() {
-    let file_name = "./data.txt";
-    let scores = calculate_scores(&file_name);
+    let args: Vec<String> = env::args().collect();
+    match args.get(1).map(String::as_str) {
+        Some("optimize") => return run_optimize(&args[2..]),
+        Some("tournament") => return run_tournament(&args[2..]),
+        _ => {}
+    }
+
+    let game = game_from_arg(args.get(1));
+    let guide = match args.get(2) {
+        Some(guide_path) => StrategyGuide::from_file(guide_path, &game).expect("Failed to read strategy guide"),
+        None => StrategyGuide::default(),
+    };
+
+    let file_name = args.get(3).map(String::as_str).unwrap_or("./data.txt");
+    let scores = calculate_scores(file_name, &game, &guide);
     println!("Part One: Player 1 total score: {}", scores.0.0);
     println!("Part One: Player 2 total score: {}", scores.0.1);
     println!("Part Two: Player 1 total score: {}", scores.1.0);
     println!("Part Two: Player 2 total score: {}", scores.1.1);
 }
 
-fn calculate_scores(file_name: &str) -> ((i32, i32), (i32, i32)) {
+fn game_from_arg(arg: Option<&String>) -> Game {
+    match arg.map(String::as_str) {
+        None | Some("rps") => Game::rock_paper_scissors(), // Default to the classic game
+        Some("rpsls") => Game::rock_paper_scissors_lizard_spock(),
+        Some(name) => {
+            eprintln!("Unknown game '{}', expected rps or rpsls", name);
+            process::exit(1);
+        }
+    }
+}
+
+// optimize [rps|rpsls] [part1|part2] [input]
+fn run_optimize(args: &[String]) {
+    let game = game_from_arg(args.first());
+    let strategy = match args.get(1).map(String::as_str) {
+        Some("part2") => Strategy::PartTwo,
+        _ => Strategy::PartOne,
+    };
+    let file_name = args.get(2).map(String::as_str).unwrap_or("./data.txt");
+
+    let file = advent_vault::open(file_name).expect("Failed to open input file");
+    let best = best_response(io::BufReader::new(file), &game, &StrategyGuide::default(), &strategy).expect("Failed to optimize");
+    // Printed as a strategy guide so it can be fed back in
+    println!("# Best total score for player 2: {}", best.total);
+    println!("[{}]", match strategy { Strategy::PartOne => "player", Strategy::PartTwo => "outcome" });
+    for (token, target) in &best.mapping {
+        println!("{} = \"{}\"", token, target);
+    }
+}
+
+// tournament [rps|rpsls] [input] [guide[:part2]]...
+fn run_tournament(args: &[String]) {
+    let game = game_from_arg(args.first());
+    let file_name = args.get(1).map(String::as_str).unwrap_or("./data.txt");
+    let lines: Vec<String> = read_lines(file_name).expect("Failed to open input file").map_while(Result::ok).collect();
+
+    let mut entrants: Vec<Entrant> = args.iter().skip(2).map(|arg| {
+        let (path, strategy) = match arg.strip_suffix(":part2") {
+            Some(path) => (path, Strategy::PartTwo),
+            None => (arg.as_str(), Strategy::PartOne),
+        };
+        let guide = StrategyGuide::from_file(path, &game).expect("Failed to read strategy guide");
+        Entrant { name: arg.clone(), guide, strategy }
+    }).collect();
+    if entrants.is_empty() {
+        entrants.push(Entrant { name: "part1".to_owned(), guide: StrategyGuide::default(), strategy: Strategy::PartOne });
+        entrants.push(Entrant { name: "part2".to_owned(), guide: StrategyGuide::default(), strategy: Strategy::PartTwo });
+    }
+
+    let standings = round_robin(&game, &lines, &entrants).expect("Failed to play the tournament");
+    print!("{}", format_standings(&standings));
+}
+
+fn calculate_scores(file_name: &str, game: &Game, guide: &StrategyGuide) -> ((i32, i32), (i32, i32)) {
     let mut p1_total_score_part_one = 0;
     let mut p2_total_score_part_one = 0;
     let mut p1_total_score_part_two = 0;
     let mut p2_total_score_part_two = 0;
 
     if let Ok(lines) = read_lines(file_name) {
-        for line in lines {
-            if let Ok(line) = line {
-                let (p1_score_part_one, p2_score_part_one) = calculate_points_from_line(&line, &Strategy::PartOne).expect("Error calculating points");
-                p1_total_score_part_one += p1_score_part_one;
-                p2_total_score_part_one += p2_score_part_one;
-                let (p1_score_part_two, p2_score_part_two) = calculate_points_from_line(&line, &Strategy::PartTwo).expect("Error calculating points");
-                p1_total_score_part_two += p1_score_part_two;
-                p2_total_score_part_two += p2_score_part_two;
-            }
+        for line in lines.map_while(Result::ok) {
+            let (p1_score_part_one, p2_score_part_one) = guide.calculate_points_from_line(game, &line, &Strategy::PartOne).expect("Error calculating points");
+            p1_total_score_part_one += p1_score_part_one;
+            p2_total_score_part_one += p2_score_part_one;
+            let (p1_score_part_two, p2_score_part_two) = guide.calculate_points_from_line(game, &line, &Strategy::PartTwo).expect("Error calculating points");
+            p1_total_score_part_two += p1_score_part_two;
+            p2_total_score_part_two += p2_score_part_two;
         }
     }
 
     ((p1_total_score_part_one, p2_total_score_part_one), (p1_total_score_part_two, p2_total_score_part_two))
 }
-
 // read lines from file
-fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
+fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<Box<dyn io::Read>>>>
 where
     P: AsRef<Path>,
 {
-    let file = File::open(filename)?;
+    let file = advent_vault::open(filename)?;
     Ok(io::BufReader::new(file).lines())
 }
 
 #[test]
 fn test_calculate_scores() {
     let file_name = "./test_data/example_data.txt";
-    let ((p1_total_score_part_one, p2_total_score_part_one), (p1_total_score_part_two, p2_total_score_part_two)) = calculate_scores(file_name);
+    let ((p1_total_score_part_one, p2_total_score_part_one), (p1_total_score_part_two, p2_total_score_part_two)) = calculate_scores(file_name, &Game::rock_paper_scissors(), &StrategyGuide::default());
     assert_eq!(p1_total_score_part_one, 15);
     assert_eq!(p2_total_score_part_one, 15);
     assert_eq!(p1_total_score_part_two, 15);
     assert_eq!(p2_total_score_part_two, 12);
 }
+
+#[test]
+fn test_calculate_scores_with_word_guide() {
+    let game = Game::rock_paper_scissors();
+    let guide = StrategyGuide::from_file("./test_data/word_guide.toml", &game).expect("Failed to read guide");
+    let ((_, p2_total_score_part_one), (_, p2_total_score_part_two)) = calculate_scores("./test_data/example_data_words.txt", &game, &guide);
+    assert_eq!(p2_total_score_part_one, 15);
+    assert_eq!(p2_total_score_part_two, 12);
+}
@@ -1,5 +1,9 @@
 use thiserror::Error;
 
+#[cfg(test)]
+use crate::strategy_guide::StrategyGuide;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Strategy {
     PartOne,
     PartTwo
@@ -20,262 +24,515 @@ pub enum ParseLineError {
     InvalidChoice(#[from] InvalidChoiceError),
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
-pub enum Choice {
-    Rock,
-    Paper,
-    Scissors,
+#[derive(Debug, Error)]
+pub enum InvalidGameError {
+    #[error("A cyclic game needs an odd number of at least three choices, got {0}")]
+    InvalidChoiceCount(usize),
+
+    #[error("Expected {expected} shape scores, got {actual}")]
+    ShapeScoreCountMismatch { expected: usize, actual: usize },
+
+    #[error("Duplicate choice name: '{0}'")]
+    DuplicateChoice(String),
 }
 
-impl Choice {
-    pub fn wins(&self) -> Result<Self, InvalidChoiceError> {
-        match self {
-            Choice::Rock => Ok(Choice::Scissors),
-            Choice::Paper => Ok(Choice::Rock),
-            Choice::Scissors => Ok(Choice::Paper)
-        }
-    }
-    pub fn draws(&self) -> Result<Self, InvalidChoiceError> {
-        Ok(self.clone())
-    }
-    pub fn loses(&self) -> Result<Self, InvalidChoiceError> {
-        match self {
-            Choice::Rock => Ok(Choice::Paper),
-            Choice::Paper => Ok(Choice::Scissors),
-            Choice::Scissors => Ok(Choice::Rock)
-        }
-    }
+/// Index of a choice in the cycle of a [`Game`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Choice(pub usize);
 
+/// Result of a round from the point of view of the player being scored.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Outcome {
+    Lose,
+    Draw,
+    Win,
+}
+
+impl Outcome {
     pub fn from_str(c: &str) -> Result<Self, InvalidChoiceError> {
         match c {
-            "A" => Ok(Choice::Rock),
-            "B" => Ok(Choice::Paper),
-            "C" => Ok(Choice::Scissors),
-            "X" => Ok(Choice::Rock),
-            "Y" => Ok(Choice::Paper),
-            "Z" => Ok(Choice::Scissors),
+            "X" => Ok(Outcome::Lose),
+            "Y" => Ok(Outcome::Draw),
+            "Z" => Ok(Outcome::Win),
             _ => Err(InvalidChoiceError::InvalidChoice(format!(
                 "Invalid choice: '{}'",
                 c
             ))),
         }
     }
-    pub fn from_desired_result(previous: &Choice, desired_result: &str) -> Result<Self, InvalidChoiceError> {
-        match desired_result {
-            "X" => previous.wins(),
-            "Y" => previous.draws(),
-            "Z" => previous.loses(),
-            _ => Err(InvalidChoiceError::InvalidChoice(format!(
-                "Invalid choice: '{}'",
-                desired_result
-            )))
+
+    /// The same round seen from the other player's side.
+    pub fn reverse(&self) -> Self {
+        match self {
+            Outcome::Lose => Outcome::Win,
+            Outcome::Draw => Outcome::Draw,
+            Outcome::Win => Outcome::Lose,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Outcome::Lose => "lose",
+            Outcome::Draw => "draw",
+            Outcome::Win => "win",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "lose" => Some(Outcome::Lose),
+            "draw" => Some(Outcome::Draw),
+            "win" => Some(Outcome::Win),
+            _ => None,
         }
     }
 }
 
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct OutcomeScores {
+    pub lose: i32,
+    pub draw: i32,
+    pub win: i32,
+}
 
-pub type Score = (i32, i32);
+impl Default for OutcomeScores {
+    fn default() -> Self {
+        OutcomeScores { lose: 0, draw: 3, win: 6 }
+    }
+}
+
+impl OutcomeScores {
+    pub fn score(&self, outcome: Outcome) -> i32 {
+        match outcome {
+            Outcome::Lose => self.lose,
+            Outcome::Draw => self.draw,
+            Outcome::Win => self.win,
+        }
+    }
+}
+
+/// An odd-N cyclic hand game where every choice beats the (N-1)/2 choices
+/// that follow it in the cycle and loses to the (N-1)/2 choices before it.
+///
+/// Choices can be written either by name (case-insensitive) or by letter.
+/// Letters are handed out in order of shape score, starting from `A` for the
+/// first column and ending at `Z` for the second one, so the classic game keeps
+/// its A/B/C and X/Y/Z encoding.
+#[derive(Debug, Clone)]
+pub struct Game {
+    names: Vec<String>,
+    shape_scores: Vec<i32>,
+    outcome_scores: OutcomeScores,
+    by_score: Vec<Choice>,
+}
+
+impl Game {
+    pub fn new(names: &[&str], shape_scores: &[i32], outcome_scores: OutcomeScores) -> Result<Self, InvalidGameError> {
+        let count = names.len();
+        if count < 3 || count.is_multiple_of(2) {
+            return Err(InvalidGameError::InvalidChoiceCount(count));
+        }
+        if shape_scores.len() != count {
+            return Err(InvalidGameError::ShapeScoreCountMismatch { expected: count, actual: shape_scores.len() });
+        }
+        for (i, name) in names.iter().enumerate() {
+            if names[..i].iter().any(|other| other.eq_ignore_ascii_case(name)) {
+                return Err(InvalidGameError::DuplicateChoice(name.to_string()));
+            }
+        }
+
+        let mut by_score: Vec<Choice> = (0..count).map(Choice).collect();
+        by_score.sort_by_key(|choice| shape_scores[choice.0]);
+
+        Ok(Game {
+            names: names.iter().map(|name| name.to_string()).collect(),
+            shape_scores: shape_scores.to_vec(),
+            outcome_scores,
+            by_score,
+        })
+    }
+
+    /// Rock beats Scissors, Scissors beats Paper and Paper beats Rock.
+    pub fn rock_paper_scissors() -> Self {
+        Game::new(&["Rock", "Scissors", "Paper"], &[1, 3, 2], OutcomeScores::default())
+            .expect("Rock Paper Scissors is a valid game")
+    }
+
+    /// Rock Paper Scissors Lizard Spock, ordered so that each choice beats the two after it.
+    pub fn rock_paper_scissors_lizard_spock() -> Self {
+        Game::new(
+            &["Rock", "Scissors", "Lizard", "Paper", "Spock"],
+            &[1, 3, 4, 2, 5],
+            OutcomeScores::default(),
+        )
+        .expect("Rock Paper Scissors Lizard Spock is a valid game")
+    }
+
+    pub fn choice_count(&self) -> usize {
+        self.names.len()
+    }
 
-pub fn calculate_points_from_line(line: &str, strategy: &Strategy) -> Result<Score, ParseLineError> {
-    let mut iter = line.split_whitespace();
+    pub fn name(&self, choice: Choice) -> &str {
+        &self.names[choice.0]
+    }
 
-    let p1_choice = Choice::from_str(iter.next().ok_or(ParseLineError::InvalidLine("Missing player 1 choice".to_owned()))?)?;
-    let p2_choice = match strategy {
-        Strategy::PartOne => Choice::from_str(iter.next().ok_or(ParseLineError::InvalidLine("Missing player 2 choice".to_owned()))?)?,
-        Strategy::PartTwo => Choice::from_desired_result(&p1_choice, iter.next().ok_or(ParseLineError::InvalidLine("Missing player 2 choice".to_owned()))?)?,
-    };
+    pub fn shape_score(&self, choice: Choice) -> i32 {
+        self.shape_scores[choice.0]
+    }
 
-    if let Some(_) = iter.next() {
-        return Err(ParseLineError::InvalidLine("Unexpected third value".to_owned()));
+    pub fn choice_by_name(&self, name: &str) -> Option<Choice> {
+        self.names
+            .iter()
+            .position(|candidate| candidate.eq_ignore_ascii_case(name))
+            .map(Choice)
     }
 
+    pub fn choice_from_token(&self, token: &str) -> Result<Choice, InvalidChoiceError> {
+        let count = self.choice_count();
+        // Letters only cover games small enough to give both columns their own range
+        let rank = match token.as_bytes() {
+            [letter @ b'A'..=b'Z'] if count <= 13 => {
+                let index = usize::from(letter - b'A');
+                if index < count {
+                    Some(index)
+                } else if index >= 26 - count {
+                    Some(index - (26 - count))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
 
-    let (p1_score, p2_score) = match (&p1_choice, &p2_choice) {
-        (Choice::Rock, Choice::Scissors)
-        | (Choice::Paper, Choice::Rock)
-        | (Choice::Scissors, Choice::Paper) => (6, 0),
-        (Choice::Scissors, Choice::Rock)
-        | (Choice::Rock, Choice::Paper)
-        | (Choice::Paper, Choice::Scissors) => (0, 6),
-        _ => (3, 3),
-    };
-
-    let p1_selection_score = match &p1_choice {
-        Choice::Rock => 1,
-        Choice::Paper => 2,
-        Choice::Scissors => 3,
-    };
-
-    let p2_selection_score = match &p2_choice {
-        Choice::Rock => 1,
-        Choice::Paper => 2,
-        Choice::Scissors => 3,
-    };
-
-    let p1_total_score = p1_score + p1_selection_score;
-    let p2_total_score = p2_score + p2_selection_score;
-
-    Ok((p1_total_score, p2_total_score))
+        rank.map(|rank| self.by_score[rank])
+            .or_else(|| self.choice_by_name(token))
+            .ok_or_else(|| InvalidChoiceError::InvalidChoice(format!(
+                "Invalid choice: '{}'",
+                token
+            )))
+    }
+
+    pub fn beats(&self, choice: Choice, other: Choice) -> bool {
+        let count = self.choice_count();
+        let distance = (other.0 + count - choice.0) % count;
+        distance >= 1 && distance <= (count - 1) / 2
+    }
+
+    /// Outcome of the round for `choice` when played against `other`.
+    pub fn outcome(&self, choice: Choice, other: Choice) -> Outcome {
+        if choice == other {
+            Outcome::Draw
+        } else if self.beats(choice, other) {
+            Outcome::Win
+        } else {
+            Outcome::Lose
+        }
+    }
+
+    /// Choice that ends the round against `opponent` with `desired` for the responder.
+    ///
+    /// When several choices would do, the neighbour of `opponent` in the cycle is picked.
+    pub fn respond(&self, opponent: Choice, desired: Outcome) -> Choice {
+        let count = self.choice_count();
+        match desired {
+            Outcome::Lose => Choice((opponent.0 + 1) % count),
+            Outcome::Draw => opponent,
+            Outcome::Win => Choice((opponent.0 + count - 1) % count),
+        }
+    }
+
+    pub fn score(&self, p1_choice: Choice, p2_choice: Choice) -> Score {
+        let p1_score = self.outcome_scores.score(self.outcome(p1_choice, p2_choice)) + self.shape_score(p1_choice);
+        let p2_score = self.outcome_scores.score(self.outcome(p2_choice, p1_choice)) + self.shape_score(p2_choice);
+
+        (p1_score, p2_score)
+    }
+}
+
+
+pub type Score = (i32, i32);
+
+/// Scores a line of the puzzle's own A/B/C X/Y/Z encoding.
+#[cfg(test)]
+fn points(game: &Game, line: &str, strategy: &Strategy) -> Result<Score, ParseLineError> {
+    StrategyGuide::default().calculate_points_from_line(game, line, strategy)
 }
 
 #[test]
-fn test_from_str() {
-    assert!(matches!(Choice::from_str("A"), Ok(Choice::Rock)));
-    assert!(matches!(Choice::from_str("B"), Ok(Choice::Paper)));
-    assert!(matches!(Choice::from_str("C"), Ok(Choice::Scissors)));
-    assert!(matches!(Choice::from_str("X"), Ok(Choice::Rock)));
-    assert!(matches!(Choice::from_str("Y"), Ok(Choice::Paper)));
-    assert!(matches!(Choice::from_str("Z"), Ok(Choice::Scissors)));
-    assert!(matches!(
-        Choice::from_str("D"),
-        Err(InvalidChoiceError::InvalidChoice(_))
-    ));
+fn test_choice_from_large_games() {
+    for count in [129, 257] {
+        let names: Vec<String> = (0..count).map(|i| format!("c{}", i)).collect();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        let game = Game::new(&names, &vec![1; count], OutcomeScores::default()).unwrap();
+        assert!(game.choice_from_token("A").is_err());
+        assert_eq!(game.choice_from_token("c5").unwrap(), Choice(5));
+    }
 }
 
+/// The classic game under the original Rock/Paper/Scissors API, so its tests
+/// keep running as the N=3 case of [`Game`].
+#[cfg(test)]
+mod classic {
+    use super::{points, Game, InvalidChoiceError, Outcome, ParseLineError, Score, Strategy};
+
+    enum Choice {
+        Rock,
+        Paper,
+        Scissors,
+    }
+
+    impl Choice {
+        fn from_game(game: &Game, choice: super::Choice) -> Self {
+            match game.name(choice) {
+                "Rock" => Choice::Rock,
+                "Paper" => Choice::Paper,
+                _ => Choice::Scissors,
+            }
+        }
+
+        fn name(&self) -> &'static str {
+            match self {
+                Choice::Rock => "Rock",
+                Choice::Paper => "Paper",
+                Choice::Scissors => "Scissors",
+            }
+        }
+
+        fn from_str(c: &str) -> Result<Self, InvalidChoiceError> {
+            let game = Game::rock_paper_scissors();
+            Ok(Choice::from_game(&game, game.choice_from_token(c)?))
+        }
+
+        fn from_desired_result(previous: &Choice, desired_result: &str) -> Result<Self, InvalidChoiceError> {
+            let game = Game::rock_paper_scissors();
+            let previous = game.choice_by_name(previous.name()).expect("Rock, Paper and Scissors are all in the game");
+            Ok(Choice::from_game(&game, game.respond(previous, Outcome::from_str(desired_result)?)))
+        }
+    }
+
+    fn calculate_points_from_line(line: &str, strategy: &Strategy) -> Result<Score, ParseLineError> {
+        points(&Game::rock_paper_scissors(), line, strategy)
+    }
+
     #[test]
-fn test_from_desired_result() {
-    assert!(matches!(
-        Choice::from_desired_result(&Choice::Rock, "X"),
-        Ok(Choice::Scissors)
-    ));
-    assert!(matches!(
-        Choice::from_desired_result(&Choice::Rock, "Y"),
-        Ok(Choice::Rock)
-    ));
-    assert!(matches!(
-        Choice::from_desired_result(&Choice::Rock, "Z"),
-        Ok(Choice::Paper)
-    ));
-    assert!(matches!(
-        Choice::from_desired_result(&Choice::Paper, "X"),
-        Ok(Choice::Rock)
-    ));
-    assert!(matches!(
-        Choice::from_desired_result(&Choice::Paper, "Y"),
-        Ok(Choice::Paper)
-    ));
-    assert!(matches!(
-        Choice::from_desired_result(&Choice::Paper, "Z"),
-        Ok(Choice::Scissors)
-    ));
+    fn test_from_str() {
+        assert!(matches!(Choice::from_str("A"), Ok(Choice::Rock)));
+        assert!(matches!(Choice::from_str("B"), Ok(Choice::Paper)));
+        assert!(matches!(Choice::from_str("C"), Ok(Choice::Scissors)));
+        assert!(matches!(Choice::from_str("X"), Ok(Choice::Rock)));
+        assert!(matches!(Choice::from_str("Y"), Ok(Choice::Paper)));
+        assert!(matches!(Choice::from_str("Z"), Ok(Choice::Scissors)));
+        assert!(matches!(
+            Choice::from_str("D"),
+            Err(InvalidChoiceError::InvalidChoice(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_desired_result() {
+        assert!(matches!(
+            Choice::from_desired_result(&Choice::Rock, "X"),
+            Ok(Choice::Scissors)
+        ));
+        assert!(matches!(
+            Choice::from_desired_result(&Choice::Rock, "Y"),
+            Ok(Choice::Rock)
+        ));
+        assert!(matches!(
+            Choice::from_desired_result(&Choice::Rock, "Z"),
+            Ok(Choice::Paper)
+        ));
+        assert!(matches!(
+            Choice::from_desired_result(&Choice::Paper, "X"),
+            Ok(Choice::Rock)
+        ));
+        assert!(matches!(
+            Choice::from_desired_result(&Choice::Paper, "Y"),
+            Ok(Choice::Paper)
+        ));
+        assert!(matches!(
+            Choice::from_desired_result(&Choice::Paper, "Z"),
+            Ok(Choice::Scissors)
+        ));
+        assert!(matches!(
+            Choice::from_desired_result(&Choice::Scissors, "X"),
+            Ok(Choice::Paper)
+        ));
+        assert!(matches!(
+            Choice::from_desired_result(&Choice::Scissors, "Y"),
+            Ok(Choice::Scissors)
+        ));
+        assert!(matches!(
+            Choice::from_desired_result(&Choice::Scissors, "Z"),
+            Ok(Choice::Rock)
+        ));
+        assert!(matches!(
+            Choice::from_desired_result(&Choice::Rock, "invalid"),
+            Err(InvalidChoiceError::InvalidChoice(_))
+        ));
+    }
+
+    #[test]
+    fn test_calculate_points_from_line_part_a() {
+        assert!(
+            matches!(calculate_points_from_line("A X", &Strategy::PartOne), Ok((4, 4))),
+            "Expected 'A X' (Rock Rock) to result in a draw (4-4)"
+        );
+        assert!(
+            matches!(calculate_points_from_line("A Y", &Strategy::PartOne), Ok((1, 8))),
+            "Expected 'A Y' (Rock Paper) to result in a win for player 2 (1-8)"
+        );
+        assert!(
+            matches!(calculate_points_from_line("A Z", &Strategy::PartOne), Ok((7, 3))),
+            "Expected 'A Z' (Rock Scissors) to result in a win for player 1 (7-3)"
+        );
+        assert!(
+            matches!(calculate_points_from_line("B X", &Strategy::PartOne), Ok((8, 1))),
+            "Expected 'B X' (Paper Rock) to result in a win for player 1 (8-1)"
+        );
+        assert!(
+            matches!(calculate_points_from_line("B Y", &Strategy::PartOne), Ok((5, 5))),
+            "Expected 'B Y' (Paper Paper) to result in a draw (5-5)"
+        );
+        assert!(
+            matches!(calculate_points_from_line("B Z", &Strategy::PartOne), Ok((2, 9))),
+            "Expected 'B Z' (Paper Scissors) to result in a win for player 2 (2-9)"
+        );
+        assert!(
+            matches!(calculate_points_from_line("C X", &Strategy::PartOne), Ok((3, 7))),
+            "Expected 'C X' (Scissors Rock) to result in a win for player 2 (3-7)"
+        );
+        assert!(
+            matches!(calculate_points_from_line("C Y", &Strategy::PartOne), Ok((9, 2))),
+            "Expected 'C Y' (Scissors Paper) to result in a win for player 1 (9-2)"
+        );
+        assert!(
+            matches!(calculate_points_from_line("C Z", &Strategy::PartOne), Ok((6, 6))),
+            "Expected 'C Z' (Scissors Scissors) to result in a draw (6-6)"
+        );
+        assert!(
+            matches!(calculate_points_from_line("invalid input", &Strategy::PartOne), Err(ParseLineError::InvalidChoice(_))),
+            "Expected 'invalid input' to result in an InvalidChoice error"
+        );
+        assert!(
+            matches!(calculate_points_from_line("A B C", &Strategy::PartOne), Err(ParseLineError::InvalidLine(_))),
+            "Expected 'A B C' to result in an InvalidLine error"
+        );
+        assert!(
+            matches!(calculate_points_from_line("_", &Strategy::PartOne), Err(ParseLineError::InvalidChoice(_))),
+            "Expected '_' to result in an InvalidLine error"
+        );
+    }
+
+    #[test]
+    fn test_calculate_points_from_line_part_b() {
+        assert!(
+            matches!(calculate_points_from_line("A X", &Strategy::PartTwo), Ok((7, 3))),
+            "Expected 'A X' (Rock Lose/Scissors) to result in a win for player 1 (7-3)"
+        );
+        assert!(
+            matches!(calculate_points_from_line("A Y", &Strategy::PartTwo), Ok((4, 4))),
+            "Expected 'A Y' (Rock Draw/Rock) to result in a draw (4-4)"
+        );
+        assert!(
+            matches!(calculate_points_from_line("A Z", &Strategy::PartTwo), Ok((1, 8))),
+            "Expected 'A Z' (Rock Win/Paper) to result in a win for player 2 (1-8)"
+        );
+        assert!(
+            matches!(calculate_points_from_line("B X", &Strategy::PartTwo), Ok((8, 1))),
+            "Expected 'B X' (Paper Lose/Rock) to result in a win for player 1 (8-1)"
+        );
+        assert!(
+            matches!(calculate_points_from_line("B Y", &Strategy::PartTwo), Ok((5, 5))),
+            "Expected 'B Y' (Paper Draw/Paper) to result in a draw (5-5)"
+        );
+        assert!(
+            matches!(calculate_points_from_line("B Z", &Strategy::PartTwo), Ok((2, 9))),
+            "Expected 'B Z' (Paper Win/Scissors) to result in a win for player 2 (2-9)"
+        );
+        assert!(
+            matches!(calculate_points_from_line("C X", &Strategy::PartTwo), Ok((9, 2))),
+            "Expected 'C X' (Scissors Lose/Paper) to result in a win for player 1 (9-2)"
+        );
+        assert!(
+            matches!(calculate_points_from_line("C Y", &Strategy::PartTwo), Ok((6, 6))),
+            "Expected 'C Y' (Scissors Draw/Scissors) to result in a draw (6-6)"
+        );
+        assert!(
+            matches!(calculate_points_from_line("C Z", &Strategy::PartTwo), Ok((3, 7))),
+            "Expected 'C Z' (Scissors Win/Rock) to result in a win for player 2 (3-7)"
+        );
+        assert!(
+            matches!(calculate_points_from_line("invalid input", &Strategy::PartTwo), Err(ParseLineError::InvalidChoice(_))),
+            "Expected 'invalid input' to result in an InvalidChoice error"
+        );
+        assert!(
+            matches!(calculate_points_from_line("A X C", &Strategy::PartTwo), Err(ParseLineError::InvalidLine(_))),
+            "Expected 'A B C' to result in an InvalidLine error"
+        );
+        assert!(
+            matches!(calculate_points_from_line("_", &Strategy::PartTwo), Err(ParseLineError::InvalidChoice(_))),
+            "Expected '_' to result in an InvalidLine error"
+        );
+    }
+}
+
+#[test]
+fn test_invalid_games() {
     assert!(matches!(
-        Choice::from_desired_result(&Choice::Scissors, "X"),
-        Ok(Choice::Paper)
+        Game::new(&["Rock", "Paper"], &[1, 2], OutcomeScores::default()),
+        Err(InvalidGameError::InvalidChoiceCount(2))
     ));
     assert!(matches!(
-        Choice::from_desired_result(&Choice::Scissors, "Y"),
-        Ok(Choice::Scissors)
+        Game::new(&["A", "B", "C", "D"], &[1, 2, 3, 4], OutcomeScores::default()),
+        Err(InvalidGameError::InvalidChoiceCount(4))
     ));
     assert!(matches!(
-        Choice::from_desired_result(&Choice::Scissors, "Z"),
-        Ok(Choice::Rock)
+        Game::new(&["A", "B", "C"], &[1, 2], OutcomeScores::default()),
+        Err(InvalidGameError::ShapeScoreCountMismatch { expected: 3, actual: 2 })
     ));
     assert!(matches!(
-        Choice::from_desired_result(&Choice::Rock, "invalid"),
-        Err(InvalidChoiceError::InvalidChoice(_))
+        Game::new(&["Rock", "Paper", "rock"], &[1, 2, 3], OutcomeScores::default()),
+        Err(InvalidGameError::DuplicateChoice(_))
     ));
 }
 
 #[test]
-fn test_calculate_points_from_line_part_a() {
-    assert!(
-        matches!(calculate_points_from_line("A X", &Strategy::PartOne), Ok((4, 4))),
-        "Expected 'A X' (Rock Rock) to result in a draw (4-4)"
-    );
-    assert!(
-        matches!(calculate_points_from_line("A Y", &Strategy::PartOne), Ok((1, 8))),
-        "Expected 'A Y' (Rock Paper) to result in a win for player 2 (1-8)"
-    );
-    assert!(
-        matches!(calculate_points_from_line("A Z", &Strategy::PartOne), Ok((7, 3))),
-        "Expected 'A Z' (Rock Scissors) to result in a win for player 1 (7-3)"
-    );
-    assert!(
-        matches!(calculate_points_from_line("B X", &Strategy::PartOne), Ok((8, 1))),
-        "Expected 'B X' (Paper Rock) to result in a win for player 1 (8-1)"
-    );
-    assert!(
-        matches!(calculate_points_from_line("B Y", &Strategy::PartOne), Ok((5, 5))),
-        "Expected 'B Y' (Paper Paper) to result in a draw (5-5)"
-    );
-    assert!(
-        matches!(calculate_points_from_line("B Z", &Strategy::PartOne), Ok((2, 9))),
-        "Expected 'B Z' (Paper Scissors) to result in a win for player 2 (2-9)"
-    );
-    assert!(
-        matches!(calculate_points_from_line("C X", &Strategy::PartOne), Ok((3, 7))),
-        "Expected 'C X' (Scissors Rock) to result in a win for player 2 (3-7)"
-    );
-    assert!(
-        matches!(calculate_points_from_line("C Y", &Strategy::PartOne), Ok((9, 2))),
-        "Expected 'C Y' (Scissors Paper) to result in a win for player 1 (9-2)"
-    );
-    assert!(
-        matches!(calculate_points_from_line("C Z", &Strategy::PartOne), Ok((6, 6))),
-        "Expected 'C Z' (Scissors Scissors) to result in a draw (6-6)"
-    );
-    assert!(
-        matches!(calculate_points_from_line("invalid input", &Strategy::PartOne), Err(ParseLineError::InvalidChoice(_))),
-        "Expected 'invalid input' to result in an InvalidChoice error"
-    );
-    assert!(
-        matches!(calculate_points_from_line("A B C", &Strategy::PartOne), Err(ParseLineError::InvalidLine(_))),
-        "Expected 'A B C' to result in an InvalidLine error"
-    );
-    assert!(
-        matches!(calculate_points_from_line("_", &Strategy::PartOne), Err(ParseLineError::InvalidChoice(_))),
-        "Expected '_' to result in an InvalidLine error"
-    );
+fn test_rock_paper_scissors_lizard_spock() {
+    let game = Game::rock_paper_scissors_lizard_spock();
+    let choice = |name| game.choice_by_name(name).unwrap();
+
+    let wins = [
+        ("Scissors", "Paper"), ("Paper", "Rock"), ("Rock", "Lizard"), ("Lizard", "Spock"), ("Spock", "Scissors"),
+        ("Scissors", "Lizard"), ("Lizard", "Paper"), ("Paper", "Spock"), ("Spock", "Rock"), ("Rock", "Scissors"),
+    ];
+    for (winner, loser) in wins {
+        assert!(game.beats(choice(winner), choice(loser)), "{} should beat {}", winner, loser);
+        assert!(!game.beats(choice(loser), choice(winner)), "{} should not beat {}", loser, winner);
+        assert_eq!(game.outcome(choice(loser), choice(winner)), Outcome::Lose);
+    }
+
+    assert_eq!(game.choice_from_token("A").unwrap(), choice("Rock"));
+    assert_eq!(game.choice_from_token("E").unwrap(), choice("Spock"));
+    assert_eq!(game.choice_from_token("V").unwrap(), choice("Rock"));
+    assert_eq!(game.choice_from_token("Y").unwrap(), choice("Lizard"));
+    assert_eq!(game.choice_from_token("spock").unwrap(), choice("Spock"));
+    assert!(game.choice_from_token("F").is_err());
+
+    // Spock (5) vaporizes Rock (1)
+    assert!(matches!(points(&game, "A Z", &Strategy::PartOne), Ok((1, 11))));
+    // Player 2 has to lose against Lizard and picks Paper
+    assert!(matches!(points(&game, "D X", &Strategy::PartTwo), Ok((10, 2))));
+    for opponent in (0..game.choice_count()).map(Choice) {
+        for desired in [Outcome::Lose, Outcome::Draw, Outcome::Win] {
+            assert_eq!(game.outcome(game.respond(opponent, desired), opponent), desired);
+        }
+    }
 }
 
 #[test]
-fn test_calculate_points_from_line_part_b() {
-    assert!(
-        matches!(calculate_points_from_line("A X", &Strategy::PartTwo), Ok((7, 3))),
-        "Expected 'A X' (Rock Lose/Scissors) to result in a win for player 1 (7-3)"
-    );
-    assert!(
-        matches!(calculate_points_from_line("A Y", &Strategy::PartTwo), Ok((4, 4))),
-        "Expected 'A Y' (Rock Draw/Rock) to result in a draw (4-4)"
-    );
-    assert!(
-        matches!(calculate_points_from_line("A Z", &Strategy::PartTwo), Ok((1, 8))),
-        "Expected 'A Z' (Rock Win/Paper) to result in a win for player 2 (1-8)"
-    );
-    assert!(
-        matches!(calculate_points_from_line("B X", &Strategy::PartTwo), Ok((8, 1))),
-        "Expected 'B X' (Paper Lose/Rock) to result in a win for player 1 (8-1)"
-    );
-    assert!(
-        matches!(calculate_points_from_line("B Y", &Strategy::PartTwo), Ok((5, 5))),
-        "Expected 'B Y' (Paper Draw/Paper) to result in a draw (5-5)"
-    );
-    assert!(
-        matches!(calculate_points_from_line("B Z", &Strategy::PartTwo), Ok((2, 9))),
-        "Expected 'B Z' (Paper Win/Scissors) to result in a win for player 2 (2-9)"
-    );
-    assert!(
-        matches!(calculate_points_from_line("C X", &Strategy::PartTwo), Ok((9, 2))),
-        "Expected 'C X' (Scissors Lose/Paper) to result in a win for player 1 (9-2)"
-    );
-    assert!(
-        matches!(calculate_points_from_line("C Y", &Strategy::PartTwo), Ok((6, 6))),
-        "Expected 'C Y' (Scissors Draw/Scissors) to result in a draw (6-6)"
-    );
-    assert!(
-        matches!(calculate_points_from_line("C Z", &Strategy::PartTwo), Ok((3, 7))),
-        "Expected 'C Z' (Scissors Win/Rock) to result in a win for player 2 (3-7)"
-    );
-    assert!(
-        matches!(calculate_points_from_line("invalid input", &Strategy::PartTwo), Err(ParseLineError::InvalidChoice(_))),
-        "Expected 'invalid input' to result in an InvalidChoice error"
-    );
-    assert!(
-        matches!(calculate_points_from_line("A X C", &Strategy::PartTwo), Err(ParseLineError::InvalidLine(_))),
-        "Expected 'A B C' to result in an InvalidLine error"
-    );
-    assert!(
-        matches!(calculate_points_from_line("_", &Strategy::PartTwo), Err(ParseLineError::InvalidChoice(_))),
-        "Expected '_' to result in an InvalidLine error"
-    );
+fn test_custom_outcome_scores() {
+    let game = Game::new(&["Rock", "Scissors", "Paper"], &[10, 30, 20], OutcomeScores { lose: -1, draw: 0, win: 1 }).unwrap();
+    assert!(matches!(points(&game, "A Y", &Strategy::PartOne), Ok((9, 21))));
+    assert!(matches!(points(&game, "C Z", &Strategy::PartOne), Ok((30, 30))));
 }
@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use thiserror::Error;
+
+use crate::rps_lib::{Choice, Game, InvalidChoiceError, Outcome, ParseLineError, Score, Strategy};
+
+#[derive(Debug, Error)]
+pub enum GuideError {
+    #[error("Failed to read strategy guide: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Line {line_number}: {message}")]
+    InvalidLine { line_number: usize, message: String },
+}
+
+/// Maps the tokens of a strategy guide to choices and outcomes.
+///
+/// A guide is read from a small TOML-like file with `[opponent]`, `[player]`
+/// and `[outcome]` sections, or from plain `section.token = value` lines:
+///
+/// ```text
+/// [opponent]
+/// rock = "Rock"
+///
+/// outcome.lose = "lose"
+/// ```
+///
+/// Columns without any mapping fall back to [`Game::choice_from_token`] and
+/// [`Outcome::from_str`], so the default guide reads the puzzle's own encoding.
+#[derive(Debug, Clone, Default)]
+pub struct StrategyGuide {
+    opponent: HashMap<String, Choice>,
+    player: HashMap<String, Choice>,
+    outcome: HashMap<String, Outcome>,
+}
+
+impl StrategyGuide {
+    pub fn from_file<P: AsRef<Path>>(path: P, game: &Game) -> Result<Self, GuideError> {
+        let content = fs::read_to_string(path)?;
+        StrategyGuide::parse(&content, game)
+    }
+
+    pub fn parse(content: &str, game: &Game) -> Result<Self, GuideError> {
+        let mut guide = StrategyGuide::default();
+        let mut section: Option<String> = None;
+
+        for (index, raw_line) in content.lines().enumerate() {
+            let line_number = index + 1;
+            let invalid = |message: String| GuideError::InvalidLine { line_number, message };
+
+            let line = strip_comment(raw_line).trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(header) = line.strip_prefix('[') {
+                let header = header.strip_suffix(']').ok_or_else(|| invalid(format!("Unterminated section header '{}'", line)))?;
+                section = Some(header.trim().to_owned());
+                continue;
+            }
+
+            let (key, value) = line.split_once('=').ok_or_else(|| invalid(format!("Expected 'token = value', got '{}'", line)))?;
+            let key = unquote(key);
+            let value = unquote(value);
+            let (section, token) = match (&section, key.split_once('.')) {
+                (Some(section), _) => (section.as_str(), key),
+                (None, Some((section, token))) => (section, token),
+                (None, None) => return Err(invalid(format!("Token '{}' is not inside a section", key))),
+            };
+            if token.is_empty() {
+                return Err(invalid("Empty token".to_owned()));
+            }
+
+            match section {
+                "opponent" | "player" => {
+                    let choice = game.choice_by_name(value).ok_or_else(|| invalid(format!("Unknown choice '{}'", value)))?;
+                    let column = if section == "opponent" { &mut guide.opponent } else { &mut guide.player };
+                    column.insert(token.to_owned(), choice);
+                }
+                "outcome" => {
+                    let outcome = Outcome::from_name(value).ok_or_else(|| invalid(format!("Unknown outcome '{}'", value)))?;
+                    guide.outcome.insert(token.to_owned(), outcome);
+                }
+                _ => return Err(invalid(format!("Unknown section '{}'", section))),
+            }
+        }
+
+        Ok(guide)
+    }
+
+    pub fn opponent_choice(&self, game: &Game, token: &str) -> Result<Choice, InvalidChoiceError> {
+        lookup(&self.opponent, token).unwrap_or_else(|| game.choice_from_token(token))
+    }
+
+    pub fn player_choice(&self, game: &Game, token: &str) -> Result<Choice, InvalidChoiceError> {
+        lookup(&self.player, token).unwrap_or_else(|| game.choice_from_token(token))
+    }
+
+    pub fn desired_outcome(&self, token: &str) -> Result<Outcome, InvalidChoiceError> {
+        lookup(&self.outcome, token).unwrap_or_else(|| Outcome::from_str(token))
+    }
+
+    pub fn set_player(&mut self, token: &str, choice: Choice) {
+        self.player.insert(token.to_owned(), choice);
+    }
+
+    pub fn set_outcome(&mut self, token: &str, outcome: Outcome) {
+        self.outcome.insert(token.to_owned(), outcome);
+    }
+
+    /// Choices of both players for a line of the guide.
+    pub fn choices_from_line(&self, game: &Game, line: &str, strategy: &Strategy) -> Result<(Choice, Choice), ParseLineError> {
+        let mut iter = line.split_whitespace();
+
+        let p1_choice = self.opponent_choice(game, iter.next().ok_or(ParseLineError::InvalidLine("Missing player 1 choice".to_owned()))?)?;
+        let p2_token = iter.next().ok_or(ParseLineError::InvalidLine("Missing player 2 choice".to_owned()))?;
+        let p2_choice = match strategy {
+            Strategy::PartOne => self.player_choice(game, p2_token)?,
+            Strategy::PartTwo => game.respond(p1_choice, self.desired_outcome(p2_token)?),
+        };
+
+        if iter.next().is_some() {
+            return Err(ParseLineError::InvalidLine("Unexpected third value".to_owned()));
+        }
+
+        Ok((p1_choice, p2_choice))
+    }
+
+    pub fn calculate_points_from_line(&self, game: &Game, line: &str, strategy: &Strategy) -> Result<Score, ParseLineError> {
+        let (p1_choice, p2_choice) = self.choices_from_line(game, line, strategy)?;
+        Ok(game.score(p1_choice, p2_choice))
+    }
+}
+
+/// A column with a mapping only accepts its own tokens.
+fn lookup<T: Copy>(column: &HashMap<String, T>, token: &str) -> Option<Result<T, InvalidChoiceError>> {
+    if column.is_empty() {
+        return None;
+    }
+    Some(column.get(token).copied().ok_or_else(|| InvalidChoiceError::InvalidChoice(format!(
+        "Invalid choice: '{}'",
+        token
+    ))))
+}
+
+/// The line up to the first `#` that isn't inside a quoted value.
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    for (index, ch) in line.char_indices() {
+        match ch {
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..index],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn unquote(s: &str) -> &str {
+    let s = s.trim();
+    s.strip_prefix('"').and_then(|s| s.strip_suffix('"')).unwrap_or(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_toml_guide() {
+        let game = Game::rock_paper_scissors();
+        let guide = StrategyGuide::from_file("./test_data/word_guide.toml", &game).expect("Failed to read guide");
+
+        assert_eq!(guide.opponent_choice(&game, "rock").unwrap(), game.choice_by_name("Rock").unwrap());
+        assert_eq!(guide.player_choice(&game, "win").unwrap(), game.choice_by_name("Scissors").unwrap());
+        assert_eq!(guide.desired_outcome("lose").unwrap(), Outcome::Lose);
+        assert!(guide.opponent_choice(&game, "A").is_err(), "Mapped columns only accept their own tokens");
+    }
+
+    #[test]
+    fn test_parse_key_value_guide() {
+        let game = Game::rock_paper_scissors_lizard_spock();
+        let content = "
+            # Sections can also be given inline
+            opponent.1 = rock
+            opponent.5 = spock
+            outcome.L = lose
+            outcome.W = \"Win\"
+        ";
+        let guide = StrategyGuide::parse(content, &game).expect("Failed to parse guide");
+
+        assert_eq!(guide.opponent_choice(&game, "5").unwrap(), game.choice_by_name("Spock").unwrap());
+        assert_eq!(guide.player_choice(&game, "E").unwrap(), game.choice_by_name("Spock").unwrap());
+        assert_eq!(guide.desired_outcome("W").unwrap(), Outcome::Win);
+        assert!(guide.desired_outcome("X").is_err());
+        assert!(matches!(guide.calculate_points_from_line(&game, "5 W", &Strategy::PartTwo), Ok((5, 8))));
+    }
+
+    #[test]
+    fn test_hash_inside_quotes() {
+        let game = Game::rock_paper_scissors();
+        let content = "[opponent]\n\"#\" = rock # a comment\n[player]\nX = \"Paper\"# another one\n";
+        let guide = StrategyGuide::parse(content, &game).expect("Failed to parse guide");
+
+        assert_eq!(guide.opponent_choice(&game, "#").unwrap(), game.choice_by_name("Rock").unwrap());
+        assert_eq!(guide.player_choice(&game, "X").unwrap(), game.choice_by_name("Paper").unwrap());
+    }
+
+    #[test]
+    fn test_invalid_guides() {
+        let game = Game::rock_paper_scissors();
+        let cases = [
+            ("rock = Rock", 1),
+            ("[opponent]\nrock = Lizard", 2),
+            ("\n[outcome]\nX = maybe", 3),
+            ("[referee]\nX = Rock", 2),
+            ("[player\nX = Rock", 1),
+            ("[player]\nX Rock", 2),
+        ];
+        for (content, expected_line) in cases {
+            match StrategyGuide::parse(content, &game) {
+                Err(GuideError::InvalidLine { line_number, .. }) => assert_eq!(line_number, expected_line, "{}", content),
+                other => panic!("Expected an invalid line error for {:?}, got {:?}", content, other),
+            }
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use std::fmt::Write;
+
+use crate::rps_lib::{Game, Outcome, ParseLineError, Strategy};
+use crate::strategy_guide::StrategyGuide;
+
+/// A player in the tournament: the moves it makes are what `guide` tells
+/// player 2 to play on each line of the shared input.
+pub struct Entrant {
+    pub name: String,
+    pub guide: StrategyGuide,
+    pub strategy: Strategy,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Standing {
+    pub name: String,
+    pub played: u32,
+    pub won: u32,
+    pub drawn: u32,
+    pub lost: u32,
+    pub score_for: i32,
+    pub score_against: i32,
+}
+
+impl Standing {
+    /// Three points for a match win and one for a draw.
+    pub fn points(&self) -> u32 {
+        3 * self.won + self.drawn
+    }
+
+    fn record(&mut self, outcome: Outcome, score_for: i32, score_against: i32) {
+        self.played += 1;
+        self.score_for += score_for;
+        self.score_against += score_against;
+        match outcome {
+            Outcome::Win => self.won += 1,
+            Outcome::Draw => self.drawn += 1,
+            Outcome::Lose => self.lost += 1,
+        }
+    }
+}
+
+/// Plays every entrant once against every other entrant over all `lines`.
+///
+/// A match is won by the entrant with the higher total round score. The
+/// standings are sorted by points, then by score difference, then by name.
+pub fn round_robin(game: &Game, lines: &[String], entrants: &[Entrant]) -> Result<Vec<Standing>, ParseLineError> {
+    let moves = entrants
+        .iter()
+        .map(|entrant| {
+            lines
+                .iter()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| Ok(entrant.guide.choices_from_line(game, line, &entrant.strategy)?.1))
+                .collect::<Result<Vec<_>, ParseLineError>>()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut standings: Vec<Standing> = entrants
+        .iter()
+        .map(|entrant| Standing { name: entrant.name.clone(), ..Standing::default() })
+        .collect();
+
+    for a in 0..entrants.len() {
+        for b in a + 1..entrants.len() {
+            let (score_a, score_b) = moves[a]
+                .iter()
+                .zip(&moves[b])
+                .map(|(move_a, move_b)| game.score(*move_a, *move_b))
+                .fold((0, 0), |(total_a, total_b), (a, b)| (total_a + a, total_b + b));
+
+            let outcome = match score_a.cmp(&score_b) {
+                std::cmp::Ordering::Greater => Outcome::Win,
+                std::cmp::Ordering::Equal => Outcome::Draw,
+                std::cmp::Ordering::Less => Outcome::Lose,
+            };
+            standings[a].record(outcome, score_a, score_b);
+            standings[b].record(outcome.reverse(), score_b, score_a);
+        }
+    }
+
+    standings.sort_by(|a, b| {
+        b.points()
+            .cmp(&a.points())
+            .then((b.score_for - b.score_against).cmp(&(a.score_for - a.score_against)))
+            .then(a.name.cmp(&b.name))
+    });
+
+    Ok(standings)
+}
+
+pub fn format_standings(standings: &[Standing]) -> String {
+    let name_width = standings.iter().map(|standing| standing.name.len()).max().unwrap_or(0).max("Entrant".len());
+
+    let mut table = String::new();
+    let _ = writeln!(table, "{:>3}  {:<name_width$}  {:>2} {:>2} {:>2} {:>2} {:>8} {:>8} {:>3}", "#", "Entrant", "P", "W", "D", "L", "For", "Against", "Pts");
+    for (rank, standing) in standings.iter().enumerate() {
+        let _ = writeln!(
+            table,
+            "{:>3}  {:<name_width$}  {:>2} {:>2} {:>2} {:>2} {:>8} {:>8} {:>3}",
+            rank + 1,
+            standing.name,
+            standing.played,
+            standing.won,
+            standing.drawn,
+            standing.lost,
+            standing.score_for,
+            standing.score_against,
+            standing.points()
+        );
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example_lines() -> Vec<String> {
+        ["A Y", "B X", "C Z"].iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn test_round_robin() {
+        let game = Game::rock_paper_scissors();
+        let entrants = vec![
+            // Plays Paper, Rock, Scissors
+            Entrant { name: "literal".to_owned(), guide: StrategyGuide::default(), strategy: Strategy::PartOne },
+            // Plays Rock, Rock, Rock
+            Entrant { name: "outcomes".to_owned(), guide: StrategyGuide::default(), strategy: Strategy::PartTwo },
+        ];
+
+        let standings = round_robin(&game, &example_lines(), &entrants).unwrap();
+        assert_eq!(standings.len(), 2);
+        // Paper beats Rock, Rock draws Rock and Scissors loses to Rock
+        assert_eq!(standings[0].name, "literal");
+        assert_eq!((standings[0].played, standings[0].won, standings[0].drawn, standings[0].lost), (1, 1, 0, 0));
+        assert_eq!((standings[0].score_for, standings[0].score_against), (8 + 4 + 3, 1 + 4 + 7));
+        assert_eq!(standings[1].points(), 0);
+    }
+
+    #[test]
+    fn test_round_robin_three_entrants() {
+        let game = Game::rock_paper_scissors();
+        let mut always_paper = StrategyGuide::default();
+        for token in ["X", "Y", "Z"] {
+            always_paper.set_player(token, game.choice_by_name("Paper").unwrap());
+        }
+        let entrants = vec![
+            Entrant { name: "literal".to_owned(), guide: StrategyGuide::default(), strategy: Strategy::PartOne },
+            Entrant { name: "outcomes".to_owned(), guide: StrategyGuide::default(), strategy: Strategy::PartTwo },
+            Entrant { name: "paper".to_owned(), guide: always_paper, strategy: Strategy::PartOne },
+        ];
+
+        let standings = round_robin(&game, &example_lines(), &entrants).unwrap();
+        // "literal" and "paper" both win one match and draw the other, "paper" by a wider margin
+        assert_eq!(standings[0].name, "paper");
+        assert_eq!(standings[0].points(), 4);
+        assert_eq!(standings[1].name, "literal");
+        assert_eq!(standings[1].points(), 4);
+        assert!(standings.iter().all(|standing| standing.played == 2));
+
+        let table = format_standings(&standings);
+        assert_eq!(table.lines().count(), 4);
+        assert!(table.lines().nth(1).unwrap().contains("paper"));
+    }
+
+    #[test]
+    fn test_round_robin_invalid_line() {
+        let game = Game::rock_paper_scissors();
+        let entrants = vec![Entrant { name: "literal".to_owned(), guide: StrategyGuide::default(), strategy: Strategy::PartOne }];
+        let lines = vec!["A Q".to_owned()];
+        assert!(round_robin(&game, &lines, &entrants).is_err());
+    }
+}
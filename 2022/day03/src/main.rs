@@ -1,22 +1,93 @@
-use std::path::Path;
+use std::env;
+use std::io::{self, BufRead, BufReader};
 
 mod rucksack_lib;
-use rucksack_lib::{ calculate_duplicate_points, three_elf_badge_counts };
+use rucksack_lib::{ badge_report, badge_sum, calculate_duplicate_points, duplicate_report, three_elf_badge_counts, ParsingError };
 
+/// Per-line output instead of the totals.
+enum Report {
+    Duplicates,
+    Badges,
+}
+
+// Usage: day3 [input|-] [--report duplicates|badges] [--group-size N]
 fn main() {
-    let file_path = Path::new("./data.txt");
-    let result = calculate_duplicate_points(&file_path);
-    println!("Total sum: {:?}", result);
+    let mut input_path = String::from("./data.txt");
+    let mut report = None;
+    let mut group_size = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--report" => {
+                report = match args.next().as_deref() {
+                    Some("duplicates") => Some(Report::Duplicates),
+                    Some("badges") => Some(Report::Badges),
+                    other => {
+                        eprintln!("Unknown report '{}', expected 'duplicates' or 'badges'", other.unwrap_or_default());
+                        std::process::exit(1);
+                    }
+                }
+            }
+            "--group-size" => {
+                group_size = match args.next().map(|n| n.parse()) {
+                    Some(Ok(n)) => Some(n),
+                    _ => {
+                        eprintln!("--group-size expects a number");
+                        std::process::exit(1);
+                    }
+                }
+            }
+            _ => input_path = arg,
+        }
+    }
+
+    let reader: Box<dyn BufRead> = if input_path == "-" {
+        Box::new(io::stdin().lock())
+    } else {
+        match advent_vault::open(&input_path) {
+            Ok(file) => Box::new(BufReader::new(file)),
+            Err(err) => {
+                eprintln!("Error opening input file: {}", err);
+                std::process::exit(1);
+            }
+        }
+    };
+
+    if let Err(err) = run(reader, report, group_size) {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+fn run<R: BufRead>(mut reader: R, report: Option<Report>, group_size: Option<usize>) -> Result<(), ParsingError> {
+    let stdout = io::stdout().lock();
+    match report {
+        Some(Report::Duplicates) => duplicate_report(reader, stdout),
+        Some(Report::Badges) => badge_report(reader, stdout, group_size.unwrap_or(3)),
+        None => {
+            // Both parts need their own pass over the input
+            let mut input = Vec::new();
+            reader.read_to_end(&mut input)?;
+
+            let result = calculate_duplicate_points(input.as_slice())?;
+            println!("Total sum: {:?}", result);
 
-    let result = three_elf_badge_counts(file_path).expect("Error getting badge count");
-    println!("Total badge sum: {:?}", result);
+            let result = match group_size {
+                Some(group_size) => badge_sum(input.as_slice(), group_size)?,
+                None => three_elf_badge_counts(input.as_slice())?,
+            };
+            println!("Total badge sum: {:?}", result);
+            Ok(())
+        }
+    }
 }
 
 #[test]
 fn test_main() {
-    let filepath = Path::new("./test_data/example_data.txt");
+    let file = std::fs::File::open("./test_data/example_data.txt").expect("Unable to open example_data.txt");
     let expected_priority_sum = 157;
 
-    let output = rucksack_lib::calculate_duplicate_points(&filepath);
-    assert_eq!(output, expected_priority_sum);
+    let output = rucksack_lib::calculate_duplicate_points(BufReader::new(file));
+    assert_eq!(output.unwrap(), expected_priority_sum);
 }
@@ -1,13 +1,18 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::path::Path;
-use std::collections::HashSet;
+use std::io::{BufRead, Write};
 use std::error::Error;
 use std::fmt::{ Display, Formatter };
+use std::ops::{ BitAnd, BitOr, Sub };
 
 #[derive(Debug)]
 pub enum ParsingError {
     NoCommonChar(String),
+    MultipleCommonChars(String),
+    OddLength(usize),
+    InvalidItem(char),
+    InvalidGroupSize(usize),
+    IncompleteGroup { expected: usize, actual: usize },
+    AtLine(usize, Box<ParsingError>),
+    Io(std::io::Error),
 }
 
 impl Error for ParsingError {}
@@ -16,179 +21,417 @@ impl Display for ParsingError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             ParsingError::NoCommonChar(msg) => write!(f, "{}", msg),
+            ParsingError::MultipleCommonChars(items) => write!(f, "Multiple common characters found: {}", items),
+            ParsingError::OddLength(length) => write!(f, "Rucksack with {} items can't be split into two compartments", length),
+            ParsingError::InvalidItem(item) => write!(f, "Invalid item '{}'", item),
+            ParsingError::InvalidGroupSize(size) => write!(f, "Invalid group size {}", size),
+            ParsingError::IncompleteGroup { expected, actual } => write!(f, "Last group has {} of {} rucksacks", actual, expected),
+            ParsingError::AtLine(line_number, err) => write!(f, "Line {}: {}", line_number, err),
+            ParsingError::Io(err) => write!(f, "{}", err),
         }
     }
 }
 
+impl From<std::io::Error> for ParsingError {
+    fn from(err: std::io::Error) -> Self {
+        ParsingError::Io(err)
+    }
+}
+
+/// Priority of an item: `a`-`z` are 1-26 and `A`-`Z` are 27-52.
+pub fn priority(item: char) -> Result<u32, ParsingError> {
+    match item {
+        'a'..='z' => Ok(item as u32 - 'a' as u32 + 1),
+        'A'..='Z' => Ok(item as u32 - 'A' as u32 + 27),
+        _ => Err(ParsingError::InvalidItem(item)),
+    }
+}
+
+/// Set of item types, one bit per priority.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ItemSet(u64);
+
+impl ItemSet {
+    pub fn from_items(items: &str) -> Result<Self, ParsingError> {
+        let mut set = ItemSet::default();
+        for item in items.chars() {
+            set.0 |= 1 << (priority(item)? - 1);
+        }
+        Ok(set)
+    }
+
+    pub fn union(self, other: ItemSet) -> ItemSet {
+        ItemSet(self.0 | other.0)
+    }
+
+    pub fn intersection(self, other: ItemSet) -> ItemSet {
+        ItemSet(self.0 & other.0)
+    }
+
+    pub fn difference(self, other: ItemSet) -> ItemSet {
+        ItemSet(self.0 & !other.0)
+    }
+
+    pub fn len(&self) -> u32 {
+        self.0.count_ones()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Priorities of the items in the set, lowest first.
+    pub fn priorities(&self) -> impl Iterator<Item = u32> {
+        let bits = self.0;
+        (1..=52).filter(move |priority| bits & (1 << (priority - 1)) != 0)
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = char> {
+        self.priorities().map(|priority| match priority {
+            1..=26 => (b'a' + (priority - 1) as u8) as char,
+            _ => (b'A' + (priority - 27) as u8) as char,
+        })
+    }
+
+    pub fn priority_sum(&self) -> u32 {
+        self.priorities().sum()
+    }
+}
+
+impl BitAnd for ItemSet {
+    type Output = ItemSet;
+
+    fn bitand(self, other: ItemSet) -> ItemSet {
+        self.intersection(other)
+    }
+}
+
+impl BitOr for ItemSet {
+    type Output = ItemSet;
+
+    fn bitor(self, other: ItemSet) -> ItemSet {
+        self.union(other)
+    }
+}
+
+impl Sub for ItemSet {
+    type Output = ItemSet;
+
+    fn sub(self, other: ItemSet) -> ItemSet {
+        self.difference(other)
+    }
+}
+
 #[derive(Debug)]
 struct Rucksack {
-    compartment1: String,
-    compartment2: String,
+    compartment1: ItemSet,
+    compartment2: ItemSet,
 }
 
 impl Rucksack {
-    fn new(compartment1: String, compartment2: String) -> Self {
-        Rucksack {
-            compartment1,
-            compartment2,
-        }
+    fn new(compartment1: &str, compartment2: &str) -> Result<Self, ParsingError> {
+        Ok(Rucksack {
+            compartment1: ItemSet::from_items(compartment1)?,
+            compartment2: ItemSet::from_items(compartment2)?,
+        })
     }
 
-    fn calculate_points(&self) -> i32 {
-        let mut total_sum = 0;
-        let comp1 = &self.compartment1;
-        let comp2 = &self.compartment2;
-
-        let mut seen = HashSet::new();
-        for char in comp1.chars() {
-            if seen.insert(char) && comp2.contains(char) {
-                total_sum += char as i32 - if char.is_lowercase() { 'a' as i32 - 1 } else { 'A' as i32 - 27 };
-            }
-        }
+    fn items(&self) -> ItemSet {
+        self.compartment1 | self.compartment2
+    }
 
-        total_sum
+    fn calculate_points(&self) -> i32 {
+        (self.compartment1 & self.compartment2).priority_sum() as i32
     }
 }
 
-fn parse_line(line: &str) -> Option<Rucksack> {
+fn parse_line(line: &str) -> Result<Rucksack, ParsingError> {
+    if let Some(item) = line.chars().find(|item| !item.is_ascii_alphabetic()) {
+        return Err(ParsingError::InvalidItem(item));
+    }
+    if !line.len().is_multiple_of(2) {
+        return Err(ParsingError::OddLength(line.len()));
+    }
     let mid = line.len() / 2;
-    let compartment1 = line[..mid].to_string();
-    let compartment2 = line[mid..].to_string();
 
-    Some(Rucksack::new(compartment1, compartment2))
+    Rucksack::new(&line[..mid], &line[mid..])
 }
 
-pub fn calculate_duplicate_points(file_path: &Path) -> i32 {
-    let file = File::open(file_path).unwrap();
-    let reader = BufReader::new(file);
+/// Parses rucksacks one line at a time, tagging errors with their line number.
+fn read_rucksacks<R: BufRead>(reader: R) -> impl Iterator<Item = Result<Rucksack, ParsingError>> {
+    reader.lines().enumerate().map(|(index, line)| {
+        parse_line(&line?).map_err(|err| ParsingError::AtLine(index + 1, Box::new(err)))
+    })
+}
 
+pub fn calculate_duplicate_points<R: BufRead>(reader: R) -> Result<i32, ParsingError> {
     let mut points = 0;
-    for line in reader.lines().flatten() {
-        if let Some(rucksack) = parse_line(&line) {
-            points += rucksack.calculate_points();
-        }
+    for rucksack in read_rucksacks(reader) {
+        points += rucksack?.calculate_points();
     }
 
-    points
+    Ok(points)
 }
 
-fn find_badge_char(first: &str, second: &str, third: &str) -> Result<char, ParsingError> {
-    for c1 in first.chars() {
-        if second.contains(c1) && third.contains(c1) {
-            return Ok(c1)
-        }
+/// The one item type carried by every rucksack of the group.
+fn find_badge(group: &[ItemSet]) -> Result<char, ParsingError> {
+    let common = group.iter().fold(ItemSet(!0), |common, items| common & *items);
+    if common.is_empty() {
+        return Err(ParsingError::NoCommonChar("No Common Character found".to_string()));
+    }
+    if common.len() > 1 {
+        return Err(ParsingError::MultipleCommonChars(common.items().collect()));
     }
-    Err(ParsingError::NoCommonChar("No Common Character found".to_string()))
+    Ok(common.items().next().expect("set has exactly one item"))
 }
 
+/// Calls `on_badge` with the first line number and badge of every group of `group_size` rucksacks.
+fn for_each_badge<R, F>(reader: R, group_size: usize, mut on_badge: F) -> Result<(), ParsingError>
+where
+    R: BufRead,
+    F: FnMut(usize, char) -> Result<(), ParsingError>,
+{
+    if group_size == 0 {
+        return Err(ParsingError::InvalidGroupSize(group_size));
+    }
 
-pub fn three_elf_badge_counts(file_path: &Path) -> Result<i32, std::io::Error> {
-    let file = File::open(file_path)?;
-    let reader = BufReader::new(file);
-
-    let mut count = 0;
+    let mut group = Vec::with_capacity(group_size);
+    let mut first_line = 1;
+    for (index, rucksack) in read_rucksacks(reader).enumerate() {
+        if group.is_empty() {
+            first_line = index + 1;
+        }
+        group.push(rucksack?.items());
+        if group.len() == group_size {
+            let badge = find_badge(&group).map_err(|err| ParsingError::AtLine(first_line, Box::new(err)))?;
+            on_badge(first_line, badge)?;
+            group.clear();
+        }
+    }
 
-    let mut lines_iter = reader.lines().map(|l| l.unwrap());
+    if !group.is_empty() {
+        return Err(ParsingError::IncompleteGroup { expected: group_size, actual: group.len() });
+    }
+    Ok(())
+}
 
-    loop {
-        let line1 = match lines_iter.next() {
-            Some(line) => line,
-            None => break,
-        };
+pub fn badge_sum<R: BufRead>(reader: R, group_size: usize) -> Result<i32, ParsingError> {
+    let mut count = 0;
+    for_each_badge(reader, group_size, |_, badge| {
+        count += priority(badge)? as i32;
+        Ok(())
+    })?;
 
-        let line2 = match lines_iter.next() {
-            Some(line) => line,
-            None => return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid number of lines")),
-        };
+    Ok(count)
+}
 
-        let line3 = match lines_iter.next() {
-            Some(line) => line,
-            None => return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid number of lines")),
-        };
+pub fn three_elf_badge_counts<R: BufRead>(reader: R) -> Result<i32, ParsingError> {
+    badge_sum(reader, 3)
+}
 
-        let badge = find_badge_char(&line1, &line2, &line3).expect("Couldn't find badge");
-        count += badge as i32 - if badge.is_lowercase() { 'a' as i32 - 1 } else { 'A' as i32 - 27 };
+/// Writes `line,items,priority` CSV rows with the items found in both compartments of each rucksack.
+pub fn duplicate_report<R: BufRead, W: Write>(reader: R, mut writer: W) -> Result<(), ParsingError> {
+    writeln!(writer, "line,items,priority")?;
+    for (index, rucksack) in read_rucksacks(reader).enumerate() {
+        let rucksack = rucksack?;
+        let duplicates = rucksack.compartment1 & rucksack.compartment2;
+        writeln!(writer, "{},{},{}", index + 1, duplicates.items().collect::<String>(), duplicates.priority_sum())?;
     }
 
-    Ok(count)
+    Ok(())
+}
+
+/// Writes `group,first_line,badge,priority` CSV rows for every group of `group_size` rucksacks.
+pub fn badge_report<R: BufRead, W: Write>(reader: R, mut writer: W, group_size: usize) -> Result<(), ParsingError> {
+    writeln!(writer, "group,first_line,badge,priority")?;
+    let mut group = 0;
+    for_each_badge(reader, group_size, |first_line, badge| {
+        group += 1;
+        writeln!(writer, "{},{},{},{}", group, first_line, badge, priority(badge)?)?;
+        Ok(())
+    })
 }
 
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use propcheck::Fuzzer;
+    use std::fs::File;
+    use std::io::{BufReader, Cursor};
+
+    fn example_data() -> BufReader<File> {
+        BufReader::new(File::open("./test_data/example_data.txt").expect("Unable to open example_data.txt"))
+    }
 
     #[test]
     fn test_three_elf_badge_counts() {
-        let file_path = Path::new("./test_data/example_data.txt");
-        let total_count = three_elf_badge_counts(file_path);
+        let total_count = three_elf_badge_counts(example_data());
         assert!(total_count.is_ok());
         assert_eq!(total_count.unwrap(), 70);
     }
 
     #[test]
     fn test_find_badge_char() {
-        let badge_character = find_badge_char(
+        let badge_character = find_badge(&item_sets(&[
             "vJrwpWtwJgWrhcsFMMfFFhFp",
             "jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL",
-            "PmmdzqPrVvPwwTWBwg");
+            "PmmdzqPrVvPwwTWBwg"]));
         assert!(badge_character.is_ok());
         assert_eq!(badge_character.unwrap(), 'r');
 
-        let badge_character = find_badge_char(
+        let badge_character = find_badge(&item_sets(&[
             "wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn",
             "ttgJtRGJQctTZtZT",
-            "CrZsJsPPZsGzwwsLwLmpwMDw");
+            "CrZsJsPPZsGzwwsLwLmpwMDw"]));
         assert!(badge_character.is_ok());
         assert_eq!(badge_character.unwrap(), 'Z');
     }
 
     #[test]
     fn test_calculate_points() {
-        let rucksack = Rucksack::new("ab".to_string(), "ba".to_string());
+        let rucksack = Rucksack::new("ab", "ba").unwrap();
         assert_eq!(rucksack.calculate_points(), 3);
 
-        let rucksack = Rucksack::new("abc".to_string(), "def".to_string());
+        let rucksack = Rucksack::new("abc", "def").unwrap();
         assert_eq!(rucksack.calculate_points(), 0);
 
-        let rucksack = Rucksack::new("Aa".to_string(), "bB".to_string());
+        let rucksack = Rucksack::new("Aa", "bB").unwrap();
         assert_eq!(rucksack.calculate_points(), 0);
 
-        let rucksack = Rucksack::new("aB".to_string(), "Cc".to_string());
+        let rucksack = Rucksack::new("aB", "Cc").unwrap();
         assert_eq!(rucksack.calculate_points(), 0);
 
-        let rucksack = Rucksack::new("abcde".to_string(), "ABCDe".to_string());
+        let rucksack = Rucksack::new("abcde", "ABCDe").unwrap();
         assert_eq!(rucksack.calculate_points(), 5);
 
-        let rucksack = Rucksack::new("Abcde".to_string(), "ABCDE".to_string());
+        let rucksack = Rucksack::new("Abcde", "ABCDE").unwrap();
         assert_eq!(rucksack.calculate_points(), 27);
 
-        let rucksack = Rucksack::new("vJrwpWtwJgWr".to_string(), "hcsFMMfFFhFp".to_string());
+        let rucksack = Rucksack::new("vJrwpWtwJgWr", "hcsFMMfFFhFp").unwrap();
         assert_eq!(rucksack.calculate_points(), 16, "first example");
 
-        let rucksack = Rucksack::new("jqHRNqRjqzjGDLGL".to_string(), "rsFMfFZSrLrFZsSL".to_string());
+        let rucksack = Rucksack::new("jqHRNqRjqzjGDLGL", "rsFMfFZSrLrFZsSL").unwrap();
         assert_eq!(rucksack.calculate_points(), 38, "second example");
 
-        let rucksack = Rucksack::new("PmmdzqPrV".to_string(), "vPwwTWBwg".to_string());
+        let rucksack = Rucksack::new("PmmdzqPrV", "vPwwTWBwg").unwrap();
         assert_eq!(rucksack.calculate_points(), 42, "third example");
 
-        let rucksack = Rucksack::new("wMqvLMZHhHMvwLH".to_string(), "jbvcjnnSBnvTQFn".to_string());
+        let rucksack = Rucksack::new("wMqvLMZHhHMvwLH", "jbvcjnnSBnvTQFn").unwrap();
         assert_eq!(rucksack.calculate_points(), 22, "fourth example");
 
-        let rucksack = Rucksack::new("ttgJtRGJ".to_string(), "QctTZtZT".to_string());
+        let rucksack = Rucksack::new("ttgJtRGJ", "QctTZtZT").unwrap();
         assert_eq!(rucksack.calculate_points(), 20, "fifth example");
 
-        let rucksack = Rucksack::new("CrZsJsPPZsGz".to_string(), "wwsLwLmpwMDw".to_string());
+        let rucksack = Rucksack::new("CrZsJsPPZsGz", "wwsLwLmpwMDw").unwrap();
         assert_eq!(rucksack.calculate_points(), 19, "sixth example");
     }
 
     #[test]
     fn test_calculate_duplicate_points() {
-        let input_file = "./test_data/example_data.txt";
         let expected_result = 157;
 
-        let result = calculate_duplicate_points(Path::new(input_file));
+        let result = calculate_duplicate_points(example_data());
+
+        assert_eq!(result.unwrap(), expected_result);
+    }
+
+    fn item_sets(rucksacks: &[&str]) -> Vec<ItemSet> {
+        rucksacks.iter().map(|items| ItemSet::from_items(items).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_item_set() {
+        let set = ItemSet::from_items("abcA").unwrap();
+        assert_eq!(set.len(), 4);
+        assert_eq!(set.priority_sum(), 1 + 2 + 3 + 27);
+
+        let other = ItemSet::from_items("bcZ").unwrap();
+        assert_eq!((set & other).items().collect::<String>(), "bc");
+        assert_eq!((set | other).items().collect::<String>(), "abcAZ");
+        assert_eq!((set - other).items().collect::<String>(), "aA");
+        assert!(ItemSet::from_items("").unwrap().is_empty());
+        assert!(matches!(ItemSet::from_items("ab1"), Err(ParsingError::InvalidItem('1'))));
+    }
+
+    #[test]
+    fn test_parse_line_errors() {
+        assert!(matches!(parse_line("abc"), Err(ParsingError::OddLength(3))));
+        assert!(matches!(parse_line("ab-a"), Err(ParsingError::InvalidItem('-'))));
+        assert!(matches!(parse_line("aé"), Err(ParsingError::InvalidItem('é'))));
+    }
+
+    #[test]
+    fn test_find_badge_errors() {
+        assert!(matches!(find_badge(&item_sets(&["ab", "cd"])), Err(ParsingError::NoCommonChar(_))));
+        match find_badge(&item_sets(&["abc", "bca", "xab"])) {
+            Err(ParsingError::MultipleCommonChars(items)) => assert_eq!(items, "ab"),
+            other => panic!("Expected multiple common characters, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_badge_sum() {
+        assert_eq!(badge_sum(example_data(), 3).unwrap(), 70);
+        assert!(matches!(badge_sum(example_data(), 2), Err(ParsingError::AtLine(1, _))));
+        assert!(matches!(badge_sum(example_data(), 0), Err(ParsingError::InvalidGroupSize(0))));
+        assert!(matches!(
+            badge_sum(Cursor::new("abab\nacac\nadad\naeae"), 3),
+            Err(ParsingError::IncompleteGroup { expected: 3, actual: 1 })
+        ));
+        assert_eq!(badge_sum(Cursor::new("abab\nacac\nZaZa\nZbZb"), 2).unwrap(), 1 + 52);
+        match badge_sum(example_data(), 1) {
+            Err(ParsingError::AtLine(1, err)) => assert!(matches!(*err, ParsingError::MultipleCommonChars(_))),
+            other => panic!("Expected multiple common characters on line 1, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_errors_report_line_numbers() {
+        match calculate_duplicate_points(Cursor::new("abab\nabc\n")) {
+            Err(ParsingError::AtLine(2, err)) => assert!(matches!(*err, ParsingError::OddLength(3))),
+            other => panic!("Expected an odd length on line 2, got {:?}", other),
+        }
+        let err = calculate_duplicate_points(Cursor::new("ab1b")).unwrap_err();
+        assert_eq!(err.to_string(), "Line 1: Invalid item '1'");
+    }
+
+    #[test]
+    fn test_duplicate_report() {
+        let mut output = Vec::new();
+        duplicate_report(example_data(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let rows: Vec<&str> = output.lines().collect();
+        assert_eq!(rows, vec![
+            "line,items,priority",
+            "1,p,16",
+            "2,L,38",
+            "3,P,42",
+            "4,v,22",
+            "5,t,20",
+            "6,s,19",
+        ]);
+    }
+
+    #[test]
+    fn test_badge_report() {
+        let mut output = Vec::new();
+        badge_report(example_data(), &mut output, 3).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "group,first_line,badge,priority\n1,1,r,18\n2,4,Z,52\n");
+    }
 
-        assert_eq!(result, expected_result);
+    #[test]
+    fn test_fuzz_readers() {
+        let example = std::fs::read_to_string("./test_data/example_data.txt").unwrap();
+        Fuzzer::new("rucksack_readers")
+            .seed_input(&example)
+            .seed_input("abAB\nabCD\nEFab\n")
+            .run(|bytes| {
+                calculate_duplicate_points(bytes)?;
+                badge_sum(bytes, 2)?;
+                three_elf_badge_counts(bytes)?;
+                duplicate_report(bytes, std::io::sink())?;
+                badge_report(bytes, std::io::sink(), 3)
+            });
     }
 }
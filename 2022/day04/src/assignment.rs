@@ -0,0 +1,217 @@
+use std::fmt;
+use std::io::BufRead;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum AssignmentError {
+    #[error("line {line}, column {column}: expected a section number, found '{found}'")]
+    InvalidNumber { line: usize, column: usize, found: String },
+
+    #[error("line {line}, column {column}: expected 'start-end', found '{found}'")]
+    MissingSeparator { line: usize, column: usize, found: String },
+
+    #[error("line {line}, column {column}: range {start}-{end} ends before it starts")]
+    ReversedRange { line: usize, column: usize, start: u32, end: u32 },
+
+    #[error("line {line}: expected at least two ranges, found {found}")]
+    TooFewRanges { line: usize, found: usize },
+
+    #[error("Failed to read input: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ParseOptions {
+    /// Swap the bounds of ranges such as `7-3` instead of rejecting them.
+    pub normalize_reversed: bool,
+}
+
+/// Parses the comma-separated `start-end` ranges assigned to a group of elves.
+///
+/// `line_number` is only used for error reporting, columns are 1-based
+/// character positions in `line`.
+pub fn parse_assignments(line: &str, line_number: usize, options: &ParseOptions) -> Result<Vec<(u32, u32)>, AssignmentError> {
+    let mut assignments = Vec::new();
+    let mut offset = 0;
+    for range in line.split(',') {
+        assignments.push(parse_range_at(line, offset, range.len(), line_number, options)?);
+        offset += range.len() + 1;
+    }
+
+    if assignments.len() < 2 {
+        return Err(AssignmentError::TooFewRanges { line: line_number, found: assignments.len() });
+    }
+
+    Ok(assignments)
+}
+
+/// Parses a single `start-end` range.
+pub fn parse_range(text: &str, line_number: usize, options: &ParseOptions) -> Result<(u32, u32), AssignmentError> {
+    parse_range_at(text, 0, text.len(), line_number, options)
+}
+
+/// Parses the range in `line[range_offset..range_offset + range_len]` so that
+/// columns are reported relative to the whole line.
+fn parse_range_at(line: &str, range_offset: usize, range_len: usize, line_number: usize, options: &ParseOptions) -> Result<(u32, u32), AssignmentError> {
+    let column_of = |byte_offset: usize| line[..byte_offset].chars().count() + 1;
+    let range = &line[range_offset..range_offset + range_len];
+    let range_column = column_of(range_offset + (range.len() - range.trim_start().len()));
+
+    let (start_part, end_part) = range.split_once('-').ok_or_else(|| AssignmentError::MissingSeparator {
+        line: line_number,
+        column: range_column,
+        found: range.trim().to_owned(),
+    })?;
+    let end_offset = range_offset + start_part.len() + 1;
+
+    let parse_number = |part: &str, part_offset: usize| {
+        let trimmed = part.trim();
+        trimmed.parse::<u32>().map_err(|_| AssignmentError::InvalidNumber {
+            line: line_number,
+            column: column_of(part_offset + (part.len() - part.trim_start().len())),
+            found: trimmed.to_owned(),
+        })
+    };
+    let start = parse_number(start_part, range_offset)?;
+    let end = parse_number(end_part, end_offset)?;
+
+    if start <= end {
+        Ok((start, end))
+    } else if options.normalize_reversed {
+        Ok((end, start))
+    } else {
+        Err(AssignmentError::ReversedRange { line: line_number, column: range_column, start, end })
+    }
+}
+
+/// Reads every non-blank line of `reader` as a group of assignments.
+pub fn read_assignments<R: BufRead>(reader: R, options: ParseOptions) -> impl Iterator<Item = Result<Vec<(u32, u32)>, AssignmentError>> {
+    reader
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, Ok(line) if line.trim().is_empty()))
+        .map(move |(index, line)| parse_assignments(&line?, index + 1, &options))
+}
+
+/// How two assignments relate to each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Relationship {
+    Disjoint,
+    Overlapping,
+    Contains,
+    ContainedBy,
+    Equal,
+}
+
+impl Relationship {
+    pub fn of(first: (u32, u32), second: (u32, u32)) -> Self {
+        let (start1, end1) = first;
+        let (start2, end2) = second;
+        if first == second {
+            Relationship::Equal
+        } else if start1 <= start2 && end2 <= end1 {
+            Relationship::Contains
+        } else if start2 <= start1 && end1 <= end2 {
+            Relationship::ContainedBy
+        } else if start1 <= end2 && start2 <= end1 {
+            Relationship::Overlapping
+        } else {
+            Relationship::Disjoint
+        }
+    }
+}
+
+impl fmt::Display for Relationship {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            Relationship::Disjoint => "disjoint",
+            Relationship::Overlapping => "overlapping",
+            Relationship::Contains => "contains",
+            Relationship::ContainedBy => "contained by",
+            Relationship::Equal => "equal",
+        };
+        write!(f, "{}", description)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Well-formed lines parse into their ranges, surrounding whitespace is allowed.
+     */
+    #[test]
+    fn test_parse_assignments() {
+        let options = ParseOptions::default();
+        assert_eq!(parse_assignments("2-4,6-8", 1, &options).unwrap(), vec![(2, 4), (6, 8)]);
+        assert_eq!(parse_assignments("  1-3, 2 - 5 ,9-9", 1, &options).unwrap(), vec![(1, 3), (2, 5), (9, 9)]);
+    }
+
+    /**
+     * Each kind of malformed line is reported with the line and the column where
+     * the offending range or number starts.
+     */
+    #[test]
+    fn test_parse_errors() {
+        let options = ParseOptions::default();
+        assert!(matches!(
+            parse_assignments("2-4,5-", 3, &options),
+            Err(AssignmentError::InvalidNumber { line: 3, column: 7, ref found }) if found.is_empty()
+        ));
+        assert!(matches!(
+            parse_assignments("a-3,4-5", 1, &options),
+            Err(AssignmentError::InvalidNumber { line: 1, column: 1, ref found }) if found == "a"
+        ));
+        assert!(matches!(
+            parse_assignments("2-4", 2, &options),
+            Err(AssignmentError::TooFewRanges { line: 2, found: 1 })
+        ));
+        assert!(matches!(
+            parse_assignments("1-2,7-3", 4, &options),
+            Err(AssignmentError::ReversedRange { line: 4, column: 5, start: 7, end: 3 })
+        ));
+        assert!(matches!(
+            parse_assignments("1-2, 5", 1, &options),
+            Err(AssignmentError::MissingSeparator { line: 1, column: 6, .. })
+        ));
+        assert!(matches!(
+            parse_assignments("1-2,-4-5", 1, &options),
+            Err(AssignmentError::InvalidNumber { line: 1, column: 5, .. })
+        ));
+    }
+
+    /**
+     * With normalisation enabled, reversed ranges get their bounds swapped.
+     */
+    #[test]
+    fn test_normalize_reversed() {
+        let options = ParseOptions { normalize_reversed: true };
+        assert_eq!(parse_assignments("1-2,7-3", 1, &options).unwrap(), vec![(1, 2), (3, 7)]);
+        assert_eq!(parse_range("9-4", 1, &options).unwrap(), (4, 9));
+    }
+
+    /**
+     * Blank lines are skipped but still count towards line numbers.
+     */
+    #[test]
+    fn test_read_assignments_line_numbers() {
+        let input = "1-2,3-4\n\n5-6,x-8\n";
+        let results: Vec<_> = read_assignments(input.as_bytes(), ParseOptions::default()).collect();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(AssignmentError::InvalidNumber { line: 3, column: 5, .. })));
+    }
+
+    /**
+     * The relationship of every pair in the example data.
+     */
+    #[test]
+    fn test_relationship() {
+        assert_eq!(Relationship::of((2, 4), (6, 8)), Relationship::Disjoint);
+        assert_eq!(Relationship::of((5, 7), (7, 9)), Relationship::Overlapping);
+        assert_eq!(Relationship::of((2, 8), (3, 7)), Relationship::Contains);
+        assert_eq!(Relationship::of((6, 6), (4, 6)), Relationship::ContainedBy);
+        assert_eq!(Relationship::of((2, 6), (2, 6)), Relationship::Equal);
+    }
+}
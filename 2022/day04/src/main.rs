@@ -1,12 +1,62 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::env;
+use std::io::{BufRead, BufReader, Read};
+use std::process;
 
+mod assignment;
+mod range_set;
+use assignment::{parse_range, read_assignments, AssignmentError, ParseOptions, Relationship};
+use range_set::{covered_by_at_least, uncovered_within, RangeSet};
+
+// Usage: day4 [--normalize] [--report] [--at-least K] [--uncovered START-END]
 fn main() {
-    let file1 = File::open("data.txt").expect("Unable to open input.txt");
-    let fully_contained = part_one(file1);
+    if let Err(err) = run() {
+        eprintln!("Error: {}", err);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), AssignmentError> {
+    let mut options = ParseOptions::default();
+    let mut report = false;
+    let mut at_least = None;
+    let mut uncovered = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--normalize" => options.normalize_reversed = true,
+            "--report" => report = true,
+            "--at-least" => match args.next().map(|k| k.parse::<usize>()) {
+                Some(Ok(k)) => at_least = Some(k),
+                _ => {
+                    eprintln!("--at-least expects a number");
+                    process::exit(1);
+                }
+            },
+            "--uncovered" => match args.next() {
+                Some(bound) => uncovered = Some(bound),
+                None => {
+                    eprintln!("--uncovered expects a range");
+                    process::exit(1);
+                }
+            },
+            other => {
+                eprintln!("Unknown option {}", other);
+                process::exit(1);
+            }
+        }
+    }
+
+    if report {
+        let file = advent_vault::open("data.txt")?;
+        return print_report(BufReader::new(file), &options);
+    }
 
-    let file2 = File::open("data.txt").expect("Unable to open input.txt");
-    let overlapping_pairs = part_two(file2);
+    let file1 = advent_vault::open("data.txt")?;
+    let fully_contained = part_one(file1, &options)?;
+
+    let file2 = advent_vault::open("data.txt")?;
+    let overlapping_pairs = part_two(file2, &options)?;
 
     println!(
         "Number of assignment pairs with full containment: {}",
@@ -17,73 +67,106 @@ fn main() {
         "Number of overlapping assignment pairs: {}",
         overlapping_pairs
     );
+
+    if let Some(k) = at_least {
+        let file = advent_vault::open("data.txt")?;
+        let sections = count_sections_covered_by_at_least(BufReader::new(file), &options, k)?;
+        println!("Sections covered by at least {} elves: {}", k, sections);
+    }
+
+    if let Some(bound) = uncovered {
+        let (start, end) = parse_range(&bound, 1, &options)?;
+        let file = advent_vault::open("data.txt")?;
+        let sections = count_uncovered_sections(BufReader::new(file), &options, (start, end))?;
+        println!("Sections within {} that no elf covers: {}", bound, sections);
+    }
+
+    Ok(())
 }
 
-fn part_one(file: File) -> u32 {
+fn part_one<R: Read>(file: R, options: &ParseOptions) -> Result<u32, AssignmentError> {
     let reader = BufReader::new(file);
-    count_fully_contained_pairs(reader)
+    count_fully_contained_pairs(reader, options)
 }
 
-fn part_two(file: File) -> u32 {
+fn part_two<R: Read>(file: R, options: &ParseOptions) -> Result<u32, AssignmentError> {
     let reader = BufReader::new(file);
-    count_overlapping_pairs(reader)
+    count_overlapping_pairs(reader, options)
+}
+
+/// Whether any elf's assignment is fully contained in another elf's assignment.
+fn has_contained_assignment(assignments: &[(u32, u32)]) -> bool {
+    let sets: Vec<RangeSet> = assignments.iter().map(|&(start, end)| RangeSet::from_range(start, end)).collect();
+    sets.iter().enumerate().any(|(i, inner)| {
+        sets.iter().enumerate().any(|(j, outer)| i != j && outer.contains(inner))
+    })
+}
+
+/// Whether any two elves share a section.
+fn has_overlapping_assignments(assignments: &[(u32, u32)]) -> bool {
+    let sets: Vec<RangeSet> = assignments.iter().map(|&(start, end)| RangeSet::from_range(start, end)).collect();
+    sets.iter().enumerate().any(|(i, a)| sets[i + 1..].iter().any(|b| a.overlaps(b)))
 }
 
-fn count_fully_contained_pairs<R: BufRead>(reader: R) -> u32 {
+fn count_fully_contained_pairs<R: BufRead>(reader: R, options: &ParseOptions) -> Result<u32, AssignmentError> {
     let mut count = 0;
 
-    for line in reader.lines() {
-        let line = line.unwrap();
-        let assignments: Vec<_> = line
-            .split(',')
-            .map(|range| {
-                let mut parts = range.split('-');
-                let start = parts.next().unwrap().trim().parse::<u32>().unwrap();
-                let end = parts.next().unwrap().trim().parse::<u32>().unwrap();
-                (start, end)
-            })
-            .collect();
-
-        let (start1, end1) = assignments[0];
-        let (start2, end2) = assignments[1];
-
-        if (start1 >= start2 && end1 <= end2) || (start2 >= start1 && end2 <= end1) {
+    for assignments in read_assignments(reader, *options) {
+        if has_contained_assignment(&assignments?) {
             count += 1;
         }
     }
 
-    count
+    Ok(count)
 }
 
-fn count_overlapping_pairs<R: BufRead>(reader: R) -> u32 {
+fn count_overlapping_pairs<R: BufRead>(reader: R, options: &ParseOptions) -> Result<u32, AssignmentError> {
     let mut count = 0;
 
-    for line in reader.lines() {
-        let line = line.unwrap();
-        let assignments: Vec<_> = line
-            .split(',')
-            .map(|range| {
-                let mut parts = range.split('-');
-                let start = parts.next().unwrap().trim().parse::<u32>().unwrap();
-                let end = parts.next().unwrap().trim().parse::<u32>().unwrap();
-                (start, end)
-            })
-            .collect();
-
-        let (start1, end1) = assignments[0];
-        let (start2, end2) = assignments[1];
-
-        if (start1 <= end2 && end1 >= start2) || (start2 <= end1 && end2 >= start1) {
+    for assignments in read_assignments(reader, *options) {
+        if has_overlapping_assignments(&assignments?) {
             count += 1;
         }
     }
 
-    count
+    Ok(count)
+}
+
+/// Total number of sections, summed over all groups, that at least `k` elves of a group cover.
+fn count_sections_covered_by_at_least<R: BufRead>(reader: R, options: &ParseOptions, k: usize) -> Result<u64, AssignmentError> {
+    read_assignments(reader, *options)
+        .map(|assignments| Ok(covered_by_at_least(&assignments?, k).len()))
+        .sum()
+}
+
+/// Total number of sections within `bound`, summed over all groups, that no elf of a group covers.
+fn count_uncovered_sections<R: BufRead>(reader: R, options: &ParseOptions, bound: (u32, u32)) -> Result<u64, AssignmentError> {
+    read_assignments(reader, *options)
+        .map(|assignments| Ok(uncovered_within(&assignments?, bound).len()))
+        .sum()
+}
+
+/// Prints how every pair of assignments within a group relates, one pair per line.
+fn print_report<R: BufRead>(reader: R, options: &ParseOptions) -> Result<(), AssignmentError> {
+    for (group, assignments) in read_assignments(reader, *options).enumerate() {
+        let assignments = assignments?;
+        for (i, &(start1, end1)) in assignments.iter().enumerate() {
+            for &(start2, end2) in &assignments[i + 1..] {
+                let relationship = Relationship::of((start1, end1), (start2, end2));
+                println!("{}: {}-{} {} {}-{}", group + 1, start1, end1, relationship, start2, end2);
+            }
+        }
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
+    use std::fs::File;
+    use std::collections::HashSet;
     use std::io::Cursor;
+    use propcheck::{ints, vecs, Fuzzer};
     use super::*;
 
     /**
@@ -92,13 +175,13 @@ mod tests {
     #[test]
     fn test_part_one() {
         let file = File::open("./test_data/example_data.txt").expect("Unable to open test_data.txt");
-        let count = part_one(file);
+        let count = part_one(file, &ParseOptions::default()).unwrap();
         assert_eq!(count, 2); // Update this value based on your test_data.txt
     }
     #[test]
     fn test_part_two() {
         let file = File::open("./test_data/example_data.txt").expect("Unable to open test_data.txt");
-        let count = part_two(file);
+        let count = part_two(file, &ParseOptions::default()).unwrap();
         assert_eq!(count, 4); // Update this value based on your test_data.txt
     }
 
@@ -120,7 +203,7 @@ mod tests {
             6-6,4-6
             2-6,4-8";
         let cursor = Cursor::new(input);
-        let count = count_fully_contained_pairs(cursor);
+        let count = count_fully_contained_pairs(cursor, &ParseOptions::default()).unwrap();
         assert_eq!(count, 2);
     }
 
@@ -134,7 +217,7 @@ mod tests {
             "1-3,4-6
             7-9,10-12";
         let cursor = Cursor::new(input);
-        let count = count_fully_contained_pairs(cursor);
+        let count = count_fully_contained_pairs(cursor, &ParseOptions::default()).unwrap();
         assert_eq!(count, 0);
     }
 
@@ -149,7 +232,7 @@ mod tests {
             3-6,1-6
             8-10,8-12";
         let cursor = Cursor::new(input);
-        let count = count_fully_contained_pairs(cursor);
+        let count = count_fully_contained_pairs(cursor, &ParseOptions::default()).unwrap();
         assert_eq!(count, 3);
     }
 
@@ -161,7 +244,7 @@ mod tests {
     fn test_empty_input() {
         let input = "";
         let cursor = Cursor::new(input);
-        let count = count_fully_contained_pairs(cursor);
+        let count = count_fully_contained_pairs(cursor, &ParseOptions::default()).unwrap();
         assert_eq!(count, 0);
     }
 
@@ -174,7 +257,7 @@ mod tests {
     fn test_single_pair_input() {
         let input = "1-3,1-5";
         let cursor = Cursor::new(input);
-        let count = count_fully_contained_pairs(cursor);
+        let count = count_fully_contained_pairs(cursor, &ParseOptions::default()).unwrap();
         assert_eq!(count, 1);
     }
 
@@ -194,8 +277,131 @@ mod tests {
             6-6,4-6
             2-6,4-8";
         let cursor = Cursor::new(input);
-        let count = count_overlapping_pairs(cursor);
+        let count = count_overlapping_pairs(cursor, &ParseOptions::default()).unwrap();
         assert_eq!(count, 4);
     }
 
+    /**
+     * Lines with more than two elves: a group counts as containing when any
+     * assignment lies inside another one, and as overlapping when any section is
+     * shared by two elves.
+     */
+    #[test]
+    fn test_groups_of_three() {
+        let input =
+            "1-2,4-5,7-8
+            1-2,4-5,2-4
+            3-3,1-9,10-12";
+        assert_eq!(count_fully_contained_pairs(Cursor::new(input), &ParseOptions::default()).unwrap(), 1);
+        assert_eq!(count_overlapping_pairs(Cursor::new(input), &ParseOptions::default()).unwrap(), 2);
+    }
+
+    /**
+     * Sections covered by at least k elves, and sections no elf covers within 1-9,
+     * summed over the example data.
+     */
+    #[test]
+    fn test_section_queries() {
+        let file = File::open("./test_data/example_data.txt").expect("Unable to open test_data.txt");
+        assert_eq!(count_sections_covered_by_at_least(BufReader::new(file), &ParseOptions::default(), 2).unwrap(), 1 + 5 + 1 + 3);
+
+        let file = File::open("./test_data/example_data.txt").expect("Unable to open test_data.txt");
+        assert_eq!(count_uncovered_sections(BufReader::new(file), &ParseOptions::default(), (1, 9)).unwrap(), 3 + 5 + 4 + 2 + 6 + 2);
+    }
+
+    /**
+     * A malformed line makes the count fail with the position of the problem
+     * instead of panicking.
+     */
+    #[test]
+    fn test_malformed_line_is_an_error() {
+        let input =
+            "2-4,6-8
+            7-3,4-5";
+        let result = count_overlapping_pairs(Cursor::new(input), &ParseOptions::default());
+        assert!(matches!(result, Err(AssignmentError::ReversedRange { line: 2, column: 13, .. })));
+
+        let options = ParseOptions { normalize_reversed: true };
+        assert_eq!(count_overlapping_pairs(Cursor::new(input), &options).unwrap(), 1);
+    }
+
+    fn sections(assignment: (u32, u32)) -> HashSet<u32> {
+        (assignment.0..=assignment.1).collect()
+    }
+
+    fn normalized(pairs: &[(i64, i64)]) -> Vec<(u32, u32)> {
+        pairs.iter().map(|&(a, b)| (a.min(b) as u32, a.max(b) as u32)).collect()
+    }
+
+    /**
+     * The predicates agree with comparing the sets of sections each elf is
+     * assigned, for random groups of elves.
+     */
+    #[test]
+    fn test_predicates_match_section_sets() {
+        let groups = vecs((ints(1, 12), ints(1, 12)), 2, 4);
+
+        propcheck::check_against("has_contained_assignment", &groups, |pairs| has_contained_assignment(&normalized(pairs)), |pairs| {
+            let sets: Vec<HashSet<u32>> = normalized(pairs).into_iter().map(sections).collect();
+            (0..sets.len()).any(|i| (0..sets.len()).any(|j| i != j && sets[i].is_subset(&sets[j])))
+        });
+
+        propcheck::check_against("has_overlapping_assignments", &groups, |pairs| has_overlapping_assignments(&normalized(pairs)), |pairs| {
+            let sets: Vec<HashSet<u32>> = normalized(pairs).into_iter().map(sections).collect();
+            (0..sets.len()).any(|i| (i + 1..sets.len()).any(|j| !sets[i].is_disjoint(&sets[j])))
+        });
+
+        propcheck::check_against("Relationship::of", &groups, |pairs| {
+            let pairs = normalized(pairs);
+            Relationship::of(pairs[0], pairs[1])
+        }, |pairs| {
+            let pairs = normalized(pairs);
+            let (first, second) = (sections(pairs[0]), sections(pairs[1]));
+            if first == second {
+                Relationship::Equal
+            } else if second.is_subset(&first) {
+                Relationship::Contains
+            } else if first.is_subset(&second) {
+                Relationship::ContainedBy
+            } else if !first.is_disjoint(&second) {
+                Relationship::Overlapping
+            } else {
+                Relationship::Disjoint
+            }
+        });
+    }
+
+    /**
+     * Random and mutated input makes every count return, never panic, with
+     * and without normalisation.
+     */
+    #[test]
+    fn test_fuzz_counts() {
+        let example = std::fs::read_to_string("./test_data/example_data.txt").unwrap();
+        Fuzzer::new("day4_counts")
+            .seed_input(&example)
+            .seed_input("2-4,6-8,1-9\n")
+            .seed_input("0-4294967295,7-7\n")
+            .run(|bytes| {
+                for normalize_reversed in [false, true] {
+                    let options = ParseOptions { normalize_reversed };
+                    count_fully_contained_pairs(bytes, &options)?;
+                    count_overlapping_pairs(bytes, &options)?;
+                    count_sections_covered_by_at_least(bytes, &options, 2)?;
+                    count_uncovered_sections(bytes, &options, (0, u32::MAX))?;
+                }
+                Ok::<_, AssignmentError>(())
+            });
+    }
+
+    /**
+     * A lone range, as given to --uncovered, never makes the parser panic.
+     */
+    #[test]
+    fn test_fuzz_parse_range() {
+        Fuzzer::new("day4_parse_range")
+            .seed_input("2-4")
+            .seed_input(" 10 - 20 ")
+            .run(|bytes| parse_range(&String::from_utf8_lossy(bytes), 1, &ParseOptions::default()));
+    }
 }
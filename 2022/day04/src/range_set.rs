@@ -0,0 +1,223 @@
+/// A set of section IDs stored as sorted, disjoint and non-adjacent
+/// inclusive ranges.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RangeSet {
+    ranges: Vec<(u32, u32)>,
+}
+
+impl RangeSet {
+    pub fn new() -> Self {
+        RangeSet::default()
+    }
+
+    /// Set covering `start..=end`, empty if `start > end`.
+    pub fn from_range(start: u32, end: u32) -> Self {
+        let mut set = RangeSet::new();
+        set.insert(start, end);
+        set
+    }
+
+    pub fn insert(&mut self, start: u32, end: u32) {
+        if start > end {
+            return;
+        }
+        let (mut start, mut end) = (start, end);
+
+        // Ranges that overlap or touch the new one are merged into it
+        let first = self.ranges.partition_point(|&(_, e)| e.saturating_add(1) < start);
+        let mut last = first;
+        while last < self.ranges.len() && self.ranges[last].0 <= end.saturating_add(1) {
+            start = start.min(self.ranges[last].0);
+            end = end.max(self.ranges[last].1);
+            last += 1;
+        }
+        self.ranges.splice(first..last, [(start, end)]);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Number of sections in the set.
+    pub fn len(&self) -> u64 {
+        self.ranges.iter().map(|&(start, end)| (end - start) as u64 + 1).sum()
+    }
+
+    /// Whether every section of `other` is also in this set.
+    pub fn contains(&self, other: &RangeSet) -> bool {
+        other.difference(self).is_empty()
+    }
+
+    pub fn union(&self, other: &RangeSet) -> RangeSet {
+        let mut result = self.clone();
+        for &(start, end) in &other.ranges {
+            result.insert(start, end);
+        }
+        result
+    }
+
+    pub fn intersection(&self, other: &RangeSet) -> RangeSet {
+        let mut result = RangeSet::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a_start, a_end) = self.ranges[i];
+            let (b_start, b_end) = other.ranges[j];
+            let (start, end) = (a_start.max(b_start), a_end.min(b_end));
+            if start <= end {
+                result.ranges.push((start, end));
+            }
+            if a_end < b_end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        result
+    }
+
+    pub fn difference(&self, other: &RangeSet) -> RangeSet {
+        let mut result = RangeSet::new();
+        for &(start, end) in &self.ranges {
+            // Widened so that a cut ending at u32::MAX doesn't overflow
+            let mut current = start as u64;
+            let first = other.ranges.partition_point(|&(_, cut_end)| cut_end < start);
+            for &(cut_start, cut_end) in other.ranges[first..].iter().take_while(|&&(cut_start, _)| cut_start <= end) {
+                if cut_start as u64 > current {
+                    result.ranges.push((current as u32, cut_start - 1));
+                }
+                current = current.max(cut_end as u64 + 1);
+            }
+            if current <= end as u64 {
+                result.ranges.push((current as u32, end));
+            }
+        }
+        result
+    }
+
+    pub fn overlaps(&self, other: &RangeSet) -> bool {
+        !self.intersection(other).is_empty()
+    }
+}
+
+impl FromIterator<(u32, u32)> for RangeSet {
+    fn from_iter<I: IntoIterator<Item = (u32, u32)>>(iter: I) -> Self {
+        let mut set = RangeSet::new();
+        for (start, end) in iter {
+            set.insert(start, end);
+        }
+        set
+    }
+}
+
+/// Sections assigned to at least `k` of the given ranges.
+pub fn covered_by_at_least(assignments: &[(u32, u32)], k: usize) -> RangeSet {
+    if k == 0 {
+        return RangeSet::from_range(0, u32::MAX);
+    }
+
+    // Sweep over range boundaries, ends are exclusive so they sort before starts at the same point
+    let mut events: Vec<(u64, i32)> = Vec::with_capacity(assignments.len() * 2);
+    for &(start, end) in assignments.iter().filter(|(start, end)| start <= end) {
+        events.push((start as u64, 1));
+        events.push((end as u64 + 1, -1));
+    }
+    events.sort();
+
+    let mut result = RangeSet::new();
+    let mut depth = 0;
+    let mut covered_since = None;
+    for (point, delta) in events {
+        depth += delta;
+        match covered_since {
+            None if depth >= k as i32 => covered_since = Some(point),
+            Some(start) if depth < k as i32 => {
+                result.insert(start as u32, (point - 1) as u32);
+                covered_since = None;
+            }
+            _ => {}
+        }
+    }
+    result
+}
+
+/// Sections within `bound` that none of the given ranges cover.
+pub fn uncovered_within(assignments: &[(u32, u32)], bound: (u32, u32)) -> RangeSet {
+    let covered = assignments
+        .iter()
+        .fold(RangeSet::new(), |covered, &(start, end)| covered.union(&RangeSet::from_range(start, end)));
+    RangeSet::from_range(bound.0, bound.1).difference(&covered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Inserting overlapping and adjacent ranges merges them, disjoint ones stay separate
+     * and reversed ranges are ignored.
+     */
+    #[test]
+    fn test_insert_merges_ranges() {
+        let set: RangeSet = vec![(5, 7), (1, 2), (3, 3), (10, 12), (11, 20), (9, 4)].into_iter().collect();
+        assert_eq!(set.ranges, [(1, 3), (5, 7), (10, 20)]);
+        assert_eq!(set.len(), 3 + 3 + 11);
+        assert!(set.contains(&RangeSet::from_range(6, 6)));
+        assert!(!set.contains(&RangeSet::from_range(4, 4)));
+        assert!(!set.contains(&RangeSet::from_range(20, 21)));
+    }
+
+    /**
+     * Union, intersection and difference of two sets with several ranges each.
+     */
+    #[test]
+    fn test_set_operations() {
+        let a: RangeSet = vec![(1, 5), (10, 15)].into_iter().collect();
+        let b: RangeSet = vec![(4, 11), (14, 14), (20, 21)].into_iter().collect();
+
+        assert_eq!(a.union(&b).ranges, [(1, 15), (20, 21)]);
+        assert_eq!(a.intersection(&b).ranges, [(4, 5), (10, 11), (14, 14)]);
+        assert_eq!(a.difference(&b).ranges, [(1, 3), (12, 13), (15, 15)]);
+        assert_eq!(b.difference(&a).ranges, [(6, 9), (20, 21)]);
+        assert!(a.overlaps(&b));
+        assert!(a.union(&b).contains(&a));
+        assert!(a.union(&b).contains(&b));
+        assert_eq!(a.union(&RangeSet::new()), a);
+        assert!(!a.contains(&b));
+        assert!(a.contains(&RangeSet::new()));
+    }
+
+    /**
+     * Ranges touching the ends of the u32 domain must not overflow.
+     */
+    #[test]
+    fn test_domain_edges() {
+        let full = RangeSet::from_range(0, u32::MAX);
+        assert_eq!(full.len(), u32::MAX as u64 + 1);
+        assert!(full.difference(&full).is_empty());
+        assert_eq!(full.difference(&RangeSet::from_range(0, 9)).ranges, [(10, u32::MAX)]);
+        assert_eq!(full.difference(&RangeSet::from_range(10, u32::MAX)).ranges, [(0, 9)]);
+    }
+
+    /**
+     * With three elves, sections 4-5 are covered by all of them and 2-8 by at least two.
+     */
+    #[test]
+    fn test_covered_by_at_least() {
+        let assignments = [(2, 5), (4, 8), (1, 10)];
+        assert_eq!(covered_by_at_least(&assignments, 1).ranges, [(1, 10)]);
+        assert_eq!(covered_by_at_least(&assignments, 2).ranges, [(2, 8)]);
+        assert_eq!(covered_by_at_least(&assignments, 3).ranges, [(4, 5)]);
+        assert!(covered_by_at_least(&assignments, 4).is_empty());
+        assert_eq!(covered_by_at_least(&[(1, 3), (3, 5)], 2).ranges, [(3, 3)]);
+    }
+
+    /**
+     * Sections outside every assignment but inside the bound are reported.
+     */
+    #[test]
+    fn test_uncovered_within() {
+        let assignments = [(2, 4), (6, 8)];
+        assert_eq!(uncovered_within(&assignments, (1, 9)).ranges, [(1, 1), (5, 5), (9, 9)]);
+        assert!(uncovered_within(&assignments, (6, 7)).is_empty());
+    }
+}
@@ -0,0 +1,207 @@
+use std::fmt;
+use thiserror::Error;
+
+use crate::crane::{Crane, CraneError};
+use crate::stacks::Stacks;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum CommandError {
+    #[error("line {line}: unknown command '{verb}', expected move, swap or reverse")]
+    UnknownVerb { line: usize, verb: String },
+
+    #[error("line {line}: expected '{usage}', found '{found}'")]
+    Malformed { line: usize, usage: &'static str, found: String },
+
+    #[error("line {line}: expected a number, found '{found}'")]
+    InvalidNumber { line: usize, found: String },
+
+    #[error("line {line}: stack {stack} does not exist, the drawing has {stacks} stacks")]
+    NoSuchStack { line: usize, stack: usize, stacks: usize },
+}
+
+/// A rearrangement step, stack numbers are 1-based as in the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// `move N from A to B`
+    Move { count: usize, from: usize, to: usize },
+    /// `move all from A to B`
+    MoveAll { from: usize, to: usize },
+    /// `swap A B` exchanges two whole stacks.
+    Swap { first: usize, second: usize },
+    /// `reverse N on S` flips the top N crates of a stack.
+    Reverse { count: usize, stack: usize },
+}
+
+const MOVE_USAGE: &str = "move N|all from A to B";
+const SWAP_USAGE: &str = "swap A B";
+const REVERSE_USAGE: &str = "reverse N on S";
+
+impl Command {
+    /// Parses a command and checks its stack numbers against the `stack_count`
+    /// stacks of the drawing. `line_number` is only used for error reporting.
+    pub fn parse(line: &str, line_number: usize, stack_count: usize) -> Result<Command, CommandError> {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let malformed = |usage| CommandError::Malformed { line: line_number, usage, found: line.trim().to_owned() };
+        let number = |part: &str| {
+            part.parse::<usize>()
+                .map_err(|_| CommandError::InvalidNumber { line: line_number, found: part.to_owned() })
+        };
+        let stack = |part: &str| {
+            let stack = number(part)?;
+            if stack == 0 || stack > stack_count {
+                return Err(CommandError::NoSuchStack { line: line_number, stack, stacks: stack_count });
+            }
+            Ok(stack)
+        };
+
+        match parts.as_slice() {
+            ["move", "all", "from", from, "to", to] => Ok(Command::MoveAll { from: stack(from)?, to: stack(to)? }),
+            ["move", count, "from", from, "to", to] => {
+                Ok(Command::Move { count: number(count)?, from: stack(from)?, to: stack(to)? })
+            }
+            ["swap", first, second] => Ok(Command::Swap { first: stack(first)?, second: stack(second)? }),
+            ["reverse", count, "on", on] => Ok(Command::Reverse { count: number(count)?, stack: stack(on)? }),
+            ["move", ..] => Err(malformed(MOVE_USAGE)),
+            ["swap", ..] => Err(malformed(SWAP_USAGE)),
+            ["reverse", ..] => Err(malformed(REVERSE_USAGE)),
+            [verb, ..] => Err(CommandError::UnknownVerb { line: line_number, verb: verb.to_string() }),
+            [] => Err(malformed(MOVE_USAGE)),
+        }
+    }
+
+    /// Carries out the command, moves go through `crane`. A failed command
+    /// leaves the stacks untouched.
+    pub fn execute(&self, stacks: &mut Stacks, crane: &dyn Crane) -> Result<(), CraneError> {
+        for stack in self.stacks() {
+            if stacks.stack(stack).is_none() {
+                return Err(CraneError::NoSuchStack(stack));
+            }
+        }
+
+        match *self {
+            Command::Move { count, from, to } => crane.apply(stacks, count, from, to),
+            Command::MoveAll { from, to } => {
+                let count = stacks.stack(from).map_or(0, |stack| stack.len());
+                crane.apply(stacks, count, from, to)
+            }
+            Command::Swap { first, second } => {
+                stacks.stacks_mut().swap(first - 1, second - 1);
+                Ok(())
+            }
+            Command::Reverse { count, stack } => {
+                let stack_crates = &mut stacks.stacks_mut()[stack - 1];
+                if stack_crates.len() < count {
+                    return Err(CraneError::NotEnoughCrates { stack, requested: count, available: stack_crates.len() });
+                }
+                stack_crates.make_contiguous()[..count].reverse();
+                Ok(())
+            }
+        }
+    }
+
+    /// Reverts the command on stacks it has been carried out on. Moving all
+    /// crates can't be undone as their number isn't known afterwards.
+    pub fn undo(&self, stacks: &mut Stacks, crane: &dyn Crane) -> Result<(), CraneError> {
+        match *self {
+            Command::Move { count, from, to } => crane.unapply(stacks, count, from, to),
+            Command::MoveAll { .. } => Err(CraneError::Irreversible(self.to_string())),
+            // Both are their own inverse
+            Command::Swap { .. } | Command::Reverse { .. } => self.execute(stacks, crane),
+        }
+    }
+
+    /// The stack numbers the command refers to.
+    fn stacks(&self) -> Vec<usize> {
+        match *self {
+            Command::Move { from, to, .. } | Command::MoveAll { from, to } => vec![from, to],
+            Command::Swap { first, second } => vec![first, second],
+            Command::Reverse { stack, .. } => vec![stack],
+        }
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Command::Move { count, from, to } => write!(f, "move {} from {} to {}", count, from, to),
+            Command::MoveAll { from, to } => write!(f, "move all from {} to {}", from, to),
+            Command::Swap { first, second } => write!(f, "swap {} {}", first, second),
+            Command::Reverse { count, stack } => write!(f, "reverse {} on {}", count, stack),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crane::{CrateMover9000, CrateMover9001};
+
+    fn example_stacks() -> Stacks {
+        Stacks::parse("    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 ").unwrap()
+    }
+
+    fn contents(stacks: &Stacks, number: usize) -> String {
+        stacks.stack(number).unwrap().iter().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(Command::parse("move 2 from 1 to 3", 1, 3), Ok(Command::Move { count: 2, from: 1, to: 3 }));
+        assert_eq!(Command::parse("  move all from 3 to 1 ", 1, 3), Ok(Command::MoveAll { from: 3, to: 1 }));
+        assert_eq!(Command::parse("swap 1 2", 1, 3), Ok(Command::Swap { first: 1, second: 2 }));
+        assert_eq!(Command::parse("reverse 2 on 3", 1, 3), Ok(Command::Reverse { count: 2, stack: 3 }));
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for line in ["move 2 from 1 to 3", "move all from 3 to 1", "swap 1 2", "reverse 2 on 3"] {
+            assert_eq!(Command::parse(line, 1, 3).unwrap().to_string(), line);
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Command::parse("move 2 from 1", 7, 3),
+            Err(CommandError::Malformed { line: 7, usage: MOVE_USAGE, found: "move 2 from 1".to_owned() })
+        );
+        assert_eq!(
+            Command::parse("move two from 1 to 3", 7, 3),
+            Err(CommandError::InvalidNumber { line: 7, found: "two".to_owned() })
+        );
+        assert_eq!(
+            Command::parse("move 1 from 4 to 3", 8, 3),
+            Err(CommandError::NoSuchStack { line: 8, stack: 4, stacks: 3 })
+        );
+        assert_eq!(
+            Command::parse("swap 0 1", 9, 3),
+            Err(CommandError::NoSuchStack { line: 9, stack: 0, stacks: 3 })
+        );
+        assert_eq!(
+            Command::parse("lift 1 from 1 to 2", 10, 3),
+            Err(CommandError::UnknownVerb { line: 10, verb: "lift".to_owned() })
+        );
+        assert!(matches!(Command::parse("reverse 1 of 2", 11, 3), Err(CommandError::Malformed { line: 11, .. })));
+    }
+
+    #[test]
+    fn test_execute_new_verbs() {
+        let mut stacks = example_stacks();
+        Command::MoveAll { from: 2, to: 3 }.execute(&mut stacks, &CrateMover9001).unwrap();
+        assert_eq!(stacks.top_crates(), "ND");
+        assert_eq!(stacks.stack(3).unwrap().len(), 4);
+
+        Command::Reverse { count: 3, stack: 3 }.execute(&mut stacks, &CrateMover9000).unwrap();
+        assert_eq!(contents(&stacks, 3), "MCDP");
+
+        Command::Swap { first: 1, second: 3 }.execute(&mut stacks, &CrateMover9000).unwrap();
+        assert_eq!(contents(&stacks, 1), "MCDP");
+        assert_eq!(contents(&stacks, 3), "NZ");
+
+        assert_eq!(
+            Command::Reverse { count: 3, stack: 3 }.execute(&mut stacks, &CrateMover9000),
+            Err(CraneError::NotEnoughCrates { stack: 3, requested: 3, available: 2 })
+        );
+        assert_eq!(Command::Swap { first: 1, second: 4 }.execute(&mut stacks, &CrateMover9000), Err(CraneError::NoSuchStack(4)));
+    }
+}
@@ -0,0 +1,227 @@
+use thiserror::Error;
+
+use crate::stacks::{Crate, Stacks};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum CraneError {
+    #[error("stack {0} does not exist")]
+    NoSuchStack(usize),
+
+    #[error("cannot move {requested} crates from stack {stack}, it only holds {available}")]
+    NotEnoughCrates { stack: usize, requested: usize, available: usize },
+
+    #[error("unknown crane model '{0}', expected 9000, 9001 or batch:K")]
+    UnknownModel(String),
+
+    #[error("'{0}' cannot be undone, the number of crates it moved is unknown")]
+    Irreversible(String),
+}
+
+/// A crane model that knows how to carry out a single `move` command.
+///
+/// Stacks are stored top first and `from`/`to` are 1-based, as in the commands.
+/// A failed move leaves the stacks untouched.
+pub trait Crane {
+    fn name(&self) -> String;
+
+    /// Where the moved crates end up on the target stack, top first: the
+    /// crate placed at position `i` is the one lifted at `arrangement(count)[i]`,
+    /// with the lifted crates also counted from the top.
+    fn arrangement(&self, count: usize) -> Vec<usize>;
+
+    /// The order the moved crates end up in on the target stack, top first,
+    /// given the crates as they were taken off the source stack, top first.
+    fn arrange(&self, lifted: Vec<Crate>) -> Vec<Crate> {
+        self.arrangement(lifted.len()).into_iter().map(|index| lifted[index].clone()).collect()
+    }
+
+    /// Inverse of `arrange`: the order the crates were lifted in, given the
+    /// order they ended up in.
+    fn unarrange(&self, placed: Vec<Crate>) -> Vec<Crate> {
+        let mut lifted = vec![Crate::new(); placed.len()];
+        for (label, index) in placed.into_iter().zip(self.arrangement(lifted.len())) {
+            lifted[index] = label;
+        }
+        lifted
+    }
+
+    fn apply(&self, stacks: &mut Stacks, num_crates: usize, from: usize, to: usize) -> Result<(), CraneError> {
+        for index in [from, to] {
+            if stacks.stack(index).is_none() {
+                return Err(CraneError::NoSuchStack(index));
+            }
+        }
+        let available = stacks.stack(from).map_or(0, |stack| stack.len());
+        if available < num_crates {
+            return Err(CraneError::NotEnoughCrates { stack: from, requested: num_crates, available });
+        }
+
+        let stacks = stacks.stacks_mut();
+
+        let lifted: Vec<Crate> = stacks[from - 1].drain(..num_crates).collect();
+        for label in self.arrange(lifted).into_iter().rev() {
+            stacks[to - 1].push_front(label);
+        }
+        Ok(())
+    }
+
+    /// Undoes `apply`, moving the top `num_crates` of `to` back onto `from`.
+    fn unapply(&self, stacks: &mut Stacks, num_crates: usize, from: usize, to: usize) -> Result<(), CraneError> {
+        for index in [from, to] {
+            if stacks.stack(index).is_none() {
+                return Err(CraneError::NoSuchStack(index));
+            }
+        }
+        let available = stacks.stack(to).map_or(0, |stack| stack.len());
+        if available < num_crates {
+            return Err(CraneError::NotEnoughCrates { stack: to, requested: num_crates, available });
+        }
+
+        let stacks = stacks.stacks_mut();
+
+        let placed: Vec<Crate> = stacks[to - 1].drain(..num_crates).collect();
+        for label in self.unarrange(placed).into_iter().rev() {
+            stacks[from - 1].push_front(label);
+        }
+        Ok(())
+    }
+}
+
+/// Moves one crate at a time, so the moved crates end up reversed.
+pub struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn name(&self) -> String {
+        "CrateMover 9000".to_owned()
+    }
+
+    fn arrangement(&self, count: usize) -> Vec<usize> {
+        (0..count).rev().collect()
+    }
+}
+
+/// Moves all crates at once, keeping their order.
+pub struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn name(&self) -> String {
+        "CrateMover 9001".to_owned()
+    }
+
+    fn arrangement(&self, count: usize) -> Vec<usize> {
+        (0..count).collect()
+    }
+}
+
+/// Moves at most `max_per_lift` crates at once, keeping their order within a lift.
+pub struct BatchCrane {
+    pub max_per_lift: usize,
+}
+
+impl Crane for BatchCrane {
+    fn name(&self) -> String {
+        format!("batch crane ({} per lift)", self.max_per_lift)
+    }
+
+    fn arrangement(&self, count: usize) -> Vec<usize> {
+        // Later lifts land on top of earlier ones
+        let lifted: Vec<usize> = (0..count).collect();
+        lifted.chunks(self.max_per_lift.max(1)).rev().flatten().copied().collect()
+    }
+}
+
+/// Crane for a model name as given on the command line.
+pub fn crane_from_name(name: &str) -> Result<Box<dyn Crane>, CraneError> {
+    match name {
+        "9000" => Ok(Box::new(CrateMover9000)),
+        "9001" => Ok(Box::new(CrateMover9001)),
+        _ => name
+            .strip_prefix("batch:")
+            .and_then(|k| k.parse::<usize>().ok())
+            .filter(|&k| k > 0)
+            .map(|max_per_lift| Box::new(BatchCrane { max_per_lift }) as Box<dyn Crane>)
+            .ok_or_else(|| CraneError::UnknownModel(name.to_owned())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::VecDeque;
+
+    /// Stacks of single-letter crates, top first.
+    fn stacks(contents: &[&str]) -> Stacks {
+        let stacks: Vec<VecDeque<Crate>> = contents.iter().map(|s| s.chars().map(String::from).collect()).collect();
+        Stacks::from(stacks)
+    }
+
+    fn crates(labels: &str) -> Vec<Crate> {
+        labels.chars().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_crate_movers() {
+        let mut state = stacks(&["ABC", "D"]);
+        CrateMover9000.apply(&mut state, 3, 1, 2).unwrap();
+        assert_eq!(state, stacks(&["", "CBAD"]));
+
+        let mut state = stacks(&["ABC", "D"]);
+        CrateMover9001.apply(&mut state, 3, 1, 2).unwrap();
+        assert_eq!(state, stacks(&["", "ABCD"]));
+    }
+
+    #[test]
+    fn test_batch_crane() {
+        let mut state = stacks(&["ABCDE", ""]);
+        BatchCrane { max_per_lift: 2 }.apply(&mut state, 5, 1, 2).unwrap();
+        assert_eq!(state, stacks(&["", "ECDAB"]));
+        assert_eq!(BatchCrane { max_per_lift: 2 }.arrangement(5), [4, 2, 3, 0, 1]);
+
+        // One crate per lift behaves like the 9000, unlimited like the 9001
+        assert_eq!(BatchCrane { max_per_lift: 1 }.arrange(crates("ABC")), CrateMover9000.arrange(crates("ABC")));
+        assert_eq!(BatchCrane { max_per_lift: 9 }.arrange(crates("ABC")), CrateMover9001.arrange(crates("ABC")));
+    }
+
+    #[test]
+    fn test_invalid_moves_leave_stacks_untouched() {
+        let mut state = stacks(&["AB", "C"]);
+        assert_eq!(
+            CrateMover9000.apply(&mut state, 3, 1, 2),
+            Err(CraneError::NotEnoughCrates { stack: 1, requested: 3, available: 2 })
+        );
+        assert_eq!(CrateMover9001.apply(&mut state, 1, 1, 3), Err(CraneError::NoSuchStack(3)));
+        assert_eq!(CrateMover9001.apply(&mut state, 1, 0, 2), Err(CraneError::NoSuchStack(0)));
+        assert_eq!(state, stacks(&["AB", "C"]));
+    }
+
+    #[test]
+    fn test_unapply_undoes_apply() {
+        let cranes: [&dyn Crane; 4] = [&CrateMover9000, &CrateMover9001, &BatchCrane { max_per_lift: 2 }, &BatchCrane { max_per_lift: 3 }];
+        for crane in cranes {
+            let mut state = stacks(&["ABCDEFG", "H"]);
+            crane.apply(&mut state, 7, 1, 2).unwrap();
+            crane.unapply(&mut state, 7, 1, 2).unwrap();
+            assert_eq!(state, stacks(&["ABCDEFG", "H"]), "{}", crane.name());
+
+            // Moving onto the same stack can be undone as well
+            crane.apply(&mut state, 5, 1, 1).unwrap();
+            crane.unapply(&mut state, 5, 1, 1).unwrap();
+            assert_eq!(state, stacks(&["ABCDEFG", "H"]), "{}", crane.name());
+        }
+
+        let mut state = stacks(&["", "A"]);
+        assert_eq!(
+            CrateMover9000.unapply(&mut state, 2, 1, 2),
+            Err(CraneError::NotEnoughCrates { stack: 2, requested: 2, available: 1 })
+        );
+    }
+
+    #[test]
+    fn test_crane_from_name() {
+        assert_eq!(crane_from_name("9000").unwrap().name(), "CrateMover 9000");
+        assert_eq!(crane_from_name("batch:3").unwrap().name(), "batch crane (3 per lift)");
+        assert!(crane_from_name("batch:0").is_err());
+        assert!(crane_from_name("9002").is_err());
+    }
+}
@@ -0,0 +1,111 @@
+use thiserror::Error;
+
+use crate::command::Command;
+use crate::crane::{Crane, CraneError};
+use crate::stacks::Stacks;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum InverseError {
+    #[error("step {step} ({command}): {source}")]
+    Step { step: usize, command: Command, source: CraneError },
+
+    #[error("running the commands again does not reproduce the stacks:\n{expected}\nbut gives\n{actual}")]
+    RoundTripMismatch { expected: Stacks, actual: Stacks },
+}
+
+/// Runs `commands` forward from `initial`.
+pub fn run_forward(initial: &Stacks, commands: &[Command], crane: &dyn Crane) -> Result<Stacks, InverseError> {
+    let mut stacks = initial.clone();
+    for (index, command) in commands.iter().enumerate() {
+        command
+            .execute(&mut stacks, crane)
+            .map_err(|source| InverseError::Step { step: index + 1, command: *command, source })?;
+    }
+    Ok(stacks)
+}
+
+/// Runs `commands` backwards from `final_stacks` to the stacks they started from.
+pub fn run_backward(final_stacks: &Stacks, commands: &[Command], crane: &dyn Crane) -> Result<Stacks, InverseError> {
+    let mut stacks = final_stacks.clone();
+    for (index, command) in commands.iter().enumerate().rev() {
+        command
+            .undo(&mut stacks, crane)
+            .map_err(|source| InverseError::Step { step: index + 1, command: *command, source })?;
+    }
+    Ok(stacks)
+}
+
+/// Reconstructs the initial stacks and checks that running the commands from
+/// them gives `final_stacks` again.
+pub fn reconstruct_initial(final_stacks: &Stacks, commands: &[Command], crane: &dyn Crane) -> Result<Stacks, InverseError> {
+    let initial = run_backward(final_stacks, commands, crane)?;
+    let replayed = run_forward(&initial, commands, crane)?;
+    if &replayed != final_stacks {
+        return Err(InverseError::RoundTripMismatch { expected: final_stacks.clone(), actual: replayed });
+    }
+    Ok(initial)
+}
+
+/// Checks that running the commands forward from `initial` and then backward
+/// returns to `initial`, and returns the final stacks.
+pub fn verify_round_trip(initial: &Stacks, commands: &[Command], crane: &dyn Crane) -> Result<Stacks, InverseError> {
+    let final_stacks = run_forward(initial, commands, crane)?;
+    let restored = run_backward(&final_stacks, commands, crane)?;
+    if &restored != initial {
+        return Err(InverseError::RoundTripMismatch { expected: initial.clone(), actual: restored });
+    }
+    Ok(final_stacks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crane::{BatchCrane, CrateMover9000, CrateMover9001};
+
+    fn example_stacks() -> Stacks {
+        Stacks::parse("    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 ").unwrap()
+    }
+
+    fn commands(lines: &[&str]) -> Vec<Command> {
+        lines.iter().map(|line| Command::parse(line, 1, 3).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_reconstruct_example() {
+        let commands = commands(&["move 1 from 2 to 1", "move 3 from 1 to 3", "move 2 from 2 to 1", "move 1 from 1 to 2"]);
+        let final_9000 = Stacks::parse("        [Z]\n        [N]\n        [D]\n[C] [M] [P]\n 1   2   3 ").unwrap();
+        let final_9001 = Stacks::parse("        [D]\n        [N]\n        [Z]\n[M] [C] [P]\n 1   2   3 ").unwrap();
+
+        assert_eq!(reconstruct_initial(&final_9000, &commands, &CrateMover9000).unwrap(), example_stacks());
+        assert_eq!(reconstruct_initial(&final_9001, &commands, &CrateMover9001).unwrap(), example_stacks());
+    }
+
+    #[test]
+    fn test_round_trip_with_every_verb() {
+        let commands = commands(&["swap 1 3", "reverse 2 on 2", "move 3 from 2 to 1", "move 2 from 1 to 3"]);
+        for crane in [&CrateMover9000 as &dyn Crane, &CrateMover9001, &BatchCrane { max_per_lift: 2 }] {
+            let final_stacks = verify_round_trip(&example_stacks(), &commands, crane).unwrap();
+            assert_eq!(reconstruct_initial(&final_stacks, &commands, crane).unwrap(), example_stacks());
+        }
+    }
+
+    #[test]
+    fn test_undo_errors() {
+        let commands = commands(&["move 1 from 1 to 2", "move all from 2 to 3"]);
+        assert!(matches!(
+            run_backward(&example_stacks(), &commands, &CrateMover9000),
+            Err(InverseError::Step { step: 2, source: CraneError::Irreversible(_), .. })
+        ));
+
+        // Stack 3 only holds one crate, so it can't have received two
+        let commands = self::commands(&["move 2 from 1 to 3"]);
+        assert_eq!(
+            run_backward(&example_stacks(), &commands, &CrateMover9000),
+            Err(InverseError::Step {
+                step: 1,
+                command: commands[0],
+                source: CraneError::NotEnoughCrates { stack: 3, requested: 2, available: 1 },
+            })
+        );
+    }
+}
@@ -1,165 +1,139 @@
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-use std::collections::VecDeque;
-
-#[derive(Debug, PartialEq)]
-pub enum ParseCargoCrateLineError {
-    InvalidFormat,
+use std::env;
+use std::io::{self, BufRead, BufReader};
+use std::process;
+
+mod command;
+mod crane;
+mod inverse;
+mod replay;
+mod stacks;
+use command::Command;
+use crane::{crane_from_name, Crane, CrateMover9000, CrateMover9001};
+use inverse::{reconstruct_initial, verify_round_trip};
+use replay::{replay, ReplayFormat};
+use stacks::Stacks;
+
+// Usage: day5 [--crane 9000|9001|batch:K] [--replay ascii|json | --inverse | --verify] [input]
+//
+// With --inverse the drawing in the input is taken as the final state and the
+// initial drawing is printed. --verify checks that the commands can be run
+// backwards from the final state to the input's drawing.
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("Error: {}", err);
+        process::exit(1);
+    }
 }
 
-pub enum Part {
-    A,
-    B
-}
+fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let mut input_file = "./data.txt".to_owned();
+    let mut crane: Option<Box<dyn Crane>> = None;
+    let mut replay_format = None;
+    let mut inverse = false;
+    let mut verify = false;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--crane" => crane = Some(crane_from_name(&args.next().unwrap_or_default())?),
+            "--replay" => match args.next().as_deref() {
+                Some("ascii") => replay_format = Some(ReplayFormat::Ascii),
+                Some("json") => replay_format = Some(ReplayFormat::Json),
+                _ => return Err("--replay expects ascii or json".into()),
+            },
+            "--inverse" => inverse = true,
+            "--verify" => verify = true,
+            _ => input_file = arg,
+        }
+    }
+
+    if let Some(format) = replay_format {
+        let crane = crane.unwrap_or_else(|| Box::new(CrateMover9000));
+        let (stacks, commands) = read_and_parse_input_file(&input_file)?;
+        replay(stacks, &commands, crane.as_ref(), format, &mut io::stdout().lock())?;
+        return Ok(());
+    }
 
-fn main() -> () {
-    // Read and parse input file
-    // let input_file = "./test_data/example_data.txt";
-    let input_file = "./data.txt";
-    if let Ok(top_chars) = process(input_file, &Part::A) {
-        println!("{}", top_chars);
+    if inverse || verify {
+        let crane = crane.unwrap_or_else(|| Box::new(CrateMover9000));
+        let (stacks, commands) = read_and_parse_input_file(&input_file)?;
+        let stacks = if inverse {
+            reconstruct_initial(&stacks, &commands, crane.as_ref())?
+        } else {
+            verify_round_trip(&stacks, &commands, crane.as_ref())?
+        };
+        print!("{}", stacks);
+        return Ok(());
     }
-    if let Ok(top_chars) = process(input_file, &Part::B) {
-        println!("{}", top_chars);
+
+    let cranes: Vec<Box<dyn Crane>> = match crane {
+        Some(crane) => vec![crane],
+        None => vec![Box::new(CrateMover9000), Box::new(CrateMover9001)],
+    };
+    for crane in cranes {
+        println!("{}", process(&input_file, crane.as_ref())?);
     }
+    Ok(())
 }
 
-fn process(input_file: &str, part: &Part) -> Result<String, Box<dyn std::error::Error>> {
+fn process(input_file: &str, crane: &dyn Crane) -> Result<String, Box<dyn std::error::Error>> {
     let (mut stacks, commands) = read_and_parse_input_file(input_file)?;
 
     // Process commands
-    for (num_crates, from, to) in commands {
-        // Account for 1-based indexing in commands
-        let from_idx = from - 1;
-        let to_idx = to - 1;
-
-        match part {
-            Part::A => {
-                // Move crates between stacks
-                for _ in 0..num_crates {
-                    if let Some(crate_char) = stacks[from_idx].pop_front() {
-                        stacks[to_idx].push_front(crate_char);
-                    } else {
-                        eprintln!("Error: Not enough crates in stack {} to move", from);
-                        return Err("Not enough crates to move".into());
-                    }
-                }
-            },
-            Part::B => {
-                // Move all at once between stacks
-                let mut values: VecDeque<char>= VecDeque::new();
-                for _ in 0..num_crates {
-                    if let Some(crate_char) = stacks[from_idx].pop_front() {
-                        values.push_front(crate_char);
-                    } else {
-                        eprintln!("Error: Not enough crates in stack {} to move", from);
-                        return Err("Not enough crates to move".into());
-                    }
-                }
-                for value in values {
-                    stacks[to_idx].push_front(value);
-                }
-
-            }
-        }
+    for command in commands {
+        command.execute(&mut stacks, crane)?;
     }
 
     // Print the final state of the stacks
-    println!("Final state of stacks:");
-    let mut top_chars = String::new();
-    for (idx, stack) in stacks.iter().enumerate() {
-        let stack_content: String = stack.iter().collect();
-        println!("Stack {}: {}", idx + 1, stack_content);
-        if let Some(first) = stack.get(0) {
-            top_chars += &first.to_string()
-        }
-    }
+    println!("Final state of stacks ({}):", crane.name());
+    print!("{}", stacks);
 
-    Ok(top_chars)
+    Ok(stacks.top_crates())
 
 }
 
-fn read_and_parse_input_file(file_path: &str) -> Result<(Vec<VecDeque<char>>, Vec<(usize, usize, usize)>), Box<dyn std::error::Error>> {
-    let file = File::open(file_path)?;
-    let reader = BufReader::new(file);
-
-    let mut cargo_section = true;
+fn read_and_parse_input_file(file_path: &str) -> Result<(Stacks, Vec<Command>), Box<dyn std::error::Error>> {
+    let file = advent_vault::open(file_path)?;
+    parse_input(BufReader::new(file))
+}
 
-    let mut stacks: Vec<VecDeque<char>> = Vec::new();
-    let mut commands: Vec<(usize, usize, usize)> = Vec::new();
+/// Splits the input into the drawing and the commands after the first blank line.
+fn parse_input<R: BufRead>(reader: R) -> Result<(Stacks, Vec<Command>), Box<dyn std::error::Error>> {
+    let mut drawing = String::new();
+    let mut stacks: Option<Stacks> = None;
+    let mut commands: Vec<Command> = Vec::new();
 
-    for line_result in reader.lines() {
+    for (index, line_result) in reader.lines().enumerate() {
         let line = line_result?;
-        match cargo_section {
-            true => {
+        match &stacks {
+            None => {
                 if line.trim().is_empty() {
-                    cargo_section = false;
+                    stacks = Some(Stacks::parse(&drawing)?);
                 } else {
-                    match parse_cargo_crate_line(&line, &mut stacks) {
-                        Ok(_) => println!("noice"),
-                        Err(err) => println!("err: {:?}", err)
-                    };
+                    drawing += &line;
+                    drawing.push('\n');
                 }
             },
-            false => commands.push(parse_command_line(&line))
-        }
-    }
-    
-    Ok((stacks, commands))
-}
-
-fn parse_cargo_crate_line(line: &str, stacks: &mut Vec<VecDeque<char>>) -> Result<(), ParseCargoCrateLineError> {
-    let mut iter = line.chars();
-    let mut index = 0;
-
-    while let Some(ch) = iter.next() {
-        let stack = match (ch, iter.next(), iter.next()) {
-            ('[', Some(c), Some(']')) if c.is_ascii_alphabetic() => {
-                let mut deque = VecDeque::new();
-                deque.push_front(c);
-                Some(deque)
-            }
-            (' ', Some(' '), Some(' ')) => Some(VecDeque::new()),
-            (' ', Some(_), Some(' ')) => None,
-            _ => return Err(ParseCargoCrateLineError::InvalidFormat),
-        };
-
-        if let Some(stack) = stack {
-            if index < stacks.len() {
-                if !stack.is_empty() {
-                    stacks[index].extend(stack);
+            Some(stacks) => {
+                if !line.trim().is_empty() {
+                    commands.push(Command::parse(&line, index + 1, stacks.len())?);
                 }
-            } else {
-                stacks.push(stack);
-            }
-
-            index += 1;
-        }
-
-        // Skip the separator space
-        if let Some(next_ch) = iter.next() {
-            if next_ch != ' ' {
-                return Err(ParseCargoCrateLineError::InvalidFormat);
             }
         }
     }
 
-    Ok(())
-}
-
-
-fn parse_command_line(line: &str) -> (usize, usize, usize) {
-    let parts: Vec<&str> = line.split_whitespace().collect();
-    let num_crates = parts[1].parse::<usize>().unwrap();
-    let from = parts[3].parse::<usize>().unwrap();
-    let to = parts[5].parse::<usize>().unwrap();
-
-    (num_crates, from, to)
+    // A file without commands may end right after the drawing
+    let stacks = match stacks {
+        Some(stacks) => stacks,
+        None => Stacks::parse(&drawing)?,
+    };
+    Ok((stacks, commands))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use propcheck::Fuzzer;
     use std::path::Path;
 
     #[test]
@@ -173,44 +147,61 @@ mod tests {
     
         // assert stacks
         assert_eq!(3, stacks.len());
-        assert_eq!(vec![&'N', &'Z'], stacks[0].iter().collect::<Vec<_>>());
-        assert_eq!(vec![&'D', &'C', &'M'], stacks[1].iter().collect::<Vec<_>>());
-        assert_eq!(vec![&'P'], stacks[2].iter().collect::<Vec<_>>());
+        assert_eq!(vec!["N", "Z"], stacks.stack(1).unwrap().iter().collect::<Vec<_>>());
+        assert_eq!(vec!["D", "C", "M"], stacks.stack(2).unwrap().iter().collect::<Vec<_>>());
+        assert_eq!(vec!["P"], stacks.stack(3).unwrap().iter().collect::<Vec<_>>());
         // assert commands
         let expected_commands = vec![
-            (1, 2, 1),
-            (3, 1, 3),
-            (2, 2, 1),
-            (1, 1, 2),
+            Command::Move { count: 1, from: 2, to: 1 },
+            Command::Move { count: 3, from: 1, to: 3 },
+            Command::Move { count: 2, from: 2, to: 1 },
+            Command::Move { count: 1, from: 1, to: 2 },
         ];
         assert_eq!(expected_commands, commands);
     }
 
     #[test]
-    fn test_parse_cargo_crate_line() {
-        let input = "    [D] [N]    ";
-        let mut stacks: Vec<VecDeque<char>> = Vec::new();
-        let result = parse_cargo_crate_line(input, &mut stacks);
-    
-        assert!(result.is_ok());
-        assert_eq!(4, stacks.len());
-    
-        assert!(stacks[0].is_empty());
-        assert_eq!(1, stacks[1].len());
-        assert_eq!(vec![&'D'], stacks[1].iter().collect::<Vec<&char>>());
-        assert_eq!(1, stacks[2].len());
-        assert_eq!(vec![&'N'], stacks[2].iter().collect::<Vec<&char>>());
-        assert!(stacks[3].is_empty());
+    fn test_process_example_with_each_crane() {
+        let input_file = "./test_data/example_data.txt";
+        assert_eq!(process(input_file, &CrateMover9000).unwrap(), "CMZ");
+        assert_eq!(process(input_file, &CrateMover9001).unwrap(), "MCD");
     }
 
     #[test]
     fn test_parse_command_line() {
         let line = "move 2 from 1 to 3";
-        let (num_crates, from, to) = parse_command_line(line);
+        let command = Command::parse(line, 1, 3).unwrap();
 
-        assert_eq!(num_crates, 2);
-        assert_eq!(from, 1);
-        assert_eq!(to, 3);
+        assert_eq!(command, Command::Move { count: 2, from: 1, to: 3 });
     }
 
+    #[test]
+    fn test_invalid_command_reports_line_number() {
+        let input_file = std::env::temp_dir().join("day5_invalid_command.txt");
+        std::fs::write(&input_file, "[A]    \n 1   2 \n\nmove 1 from 1 to 2\nmove 1 from 1 to 3\n").unwrap();
+
+        let err = read_and_parse_input_file(input_file.to_str().unwrap()).unwrap_err();
+        assert_eq!(err.to_string(), "line 5: stack 3 does not exist, the drawing has 2 stacks");
+    }
+
+    #[test]
+    fn test_fuzz_parse_and_execute() {
+        let example = std::fs::read_to_string("./test_data/example_data.txt").unwrap();
+        Fuzzer::new("day5_parse_and_execute")
+            .seed_input(&example)
+            .seed_input("[Ab] [C]\n 1    2\n\nmove all from 1 to 2\nswap 1 2\nreverse 2 on 1\n")
+            .max_len(96)
+            .run(|bytes| {
+                let (stacks, commands) = parse_input(bytes)?;
+                for crane in [&CrateMover9000 as &dyn Crane, &CrateMover9001] {
+                    let mut stacks = stacks.clone();
+                    for command in &commands {
+                        command.execute(&mut stacks, crane)?;
+                    }
+                    stacks.top_crates();
+                    stacks.render();
+                }
+                Ok::<_, Box<dyn std::error::Error>>(())
+            });
+    }
 }
@@ -0,0 +1,132 @@
+use std::error::Error;
+use std::io::Write;
+
+use crate::command::Command;
+use crate::crane::Crane;
+use crate::stacks::Stacks;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplayFormat {
+    /// Drawings in the puzzle's `[X]` format, separated by blank lines.
+    Ascii,
+    /// One JSON object per line.
+    Json,
+}
+
+/// A single replay step as a JSON object. Each stack is listed bottom to top.
+pub fn render_json(step: usize, command: Option<&Command>, stacks: &Stacks) -> String {
+    let command = match command {
+        Some(command) => json_string(&command.to_string()),
+        None => "null".to_owned(),
+    };
+    let stacks: Vec<String> = stacks
+        .iter()
+        .map(|stack| {
+            let labels: Vec<String> = stack.iter().rev().map(|label| json_string(label)).collect();
+            format!("[{}]", labels.join(","))
+        })
+        .collect();
+    format!("{{\"step\":{},\"command\":{},\"stacks\":[{}]}}", step, command, stacks.join(","))
+}
+
+fn json_string(text: &str) -> String {
+    let mut escaped = String::from("\"");
+    for ch in text.chars() {
+        match ch {
+            '"' | '\\' => {
+                escaped.push('\\');
+                escaped.push(ch);
+            }
+            ch if ch.is_control() => escaped += &format!("\\u{:04x}", ch as u32),
+            ch => escaped.push(ch),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Writes the initial stacks and the stacks after every command.
+///
+/// Stops at the first command the crane can't carry out, after writing the
+/// frames up to that point.
+pub fn replay<W: Write>(
+    mut stacks: Stacks,
+    commands: &[Command],
+    crane: &dyn Crane,
+    format: ReplayFormat,
+    writer: &mut W,
+) -> Result<Stacks, Box<dyn Error>> {
+    write_frame(writer, format, 0, None, &stacks)?;
+    for (step, command) in commands.iter().enumerate() {
+        command.execute(&mut stacks, crane).map_err(|err| format!("step {} ({}): {}", step + 1, command, err))?;
+        write_frame(writer, format, step + 1, Some(command), &stacks)?;
+    }
+    Ok(stacks)
+}
+
+fn write_frame<W: Write>(
+    writer: &mut W,
+    format: ReplayFormat,
+    step: usize,
+    command: Option<&Command>,
+    stacks: &Stacks,
+) -> std::io::Result<()> {
+    match format {
+        ReplayFormat::Ascii => {
+            match command {
+                Some(command) => writeln!(writer, "Step {}: {}", step, command)?,
+                None => writeln!(writer, "Step 0: initial state")?,
+            }
+            writeln!(writer, "{}", stacks)
+        }
+        ReplayFormat::Json => writeln!(writer, "{}", render_json(step, command, stacks)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crane::{CrateMover9000, CrateMover9001};
+
+    fn example_stacks() -> Stacks {
+        Stacks::parse("    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 ").unwrap()
+    }
+
+    #[test]
+    fn test_render_json() {
+        assert_eq!(
+            render_json(1, Some(&Command::Move { count: 1, from: 2, to: 1 }), &example_stacks()),
+            r#"{"step":1,"command":"move 1 from 2 to 1","stacks":[["Z","N"],["M","C","D"],["P"]]}"#
+        );
+        assert_eq!(render_json(0, None, &Stacks::default()), r#"{"step":0,"command":null,"stacks":[]}"#);
+    }
+
+    #[test]
+    fn test_replay_emits_a_frame_per_command() {
+        let commands: Vec<Command> = ["move 1 from 2 to 1", "move 3 from 1 to 3", "move 2 from 2 to 1", "move 1 from 1 to 2"]
+            .iter()
+            .map(|line| Command::parse(line, 1, 3).unwrap())
+            .collect();
+        let mut output = Vec::new();
+        let stacks = replay(example_stacks(), &commands, &CrateMover9001, ReplayFormat::Json, &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.lines().count(), 5);
+        assert_eq!(
+            output.lines().last().unwrap(),
+            r#"{"step":4,"command":"move 1 from 1 to 2","stacks":[["M"],["C"],["P","Z","N","D"]]}"#
+        );
+        assert_eq!(stacks.top_crates(), "MCD");
+    }
+
+    #[test]
+    fn test_replay_stops_at_invalid_command() {
+        let mut output = Vec::new();
+        let result = replay(example_stacks(), &[Command::Move { count: 1, from: 3, to: 1 }, Command::Move { count: 2, from: 3, to: 1 }], &CrateMover9000, ReplayFormat::Ascii, &mut output);
+
+        assert_eq!(result.unwrap_err().to_string(), "step 2 (move 2 from 3 to 1): cannot move 2 crates from stack 3, it only holds 0");
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("Step 0: initial state\n"));
+        assert!(output.contains("Step 1: move 1 from 3 to 1\n[P]"));
+    }
+}
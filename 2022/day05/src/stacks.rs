@@ -0,0 +1,312 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// The label of a single crate, usually one letter.
+pub type Crate = String;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum DrawingError {
+    #[error("the drawing has no label row")]
+    MissingLabels,
+
+    #[error("label row, column {column}: expected label {expected}, found '{found}'")]
+    InvalidLabel { column: usize, expected: usize, found: String },
+
+    #[error("row {row}, column {column}: expected a crate like '[A]' or an empty cell, found '{found}'")]
+    InvalidCell { row: usize, column: usize, found: String },
+
+    #[error("row {row} has {stacks} stacks but there are {labels} labels")]
+    LabelCountMismatch { row: usize, stacks: usize, labels: usize },
+
+    #[error("row {row}: crate {label} in stack {stack} has nothing below it")]
+    FloatingCrate { row: usize, stack: usize, label: Crate },
+}
+
+/// Stacks of crates as drawn in the puzzle input, each stored top first.
+///
+/// Remembers the cell width of the drawing it was parsed from so that
+/// rendering reproduces the drawing.
+#[derive(Debug, Clone, Default)]
+pub struct Stacks {
+    stacks: Vec<VecDeque<Crate>>,
+    cell_width: usize,
+}
+
+impl Stacks {
+    /// Parses a drawing such as
+    ///
+    /// ```text
+    ///     [D]
+    /// [N] [C]
+    /// [Z] [M] [P]
+    ///  1   2   3
+    /// ```
+    ///
+    /// Cells may be wider than three characters to fit longer crate labels or
+    /// stack numbers, their width is taken from the label row.
+    pub fn parse(drawing: &str) -> Result<Stacks, DrawingError> {
+        let mut lines: Vec<&str> = drawing.lines().collect();
+        while lines.last().is_some_and(|line| line.trim().is_empty()) {
+            lines.pop();
+        }
+        let label_row = lines.pop().ok_or(DrawingError::MissingLabels)?;
+        let cell_width = Self::parse_label_row(label_row, &lines)?;
+        let stack_count = label_row.split_whitespace().count();
+
+        let mut stacks: Vec<VecDeque<Crate>> = vec![VecDeque::new(); stack_count];
+        for (row_index, line) in lines.iter().enumerate() {
+            let row = row_index + 1;
+            let chars: Vec<char> = line.trim_end().chars().collect();
+
+            let row_stacks = chars.len().div_ceil(cell_width + 1);
+            if row_stacks > stack_count {
+                return Err(DrawingError::LabelCountMismatch { row, stacks: row_stacks, labels: stack_count });
+            }
+
+            for (index, cell) in chars.chunks(cell_width + 1).enumerate() {
+                let column = index * (cell_width + 1) + 1;
+                let (content, separator) = cell.split_at(cell.len().min(cell_width));
+                if separator.iter().any(|&ch| ch != ' ') {
+                    return Err(DrawingError::InvalidCell { row, column, found: cell.iter().collect() });
+                }
+
+                let content: String = content.iter().collect();
+                let trimmed = content.trim();
+                if trimmed.is_empty() {
+                    if let Some(label) = stacks[index].back() {
+                        return Err(DrawingError::FloatingCrate { row: row - 1, stack: index + 1, label: label.clone() });
+                    }
+                    continue;
+                }
+                match trimmed.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+                    Some(label) if !label.is_empty() && !label.contains(['[', ']', ' ']) => {
+                        stacks[index].push_back(label.to_owned());
+                    }
+                    _ => return Err(DrawingError::InvalidCell { row, column, found: trimmed.to_owned() }),
+                }
+            }
+
+            // Stacks missing from the end of a short row are empty cells as well
+            for (index, stack) in stacks.iter().enumerate().skip(row_stacks) {
+                if let Some(label) = stack.back() {
+                    return Err(DrawingError::FloatingCrate { row: row - 1, stack: index + 1, label: label.clone() });
+                }
+            }
+        }
+
+        Ok(Stacks { stacks, cell_width })
+    }
+
+    /// Checks that the labels count up from 1 at equal distances and returns the cell width.
+    fn parse_label_row(label_row: &str, rows: &[&str]) -> Result<usize, DrawingError> {
+        let mut positions = Vec::new();
+        let mut offset = 0;
+        for (index, label) in label_row.split_whitespace().enumerate() {
+            let byte_offset = offset + label_row[offset..].find(label).unwrap_or(0);
+            offset = byte_offset + label.len();
+            let column = label_row[..byte_offset].chars().count();
+            if label.parse::<usize>().ok() != Some(index + 1) {
+                return Err(DrawingError::InvalidLabel { column: column + 1, expected: index + 1, found: label.to_owned() });
+            }
+            positions.push(column);
+        }
+
+        let cell_width = match positions.as_slice() {
+            [] => return Err(DrawingError::MissingLabels),
+            [_] => rows
+                .iter()
+                .chain([&label_row])
+                .map(|line| line.trim_end().chars().count())
+                .max()
+                .unwrap_or(0)
+                .max(3),
+            [first, second, ..] => second - first - 1,
+        };
+
+        // Every label sits one character into its cell
+        for (index, &column) in positions.iter().enumerate() {
+            if column != index * (cell_width + 1) + 1 {
+                return Err(DrawingError::InvalidLabel {
+                    column: column + 1,
+                    expected: index + 1,
+                    found: (index + 1).to_string(),
+                });
+            }
+        }
+        Ok(cell_width)
+    }
+
+    pub fn len(&self) -> usize {
+        self.stacks.len()
+    }
+
+    /// Stack with the given 1-based number.
+    pub fn stack(&self, number: usize) -> Option<&VecDeque<Crate>> {
+        number.checked_sub(1).and_then(|index| self.stacks.get(index))
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, VecDeque<Crate>> {
+        self.stacks.iter()
+    }
+
+    pub fn stacks_mut(&mut self) -> &mut [VecDeque<Crate>] {
+        &mut self.stacks
+    }
+
+    /// The labels of the top crate of every non-empty stack.
+    pub fn top_crates(&self) -> String {
+        self.stacks.iter().filter_map(|stack| stack.front()).map(String::as_str).collect()
+    }
+
+    /// Draws the stacks in the puzzle's format, including the label row.
+    pub fn render(&self) -> String {
+        let longest_label = self.stacks.iter().flatten().map(|label| label.chars().count()).max().unwrap_or(1);
+        let cell_width = self
+            .cell_width
+            .max(3)
+            .max(longest_label + 2)
+            .max(self.len().to_string().len() + 1);
+        let height = self.stacks.iter().map(|stack| stack.len()).max().unwrap_or(0);
+
+        let mut drawing = String::new();
+        for level in (0..height).rev() {
+            let cells: Vec<String> = self
+                .stacks
+                .iter()
+                .map(|stack| {
+                    // `level` counts from the bottom
+                    match stack.len().checked_sub(level + 1).map(|index| &stack[index]) {
+                        Some(label) => format!("{:<width$}", format!("[{}]", label), width = cell_width),
+                        None => " ".repeat(cell_width),
+                    }
+                })
+                .collect();
+            drawing += &cells.join(" ");
+            drawing.push('\n');
+        }
+
+        let labels: Vec<String> = (1..=self.len())
+            .map(|number| format!(" {:<width$}", number, width = cell_width - 1))
+            .collect();
+        drawing += &labels.join(" ");
+        drawing.push('\n');
+        drawing
+    }
+}
+
+impl From<Vec<VecDeque<Crate>>> for Stacks {
+    fn from(stacks: Vec<VecDeque<Crate>>) -> Self {
+        Stacks { stacks, cell_width: 3 }
+    }
+}
+
+impl FromStr for Stacks {
+    type Err = DrawingError;
+
+    fn from_str(drawing: &str) -> Result<Self, Self::Err> {
+        Stacks::parse(drawing)
+    }
+}
+
+impl fmt::Display for Stacks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render())
+    }
+}
+
+/// Two drawings are equal when they hold the same crates, whatever their cell width.
+impl PartialEq for Stacks {
+    fn eq(&self, other: &Self) -> bool {
+        self.stacks == other.stacks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n";
+
+    fn labels(stack: &VecDeque<Crate>) -> Vec<&str> {
+        stack.iter().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn test_parse_example() {
+        let stacks = Stacks::parse(EXAMPLE).unwrap();
+        assert_eq!(stacks.len(), 3);
+        assert_eq!(labels(stacks.stack(1).unwrap()), vec!["N", "Z"]);
+        assert_eq!(labels(stacks.stack(2).unwrap()), vec!["D", "C", "M"]);
+        assert_eq!(labels(stacks.stack(3).unwrap()), vec!["P"]);
+        assert!(stacks.stack(0).is_none());
+        assert_eq!(stacks.top_crates(), "NDP");
+    }
+
+    #[test]
+    fn test_parse_row_with_empty_outer_stacks() {
+        let stacks = Stacks::parse("    [D] [N]    \n 1   2   3   4 ").unwrap();
+        assert_eq!(stacks.len(), 4);
+        assert!(stacks.stack(1).unwrap().is_empty());
+        assert_eq!(labels(stacks.stack(2).unwrap()), vec!["D"]);
+        assert_eq!(labels(stacks.stack(3).unwrap()), vec!["N"]);
+        assert!(stacks.stack(4).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_render_round_trip() {
+        assert_eq!(Stacks::parse(EXAMPLE).unwrap().render(), EXAMPLE);
+
+        // Nine stacks of uneven height, with an empty one in the middle
+        let drawing = concat!(
+            "[M] [H]         [N]                \n",
+            "[S] [W]         [F]     [W] [V]    \n",
+            "[J] [J] [G]     [B]     [S] [B] [F]\n",
+            "[R] [P] [W]     [M] [W] [R] [Q] [L]\n",
+            " 1   2   3   4   5   6   7   8   9 \n",
+        );
+        assert_eq!(Stacks::parse(drawing).unwrap().to_string(), drawing);
+    }
+
+    #[test]
+    fn test_multi_character_labels_and_many_stacks() {
+        let drawing = concat!(
+            "[AB]                                              [K] \n",
+            "[CD] [E]                                          [L] \n",
+            " 1    2    3    4    5    6    7    8    9    10   11 \n",
+        );
+        let stacks = Stacks::parse(drawing).unwrap();
+        assert_eq!(stacks.len(), 11);
+        assert_eq!(labels(stacks.stack(1).unwrap()), vec!["AB", "CD"]);
+        assert_eq!(labels(stacks.stack(11).unwrap()), vec!["K", "L"]);
+        assert_eq!(stacks.top_crates(), "ABEK");
+        assert_eq!(stacks.render(), drawing);
+
+        // Built from scratch, cells grow to fit the longest label
+        let built = Stacks::from(vec![VecDeque::from(["XYZ".to_owned()]), VecDeque::new()]);
+        assert_eq!(built.render(), "[XYZ]      \n 1     2   \n");
+        assert_eq!(Stacks::parse(&built.render()).unwrap(), built);
+    }
+
+    #[test]
+    fn test_drawing_errors() {
+        assert_eq!(Stacks::parse(""), Err(DrawingError::MissingLabels));
+        assert_eq!(
+            Stacks::parse("[A] [B]\n 1   3 "),
+            Err(DrawingError::InvalidLabel { column: 6, expected: 2, found: "3".to_owned() })
+        );
+        assert_eq!(
+            Stacks::parse("[A] [B] [C]\n 1   2 "),
+            Err(DrawingError::LabelCountMismatch { row: 1, stacks: 3, labels: 2 })
+        );
+        assert_eq!(
+            Stacks::parse("[A] (B)\n 1   2 "),
+            Err(DrawingError::InvalidCell { row: 1, column: 5, found: "(B)".to_owned() })
+        );
+        assert_eq!(
+            Stacks::parse("[A] [B]\n    [C]\n 1   2 "),
+            Err(DrawingError::FloatingCrate { row: 1, stack: 1, label: "A".to_owned() })
+        );
+    }
+}
@@ -1,69 +1,175 @@
-use std::collections::{HashSet, VecDeque};
+use std::env;
+use std::io::{self, BufWriter, ErrorKind, Read, Write};
+use std::path::PathBuf;
+use std::process;
+
+use advent_config::Config;
+use serde::Deserialize;
+
+mod marker;
+use marker::{first_markers, parse_marker, MarkerPositions, MarkerPredicate, START_OF_MESSAGE_LEN, START_OF_PACKET_LEN};
+
+/// This day's settings from `advent.toml`, flags override them.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct Settings {
+    /// Relative to advent.toml, the working directory when unset.
+    input_dir: Option<PathBuf>,
+    packet_marker: usize,
+    message_marker: usize,
+}
 
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::path::Path;
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            input_dir: None,
+            packet_marker: START_OF_PACKET_LEN,
+            message_marker: START_OF_MESSAGE_LEN,
+        }
+    }
+}
 
+// Usage: day6 [--marker SPEC] [--all] [--packet-marker N] [--message-marker N] [input|-]
+//
+// Reads from stdin when the input is `-`, and data.txt in the configured
+// input directory without one. SPEC is one of packet, message, distinct:N,
+// repeats:N:K, alphabet:N:CHARS or sentinel:TEXT. Without a marker the first
+// start-of-packet and start-of-message markers are printed, their lengths
+// come from advent.toml or the --packet-marker and --message-marker flags.
+// With --all every position where a marker ends is printed, one per line.
 fn main() {
-    let input_file_path = "./data.txt";
-    let input = read_input_from_file(input_file_path).expect("Failed to read input file");
-
-    if let Some(position) = find_start_of_marker(&input, 4) {
-        println!("First start-of-packet marker position: {}", position);
-    } else {
-        println!("Start-of-packet marker not found.");
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    };
+    let mut settings: Settings = match config.day(2022, 6) {
+        Ok(settings) => settings,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    };
+    let mut input_file_path = None;
+    let mut marker: Option<Box<dyn MarkerPredicate>> = None;
+    let mut all = false;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--marker" => match parse_marker(&args.next().unwrap_or_default()) {
+                Ok(predicate) => marker = Some(predicate),
+                Err(err) => {
+                    eprintln!("{}", err);
+                    process::exit(1);
+                }
+            },
+            "--all" => all = true,
+            "--packet-marker" | "--message-marker" => match args.next().and_then(|len| len.parse().ok()) {
+                Some(len) if arg == "--packet-marker" => settings.packet_marker = len,
+                Some(len) => settings.message_marker = len,
+                None => {
+                    eprintln!("{} expects a length", arg);
+                    process::exit(1);
+                }
+            },
+            _ => input_file_path = Some(arg),
+        }
     }
 
-    if let Some(position) = find_start_of_marker(&input, 14) {
-        println!("First start-of-message marker position: {}", position);
-    } else {
-        println!("Start-of-message marker not found.");
+    let predicates = match marker {
+        Some(predicate) => vec![predicate],
+        None => match default_predicates(&settings) {
+            Ok(predicates) => predicates,
+            Err(err) => {
+                eprintln!("{}", err);
+                process::exit(1);
+            }
+        },
+    };
+    if all && predicates.len() > 1 {
+        eprintln!("--all needs a single --marker");
+        process::exit(1);
     }
-}
-
-fn read_input_from_file(file_path: &str) -> Result<String, io::Error> {
-    let path = Path::new(file_path);
-    let file = File::open(&path)?;
-    let reader = io::BufReader::new(file);
-
-    let mut input = String::new();
-    for line in reader.lines() {
-        input.push_str(&line?);
+    let input_dir = settings.input_dir.map_or_else(|| PathBuf::from("."), |dir| config.resolve(&dir));
+    let input_file_path = input_file_path.unwrap_or_else(|| input_dir.join("data.txt").to_string_lossy().into_owned());
+    if let Err(err) = run(&input_file_path, predicates, all) {
+        eprintln!("Failed to read input: {}", err);
+        process::exit(1);
     }
-
-    Ok(input)
 }
 
-fn find_start_of_marker(input: &str, marker_len: usize) -> Option<usize> {
-    let mut unique_chars = VecDeque::new();
-    let mut char_set = HashSet::new();
+/// The start-of-packet and start-of-message markers with the configured
+/// lengths, checked like a `--marker distinct:N`.
+fn default_predicates(settings: &Settings) -> Result<Vec<Box<dyn MarkerPredicate>>, marker::MarkerSpecError> {
+    [settings.packet_marker, settings.message_marker]
+        .iter()
+        .map(|len| parse_marker(&format!("distinct:{}", len)))
+        .collect()
+}
 
-    for (index, ch) in input.chars().enumerate() {
-        unique_chars.push_back(ch);
-        char_set.insert(ch);
+fn run(input_file_path: &str, mut predicates: Vec<Box<dyn MarkerPredicate>>, all: bool) -> Result<(), io::Error> {
+    let input = open_input(input_file_path)?;
 
-        if unique_chars.len() > marker_len {
-            let removed_char = unique_chars.pop_front().unwrap();
-            if !unique_chars.contains(&removed_char) {
-                char_set.remove(&removed_char);
+    if all {
+        let mut output = BufWriter::new(io::stdout().lock());
+        for position in MarkerPositions::new(input, predicates.remove(0)) {
+            match writeln!(output, "{}", position?) {
+                // The reader of the output is gone, e.g. when piped into `head`
+                Err(err) if err.kind() == ErrorKind::BrokenPipe => return Ok(()),
+                result => result?,
             }
         }
+        return output.flush();
+    }
+
+    let markers = first_markers(input, &mut predicates)?;
 
-        if char_set.len() == marker_len {
-            return Some(index + 1);
+    if predicates.len() == 1 {
+        match markers[0] {
+            Some(position) => println!("First marker ({}) position: {}", predicates[0].describe(), position),
+            None => println!("Marker ({}) not found.", predicates[0].describe()),
         }
+        return Ok(());
     }
 
-    None
+    if let Some(position) = markers[0] {
+        println!("First start-of-packet marker position: {}", position);
+    } else {
+        println!("Start-of-packet marker not found.");
+    }
+
+    if let Some(position) = markers[1] {
+        println!("First start-of-message marker position: {}", position);
+    } else {
+        println!("Start-of-message marker not found.");
+    }
+
+    Ok(())
 }
 
+fn open_input(file_path: &str) -> Result<Box<dyn Read>, io::Error> {
+    if file_path == "-" {
+        return Ok(Box::new(io::stdin().lock()));
+    }
+    advent_vault::open(file_path)
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use propcheck::{ints, strings};
+
+    /// First marker of each default length, counted in bytes.
+    fn first_default_markers(input: &str) -> Vec<Option<u64>> {
+        let mut predicates = default_predicates(&Settings::default()).unwrap();
+        first_markers(input.as_bytes(), &mut predicates).unwrap()
+    }
 
     #[test]
-    fn test_find_start_of_marker() {
+    fn test_first_markers() {
         let test_cases = vec![
             ("mjqjpqmgbljsphdztnvjfqwrcgsmlb", Some(7), Some(19)),
             ("bvwbjplbgvbhsrlpgdmjqwftvncz", Some(5), Some(23)),
@@ -74,19 +180,47 @@ mod tests {
 
         for (input, expected_packet_marker, expected_message_marker) in test_cases {
             assert_eq!(
-                find_start_of_marker(input, 4),
-                expected_packet_marker,
-                "Failed for input: {}",
-                input
-            );
-
-            assert_eq!(
-                find_start_of_marker(input, 14),
-                expected_message_marker,
+                first_default_markers(input),
+                vec![expected_packet_marker, expected_message_marker],
                 "Failed for input: {}",
                 input
             );
         }
+
+        // Positions count bytes, the marker ends at the sixth byte but the fifth character
+        assert_eq!(first_default_markers("ééabcd"), vec![Some(6), None]);
+    }
+
+    /// The obvious check: the first window of distinct bytes.
+    fn find_start_of_marker_brute_force(input: &[u8], marker_len: usize) -> Option<u64> {
+        (marker_len..=input.len()).find(|&end| {
+            let window = &input[end - marker_len..end];
+            window.iter().enumerate().all(|(i, byte)| !window[i + 1..].contains(byte))
+        }).map(|end| end as u64)
     }
-}
 
+    #[test]
+    fn test_first_markers_match_brute_force() {
+        propcheck::check_against(
+            "first_markers matches brute force",
+            &(strings("abcdef", 0, 40), ints(1, 6)),
+            |(input, marker_len)| {
+                let mut predicates = vec![parse_marker(&format!("distinct:{}", marker_len)).unwrap()];
+                first_markers(input.as_bytes(), &mut predicates).unwrap()[0]
+            },
+            |(input, marker_len)| find_start_of_marker_brute_force(input.as_bytes(), *marker_len as usize),
+        );
+    }
+
+    #[test]
+    fn test_marker_lengths_from_config() {
+        let config = advent_config::Config::parse("[2022.day6]\npacket_marker = 5\n").unwrap();
+        let settings: Settings = config.day(2022, 6).unwrap();
+        assert_eq!((settings.packet_marker, settings.message_marker), (5, START_OF_MESSAGE_LEN));
+        let predicates = default_predicates(&settings).unwrap();
+        assert_eq!(predicates[0].describe(), "5 distinct characters");
+
+        let settings = Settings { message_marker: 0, ..Settings::default() };
+        assert!(default_predicates(&settings).is_err());
+    }
+}
@@ -0,0 +1,499 @@
+use std::io::{self, ErrorKind, Read};
+use thiserror::Error;
+
+/// Size of the chunks read from the underlying reader.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+pub const START_OF_PACKET_LEN: usize = 4;
+pub const START_OF_MESSAGE_LEN: usize = 14;
+
+/// Longest window `parse_marker` accepts, the window is allocated up front.
+const MAX_MARKER_LEN: usize = 1 << 16;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum MarkerSpecError {
+    #[error("unknown marker '{0}', expected packet, message, distinct:N, repeats:N:K, alphabet:N:CHARS or sentinel:TEXT")]
+    UnknownKind(String),
+
+    #[error("invalid number '{found}' in marker '{spec}'")]
+    InvalidNumber { spec: String, found: String },
+
+    #[error("marker '{spec}' is invalid: {reason}")]
+    Invalid { spec: String, reason: &'static str },
+}
+
+/// A definition of what makes a marker, checked against a stream one byte at a time.
+pub trait MarkerPredicate {
+    /// Short description, e.g. for output headings.
+    fn describe(&self) -> String;
+
+    /// Adds the next byte and returns whether the bytes up to and including it
+    /// end a marker.
+    fn push(&mut self, byte: u8) -> bool;
+}
+
+/// The last `len` bytes of a stream in a ring buffer, with a count per byte
+/// value and the number of distinct values, so each byte costs O(1).
+struct SlidingWindow {
+    bytes: Vec<u8>,
+    counts: [u32; 256],
+    distinct: usize,
+    pushed: u64,
+}
+
+impl SlidingWindow {
+    fn new(len: usize) -> Self {
+        SlidingWindow { bytes: vec![0; len], counts: [0; 256], distinct: 0, pushed: 0 }
+    }
+
+    fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    fn is_full(&self) -> bool {
+        self.pushed >= self.len() as u64
+    }
+
+    /// Adds a byte and returns the byte that dropped out of the window, if any.
+    fn push(&mut self, byte: u8) -> Option<u8> {
+        let slot = (self.pushed % self.len() as u64) as usize;
+
+        let removed = self.is_full().then(|| self.bytes[slot]);
+        if let Some(removed) = removed {
+            self.counts[removed as usize] -= 1;
+            if self.counts[removed as usize] == 0 {
+                self.distinct -= 1;
+            }
+        }
+
+        self.bytes[slot] = byte;
+        self.counts[byte as usize] += 1;
+        if self.counts[byte as usize] == 1 {
+            self.distinct += 1;
+        }
+        self.pushed += 1;
+
+        removed
+    }
+
+    fn count(&self, byte: u8) -> u32 {
+        self.counts[byte as usize]
+    }
+}
+
+/// `len` pairwise distinct characters, the puzzle's marker definition.
+pub struct DistinctMarker {
+    window: SlidingWindow,
+}
+
+impl DistinctMarker {
+    pub fn new(len: usize) -> Self {
+        assert!(len > 0, "marker length must be at least 1");
+        DistinctMarker { window: SlidingWindow::new(len) }
+    }
+
+    /// Four distinct characters.
+    pub fn start_of_packet() -> Self {
+        DistinctMarker::new(START_OF_PACKET_LEN)
+    }
+
+    /// Fourteen distinct characters.
+    pub fn start_of_message() -> Self {
+        DistinctMarker::new(START_OF_MESSAGE_LEN)
+    }
+}
+
+impl MarkerPredicate for DistinctMarker {
+    fn describe(&self) -> String {
+        format!("{} distinct characters", self.window.len())
+    }
+
+    fn push(&mut self, byte: u8) -> bool {
+        self.window.push(byte);
+        self.window.distinct == self.window.len()
+    }
+}
+
+/// `len` characters of which at most `max_repeats` repeat an earlier one.
+pub struct RepeatLimitedMarker {
+    window: SlidingWindow,
+    max_repeats: usize,
+}
+
+impl RepeatLimitedMarker {
+    pub fn new(len: usize, max_repeats: usize) -> Self {
+        assert!(len > 0, "marker length must be at least 1");
+        RepeatLimitedMarker { window: SlidingWindow::new(len), max_repeats }
+    }
+}
+
+impl MarkerPredicate for RepeatLimitedMarker {
+    fn describe(&self) -> String {
+        format!("{} characters with at most {} repeats", self.window.len(), self.max_repeats)
+    }
+
+    fn push(&mut self, byte: u8) -> bool {
+        self.window.push(byte);
+        self.window.is_full() && self.window.len() - self.window.distinct <= self.max_repeats
+    }
+}
+
+/// `len` characters that include every character of an alphabet.
+pub struct AlphabetMarker {
+    window: SlidingWindow,
+    in_alphabet: [bool; 256],
+    alphabet_len: usize,
+    present: usize,
+}
+
+impl AlphabetMarker {
+    pub fn new(len: usize, alphabet: &[u8]) -> Self {
+        assert!(len > 0, "marker length must be at least 1");
+        let mut in_alphabet = [false; 256];
+        for &byte in alphabet {
+            in_alphabet[byte as usize] = true;
+        }
+        let alphabet_len = in_alphabet.iter().filter(|&&member| member).count();
+        AlphabetMarker { window: SlidingWindow::new(len), in_alphabet, alphabet_len, present: 0 }
+    }
+}
+
+impl MarkerPredicate for AlphabetMarker {
+    fn describe(&self) -> String {
+        let alphabet: String = (0..=255u8).filter(|&byte| self.in_alphabet[byte as usize]).map(char::from).collect();
+        format!("{} characters containing all of '{}'", self.window.len(), alphabet)
+    }
+
+    fn push(&mut self, byte: u8) -> bool {
+        if let Some(removed) = self.window.push(byte) {
+            if self.in_alphabet[removed as usize] && self.window.count(removed) == 0 {
+                self.present -= 1;
+            }
+        }
+        // Only the first copy in the window makes a character present
+        if self.in_alphabet[byte as usize] && self.window.count(byte) == 1 {
+            self.present += 1;
+        }
+        self.window.is_full() && self.present == self.alphabet_len
+    }
+}
+
+/// A literal byte sequence, matched with the Knuth-Morris-Pratt automaton so
+/// each byte costs amortised O(1).
+pub struct SentinelMarker {
+    sentinel: Vec<u8>,
+    failure: Vec<usize>,
+    matched: usize,
+}
+
+impl SentinelMarker {
+    pub fn new(sentinel: &[u8]) -> Self {
+        assert!(!sentinel.is_empty(), "sentinel must not be empty");
+
+        // failure[i] is the length of the longest proper border of sentinel[..=i]
+        let mut failure = vec![0; sentinel.len()];
+        let mut border = 0;
+        for i in 1..sentinel.len() {
+            while border > 0 && sentinel[i] != sentinel[border] {
+                border = failure[border - 1];
+            }
+            if sentinel[i] == sentinel[border] {
+                border += 1;
+            }
+            failure[i] = border;
+        }
+
+        SentinelMarker { sentinel: sentinel.to_vec(), failure, matched: 0 }
+    }
+}
+
+impl MarkerPredicate for SentinelMarker {
+    fn describe(&self) -> String {
+        format!("sentinel '{}'", String::from_utf8_lossy(&self.sentinel))
+    }
+
+    fn push(&mut self, byte: u8) -> bool {
+        if self.matched == self.sentinel.len() {
+            self.matched = self.failure[self.matched - 1];
+        }
+        while self.matched > 0 && self.sentinel[self.matched] != byte {
+            self.matched = self.failure[self.matched - 1];
+        }
+        if self.sentinel[self.matched] == byte {
+            self.matched += 1;
+        }
+        self.matched == self.sentinel.len()
+    }
+}
+
+impl<P: MarkerPredicate + ?Sized> MarkerPredicate for Box<P> {
+    fn describe(&self) -> String {
+        (**self).describe()
+    }
+
+    fn push(&mut self, byte: u8) -> bool {
+        (**self).push(byte)
+    }
+}
+
+/// Parses a marker definition as given on the command line.
+pub fn parse_marker(spec: &str) -> Result<Box<dyn MarkerPredicate>, MarkerSpecError> {
+    let number = |part: &str| {
+        part.parse::<usize>()
+            .map_err(|_| MarkerSpecError::InvalidNumber { spec: spec.to_owned(), found: part.to_owned() })
+    };
+    let invalid = |reason| MarkerSpecError::Invalid { spec: spec.to_owned(), reason };
+    let length = |part: &str| match number(part)? {
+        0 => Err(invalid("the length must be at least 1")),
+        len if len > MAX_MARKER_LEN => Err(invalid("the length must be at most 65536")),
+        len => Ok(len),
+    };
+
+    // The sentinel may contain colons itself
+    if let Some(sentinel) = spec.strip_prefix("sentinel:") {
+        if sentinel.is_empty() {
+            return Err(invalid("the sentinel must not be empty"));
+        }
+        return Ok(Box::new(SentinelMarker::new(sentinel.as_bytes())));
+    }
+
+    let parts: Vec<&str> = spec.split(':').collect();
+    match parts.as_slice() {
+        ["packet"] => Ok(Box::new(DistinctMarker::start_of_packet())),
+        ["message"] => Ok(Box::new(DistinctMarker::start_of_message())),
+        ["distinct", len] => Ok(Box::new(DistinctMarker::new(length(len)?))),
+        ["repeats", len, max_repeats] => Ok(Box::new(RepeatLimitedMarker::new(length(len)?, number(max_repeats)?))),
+        ["alphabet", len, alphabet] => {
+            let len = length(len)?;
+            let marker = AlphabetMarker::new(len, alphabet.as_bytes());
+            if marker.alphabet_len == 0 {
+                return Err(invalid("the alphabet must not be empty"));
+            }
+            if marker.alphabet_len > len {
+                return Err(invalid("the alphabet doesn't fit in the window"));
+            }
+            Ok(Box::new(marker))
+        }
+        _ => Err(MarkerSpecError::UnknownKind(spec.to_owned())),
+    }
+}
+
+/// Iterator over the end positions of every marker in a stream.
+///
+/// Positions count the bytes up to and including the marker, line breaks are
+/// not part of the datastream and are skipped. The reader is consumed in
+/// fixed-size chunks so memory use doesn't depend on the stream's length.
+pub struct MarkerPositions<R, P> {
+    reader: R,
+    predicate: P,
+    position: u64,
+    chunk: Box<[u8]>,
+    chunk_len: usize,
+    offset: usize,
+    done: bool,
+}
+
+impl<R: Read, P: MarkerPredicate> MarkerPositions<R, P> {
+    pub fn new(reader: R, predicate: P) -> Self {
+        MarkerPositions {
+            reader,
+            predicate,
+            position: 0,
+            chunk: vec![0; CHUNK_SIZE].into_boxed_slice(),
+            chunk_len: 0,
+            offset: 0,
+            done: false,
+        }
+    }
+}
+
+impl<R: Read, P: MarkerPredicate> Iterator for MarkerPositions<R, P> {
+    type Item = io::Result<u64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            while self.offset < self.chunk_len {
+                let byte = self.chunk[self.offset];
+                self.offset += 1;
+                if byte == b'\n' || byte == b'\r' {
+                    continue;
+                }
+                self.position += 1;
+                if self.predicate.push(byte) {
+                    return Some(Ok(self.position));
+                }
+            }
+
+            match self.reader.read(&mut self.chunk) {
+                Ok(0) => self.done = true,
+                Ok(len) => {
+                    self.chunk_len = len;
+                    self.offset = 0;
+                }
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// End position of the first marker of each kind, scanning the stream once.
+///
+/// Like [`MarkerPositions`], a position is the number of bytes up to and
+/// including the marker with line breaks skipped, not a count of characters.
+pub fn first_markers<R: Read>(reader: R, predicates: &mut [Box<dyn MarkerPredicate>]) -> io::Result<Vec<Option<u64>>> {
+    let mut found = vec![None; predicates.len()];
+    let mut position = 0;
+
+    for byte in io::BufReader::with_capacity(CHUNK_SIZE, reader).bytes() {
+        let byte = byte?;
+        if byte == b'\n' || byte == b'\r' {
+            continue;
+        }
+        position += 1;
+        for (predicate, found) in predicates.iter_mut().zip(found.iter_mut()) {
+            if predicate.push(byte) && found.is_none() {
+                *found = Some(position);
+            }
+        }
+        if found.iter().all(Option::is_some) {
+            break;
+        }
+    }
+
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use propcheck::Fuzzer;
+
+    /// Hands out at most one byte per read to exercise chunk boundaries.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.0.split_first() {
+                Some((&byte, rest)) if !buf.is_empty() => {
+                    buf[0] = byte;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    fn all_markers<R: Read, P: MarkerPredicate>(reader: R, predicate: P) -> Vec<u64> {
+        MarkerPositions::new(reader, predicate).collect::<io::Result<_>>().unwrap()
+    }
+
+    fn markers_of(input: &str, spec: &str) -> Vec<u64> {
+        all_markers(input.as_bytes(), parse_marker(spec).unwrap())
+    }
+
+    #[test]
+    fn test_all_marker_positions() {
+        assert_eq!(all_markers("aabcdd".as_bytes(), DistinctMarker::new(3)), vec![4, 5]);
+        assert_eq!(all_markers("abab".as_bytes(), DistinctMarker::new(2)), vec![2, 3, 4]);
+        assert_eq!(all_markers("aaaa".as_bytes(), DistinctMarker::new(1)), vec![1, 2, 3, 4]);
+        assert!(all_markers("aaaa".as_bytes(), DistinctMarker::new(2)).is_empty());
+    }
+
+    #[test]
+    fn test_chunk_boundaries_and_line_breaks() {
+        let input = "mjqjpqmgbljsphdztnvjfqwrcgsmlb";
+        let expected = all_markers(input.as_bytes(), DistinctMarker::start_of_packet());
+        assert_eq!(expected[0], 7);
+        assert_eq!(all_markers(Trickle(input.as_bytes()), DistinctMarker::start_of_packet()), expected);
+        assert_eq!(
+            all_markers("mjqj\npqm\r\ngbljsphdztnvjfqwrcgsmlb\n".as_bytes(), DistinctMarker::start_of_packet()),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_first_markers_in_one_pass() {
+        let input = "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg";
+        let mut predicates: Vec<Box<dyn MarkerPredicate>> =
+            vec![Box::new(DistinctMarker::start_of_packet()), Box::new(DistinctMarker::start_of_message())];
+        assert_eq!(first_markers(input.as_bytes(), &mut predicates).unwrap(), vec![Some(10), Some(29)]);
+
+        let mut predicates: Vec<Box<dyn MarkerPredicate>> =
+            vec![Box::new(DistinctMarker::new(4)), Box::new(DistinctMarker::new(30))];
+        assert_eq!(first_markers(input.as_bytes(), &mut predicates).unwrap(), vec![Some(10), None]);
+    }
+
+    #[test]
+    fn test_marker_at_every_position_of_a_long_stream() {
+        // A repeating alphabet has a marker at every position once the window fills
+        let cycle = b"abcdefghij".iter().copied().cycle().take(1_000_000).collect::<Vec<u8>>();
+        let mut positions = MarkerPositions::new(cycle.as_slice(), DistinctMarker::new(10));
+        assert_eq!(positions.next().unwrap().unwrap(), 10);
+        assert_eq!(positions.count(), 1_000_000 - 10);
+    }
+
+    #[test]
+    fn test_repeat_limited_marker() {
+        // With no repeats allowed it is the distinct marker
+        assert_eq!(markers_of("aabcdd", "repeats:3:0"), markers_of("aabcdd", "distinct:3"));
+        assert_eq!(markers_of("aabcdd", "repeats:3:1"), vec![3, 4, 5, 6]);
+        assert_eq!(markers_of("aaab", "repeats:3:1"), vec![4]);
+    }
+
+    #[test]
+    fn test_alphabet_marker() {
+        assert_eq!(markers_of("xaxbxbax", "alphabet:3:ab"), vec![4, 7, 8]);
+        assert_eq!(markers_of("abba", "alphabet:2:ab"), vec![2, 4]);
+        assert!(markers_of("aaaa", "alphabet:4:ab").is_empty());
+    }
+
+    #[test]
+    fn test_sentinel_marker() {
+        assert_eq!(markers_of("abababa", "sentinel:aba"), vec![3, 5, 7]);
+        assert_eq!(markers_of("aabaabaaab", "sentinel:aab"), vec![3, 6, 10]);
+        assert_eq!(markers_of("x:y:x:y", "sentinel:x:y"), vec![3, 7]);
+    }
+
+    #[test]
+    fn test_parse_marker_errors() {
+        assert!(matches!(parse_marker("packet"), Ok(marker) if marker.describe() == "4 distinct characters"));
+        assert_eq!(parse_marker("bogus:3").err(), Some(MarkerSpecError::UnknownKind("bogus:3".to_owned())));
+        assert_eq!(
+            parse_marker("distinct:x").err(),
+            Some(MarkerSpecError::InvalidNumber { spec: "distinct:x".to_owned(), found: "x".to_owned() })
+        );
+        assert!(matches!(parse_marker("distinct:0"), Err(MarkerSpecError::Invalid { .. })));
+        assert!(matches!(parse_marker("alphabet:2:abc"), Err(MarkerSpecError::Invalid { .. })));
+        assert!(matches!(parse_marker("sentinel:"), Err(MarkerSpecError::Invalid { .. })));
+        assert!(parse_marker("repeats:65536:2").is_ok());
+        assert_eq!(
+            parse_marker("distinct:4294967296").err(),
+            Some(MarkerSpecError::Invalid { spec: "distinct:4294967296".to_owned(), reason: "the length must be at most 65536" })
+        );
+    }
+
+    /// The first line is a marker spec, the rest the datastream.
+    #[test]
+    fn test_fuzz_marker_specs_and_streams() {
+        Fuzzer::new("marker_specs_and_streams")
+            .seed_input("packet\nmjqjpqmgbljsphdztnvjfqwrcgsmlb\n")
+            .seed_input("repeats:5:1\nbvwbjplbgvbhsrlpgdmjqwftvncz")
+            .seed_input("alphabet:4:ab\nnppdvjthqldpwncqszvftbrmjlhg")
+            .seed_input("sentinel:x:y\nx:y:x:y")
+            .run(|bytes| {
+                let (spec, stream) = bytes.split_at(bytes.iter().position(|&byte| byte == b'\n').unwrap_or(bytes.len()));
+                let spec = String::from_utf8_lossy(spec);
+                let mut predicates = vec![parse_marker(&spec)?, parse_marker("packet")?, parse_marker("message")?];
+                predicates[0].describe();
+                first_markers(stream, &mut predicates)?;
+                MarkerPositions::new(stream, parse_marker(&spec)?).collect::<io::Result<Vec<_>>>()?;
+                Ok::<_, Box<dyn std::error::Error>>(())
+            });
+    }
+}
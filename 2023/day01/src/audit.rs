@@ -0,0 +1,339 @@
+use std::fmt;
+use std::io::BufRead;
+use anyhow::{Result, anyhow, Context};
+
+use crate::digit_matcher::{DigitMatcher, Match};
+
+/// A digit token as it appears in the line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub text: String,
+    /// Byte offset of the token in the line.
+    pub start: usize,
+    pub value: u32,
+}
+
+/// Why a line has no calibration value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    EmptyLine,
+    NoDigit,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Value { first: Token, last: Token, value: u32 },
+    Invalid(Failure),
+}
+
+/// How a single line of the calibration document was read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineAudit {
+    /// 1-based, as in the input file.
+    pub line_number: usize,
+    pub text: String,
+    pub outcome: Outcome,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditFormat {
+    Table,
+    Json,
+}
+
+impl AuditFormat {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "table" => Ok(AuditFormat::Table),
+            "json" => Ok(AuditFormat::Json),
+            _ => Err(anyhow!("Unknown audit format '{}', expected table or json", name)),
+        }
+    }
+}
+
+impl LineAudit {
+    pub fn value(&self) -> Option<u32> {
+        match self.outcome {
+            Outcome::Value { value, .. } => Some(value),
+            Outcome::Invalid(_) => None,
+        }
+    }
+
+    pub fn failure(&self) -> Option<Failure> {
+        match self.outcome {
+            Outcome::Value { .. } => None,
+            Outcome::Invalid(failure) => Some(failure),
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::EmptyLine => write!(f, "empty line"),
+            Failure::NoDigit => write!(f, "no digit found"),
+        }
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}' at {}", self.text, self.start)
+    }
+}
+
+impl fmt::Display for LineAudit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.outcome {
+            Outcome::Value { first, last, value } => {
+                write!(f, "Line {}: {} and {} make {}", self.line_number, first, last, value)
+            }
+            Outcome::Invalid(Failure::EmptyLine) => write!(f, "Line {}: empty line", self.line_number),
+            Outcome::Invalid(failure) => write!(f, "Line {}: {} in '{}'", self.line_number, failure, self.text),
+        }
+    }
+}
+
+/// Reads the first and last digit token of a line with `matcher`.
+pub fn audit_line(line_number: usize, text: &str, matcher: &DigitMatcher) -> LineAudit {
+    let token = |found: Match| Token { text: text[found.start..found.end].to_owned(), start: found.start, value: found.value };
+    let outcome = if text.trim().is_empty() {
+        Outcome::Invalid(Failure::EmptyLine)
+    } else {
+        match matcher.first_and_last(text) {
+            Some((first, last)) => Outcome::Value { value: first.value * 10 + last.value, first: token(first), last: token(last) },
+            None => Outcome::Invalid(Failure::NoDigit),
+        }
+    };
+    LineAudit { line_number, text: text.to_owned(), outcome }
+}
+
+/// Audits every line of `reader`, failing only if it can't be read.
+pub fn audit_lines<R: BufRead>(reader: R, matcher: &DigitMatcher) -> Result<Vec<LineAudit>> {
+    reader
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            let line = line.with_context(|| format!("Failed to read line {}", index + 1))?;
+            Ok(audit_line(index + 1, &line, matcher))
+        })
+        .collect()
+}
+
+/// Lines that have no calibration value.
+pub fn failures(audits: &[LineAudit]) -> impl Iterator<Item = &LineAudit> {
+    audits.iter().filter(|audit| audit.failure().is_some())
+}
+
+/// Sums the calibration values. An invalid line is an error unless
+/// `skip_invalid` is set, in which case it counts for nothing.
+pub fn calibration_sum(audits: &[LineAudit], skip_invalid: bool) -> Result<u32> {
+    if !skip_invalid {
+        if let Some(invalid) = failures(audits).next() {
+            return Err(anyhow!("{}", invalid));
+        }
+    }
+    Ok(audits.iter().filter_map(LineAudit::value).sum())
+}
+
+pub fn render(audits: &[LineAudit], format: AuditFormat) -> String {
+    match format {
+        AuditFormat::Table => render_table(audits),
+        AuditFormat::Json => render_json(audits),
+    }
+}
+
+/// One row per line, followed by the sum of the valid lines.
+fn render_table(audits: &[LineAudit]) -> String {
+    let mut rows = vec![["line".to_owned(), "first".to_owned(), "last".to_owned(), "value".to_owned(), "text".to_owned()]];
+    for audit in audits {
+        let (first, last, value) = match &audit.outcome {
+            Outcome::Value { first, last, value } => (first.to_string(), last.to_string(), value.to_string()),
+            Outcome::Invalid(failure) => ("-".to_owned(), "-".to_owned(), failure.to_string()),
+        };
+        rows.push([audit.line_number.to_string(), first, last, value, audit.text.clone()]);
+    }
+
+    let mut widths = [0; 4];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut table = String::new();
+    for [line, first, last, value, text] in &rows {
+        let row = format!(
+            "{:>w0$}  {:<w1$}  {:<w2$}  {:>w3$}  {}",
+            line, first, last, value, text,
+            w0 = widths[0], w1 = widths[1], w2 = widths[2], w3 = widths[3]
+        );
+        table += row.trim_end();
+        table.push('\n');
+    }
+    let invalid = failures(audits).count();
+    table += &format!(
+        "Calibration value {} from {} lines, {} invalid\n",
+        audits.iter().filter_map(LineAudit::value).sum::<u32>(),
+        audits.len() - invalid,
+        invalid
+    );
+    table
+}
+
+/// `{"lines": [...], "sum": N, "invalid": K}` with one object per line,
+/// the sum covering the valid lines.
+fn render_json(audits: &[LineAudit]) -> String {
+    let token_json = |token: &Token| {
+        format!("{{\"token\": {}, \"start\": {}, \"value\": {}}}", json_string(&token.text), token.start, token.value)
+    };
+
+    let lines: Vec<String> = audits
+        .iter()
+        .map(|audit| {
+            let details = match &audit.outcome {
+                Outcome::Value { first, last, value } => {
+                    format!("\"first\": {}, \"last\": {}, \"value\": {}", token_json(first), token_json(last), value)
+                }
+                Outcome::Invalid(failure) => format!("\"error\": {}", json_string(&failure.to_string())),
+            };
+            format!("    {{\"line\": {}, \"text\": {}, {}}}", audit.line_number, json_string(&audit.text), details)
+        })
+        .collect();
+
+    format!(
+        "{{\n  \"lines\": [\n{}\n  ],\n  \"sum\": {},\n  \"invalid\": {}\n}}\n",
+        lines.join(",\n"),
+        audits.iter().filter_map(LineAudit::value).sum::<u32>(),
+        failures(audits).count()
+    )
+}
+
+fn json_string(s: &str) -> String {
+    let mut quoted = String::from("\"");
+    for ch in s.chars() {
+        match ch {
+            '"' => quoted += "\\\"",
+            '\\' => quoted += "\\\\",
+            '\n' => quoted += "\\n",
+            '\r' => quoted += "\\r",
+            '\t' => quoted += "\\t",
+            ch if ch.is_control() => quoted += &format!("\\u{:04x}", ch as u32),
+            ch => quoted.push(ch),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vocabulary::{MatchOptions, Vocabulary};
+    use propcheck::Fuzzer;
+
+    fn english() -> DigitMatcher {
+        Vocabulary::english().matcher(&MatchOptions::default())
+    }
+
+    fn example() -> Vec<LineAudit> {
+        audit_lines("two1nine\nabc\n\nxtwone3four\n".as_bytes(), &english()).unwrap()
+    }
+
+    #[test]
+    fn test_audit_line() {
+        let audit = audit_line(4, "xtwone3four", &english());
+        assert_eq!(
+            audit.outcome,
+            Outcome::Value {
+                first: Token { text: "two".to_owned(), start: 1, value: 2 },
+                last: Token { text: "four".to_owned(), start: 7, value: 4 },
+                value: 24,
+            }
+        );
+        assert_eq!(audit.to_string(), "Line 4: 'two' at 1 and 'four' at 7 make 24");
+
+        let digits = audit_line(1, "a0b7", &DigitMatcher::digits());
+        assert_eq!(digits.value(), Some(7));
+    }
+
+    #[test]
+    fn test_invalid_lines() {
+        let audits = example();
+        assert_eq!(audits.len(), 4);
+        assert_eq!(audits[1].failure(), Some(Failure::NoDigit));
+        assert_eq!(audits[2].failure(), Some(Failure::EmptyLine));
+        assert_eq!(failures(&audits).map(|audit| audit.line_number).collect::<Vec<_>>(), vec![2, 3]);
+
+        assert_eq!(calibration_sum(&audits, false).unwrap_err().to_string(), "Line 2: no digit found in 'abc'");
+        assert_eq!(calibration_sum(&audits, true).unwrap(), 29 + 24);
+    }
+
+    #[test]
+    fn test_render_table() {
+        assert_eq!(
+            render(&example(), AuditFormat::Table),
+            concat!(
+                "line  first       last                  value  text\n",
+                "   1  'two' at 0  'nine' at 4              29  two1nine\n",
+                "   2  -           -            no digit found  abc\n",
+                "   3  -           -                empty line\n",
+                "   4  'two' at 1  'four' at 7              24  xtwone3four\n",
+                "Calibration value 53 from 2 lines, 2 invalid\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_render_json() {
+        let json = render(&example()[..2], AuditFormat::Json);
+        assert_eq!(
+            json,
+            concat!(
+                "{\n",
+                "  \"lines\": [\n",
+                "    {\"line\": 1, \"text\": \"two1nine\", ",
+                "\"first\": {\"token\": \"two\", \"start\": 0, \"value\": 2}, ",
+                "\"last\": {\"token\": \"nine\", \"start\": 4, \"value\": 9}, \"value\": 29},\n",
+                "    {\"line\": 2, \"text\": \"abc\", \"error\": \"no digit found\"}\n",
+                "  ],\n",
+                "  \"sum\": 29,\n",
+                "  \"invalid\": 1\n",
+                "}\n",
+            )
+        );
+        assert_eq!(json_string("a\"b\\c\u{1}"), "\"a\\\"b\\\\c\\u0001\"");
+    }
+
+    #[test]
+    fn test_fuzz_audit_lines() {
+        Fuzzer::new("audit_lines")
+            .seed_input("two1nine\neightwothree\nabcone2threexyz\nxtwone3four\n4nineeightseven2\n")
+            .seed_input("abc\n\n7pqrstsixteen\n")
+            .run(|bytes| {
+                for options in [MatchOptions::default(), MatchOptions { ignore_case: true, zero: true }] {
+                    let audits = audit_lines(bytes, &Vocabulary::german().matcher(&options))?;
+                    calibration_sum(&audits, true)?;
+                    render(&audits, AuditFormat::Table);
+                    render(&audits, AuditFormat::Json);
+                }
+                audit_lines(bytes, &DigitMatcher::digits())
+            });
+    }
+
+    /// Vocabulary files are matched against their own text so that words
+    /// with multi-byte characters meet lines that contain them.
+    #[test]
+    fn test_fuzz_vocabulary_files() {
+        Fuzzer::new("vocabulary_files")
+            .seed_input("yksi = 1\nkaksi = 2\nkolme = 3\nneljä = 4\nviisi = 5\nkuusi = 6\nseitsemän = 7\nkahdeksan = 8\nyhdeksän = 9\nnolla = 0\n")
+            .seed_input("# Roman\nI=1\nII=2\nIII=3\nIV=4\nV=5\nVI=6\nVII=7\nVIII=8\nIX=9\n")
+            .max_len(160)
+            .run(|bytes| {
+                let vocabulary = Vocabulary::parse("fuzz", &String::from_utf8_lossy(bytes))?;
+                let matcher = vocabulary.matcher(&MatchOptions { ignore_case: true, zero: true });
+                let audits = audit_lines(bytes, &matcher)?;
+                calibration_sum(&audits, true)
+            });
+    }
+}
@@ -0,0 +1,175 @@
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, VecDeque};
+
+/// A digit token found in a line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    /// Byte offset of the first byte of the token.
+    pub start: usize,
+    /// Byte offset just past the token.
+    pub end: usize,
+    pub value: u32,
+}
+
+#[derive(Debug, Default)]
+struct Node {
+    children: BTreeMap<u8, usize>,
+    failure: usize,
+    /// Length and value of every pattern ending at this node, including the
+    /// ones reached through failure links.
+    outputs: Vec<(usize, u32)>,
+}
+
+/// Aho-Corasick automaton over digit tokens: a trie of the patterns plus
+/// failure links, so all matches, overlapping ones included, are found in a
+/// single left-to-right pass.
+#[derive(Debug)]
+pub struct DigitMatcher {
+    nodes: Vec<Node>,
+    ignore_case: bool,
+}
+
+impl DigitMatcher {
+    /// Builds the automaton for `(pattern, value)` pairs, optionally treating
+    /// ASCII letters of either case as equal.
+    pub fn new(patterns: &[(&str, u32)], ignore_case: bool) -> Self {
+        let fold = |byte: u8| if ignore_case { byte.to_ascii_lowercase() } else { byte };
+        let mut nodes = vec![Node::default()];
+
+        for &(pattern, value) in patterns.iter().filter(|(pattern, _)| !pattern.is_empty()) {
+            let mut current = 0;
+            for byte in pattern.bytes().map(fold) {
+                current = match nodes[current].children.get(&byte) {
+                    Some(&child) => child,
+                    None => {
+                        nodes.push(Node::default());
+                        let child = nodes.len() - 1;
+                        nodes[current].children.insert(byte, child);
+                        child
+                    }
+                };
+            }
+            nodes[current].outputs.push((pattern.len(), value));
+        }
+
+        // Breadth-first, so failure targets are complete before they're used
+        let mut queue: VecDeque<usize> = nodes[0].children.values().copied().collect();
+        while let Some(parent) = queue.pop_front() {
+            let children: Vec<(u8, usize)> = nodes[parent].children.iter().map(|(&byte, &child)| (byte, child)).collect();
+            for (byte, child) in children {
+                let mut fallback = nodes[parent].failure;
+                let failure = loop {
+                    if let Some(&target) = nodes[fallback].children.get(&byte) {
+                        break target;
+                    }
+                    if fallback == 0 {
+                        break 0;
+                    }
+                    fallback = nodes[fallback].failure;
+                };
+                nodes[child].failure = failure;
+                let inherited = nodes[failure].outputs.clone();
+                nodes[child].outputs.extend(inherited);
+                queue.push_back(child);
+            }
+        }
+
+        DigitMatcher { nodes, ignore_case }
+    }
+
+    /// Matches the digits 0 to 9 only, as part 1 reads the lines.
+    pub fn digits() -> Self {
+        let digits: Vec<(String, u32)> = (0..=9).map(|value: u32| (value.to_string(), value)).collect();
+        let patterns: Vec<(&str, u32)> = digits.iter().map(|(digit, value)| (digit.as_str(), *value)).collect();
+        DigitMatcher::new(&patterns, false)
+    }
+
+    /// Every match in `text`, ordered by start offset.
+    pub fn find_all(&self, text: &str) -> Vec<Match> {
+        let mut matches = Vec::new();
+        let mut current = 0;
+
+        for (index, &byte) in text.as_bytes().iter().enumerate() {
+            current = self.step(current, if self.ignore_case { byte.to_ascii_lowercase() } else { byte });
+            for &(len, value) in &self.nodes[current].outputs {
+                matches.push(Match { start: index + 1 - len, end: index + 1, value });
+            }
+        }
+
+        matches.sort_by_key(|token| (token.start, token.end));
+        matches
+    }
+
+    /// The first and last match in `text`, from a single scan.
+    ///
+    /// Where tokens nest, as Roman numerals do, the longest one wins: the first
+    /// token is the longest starting leftmost, the last the longest ending rightmost.
+    pub fn first_and_last(&self, text: &str) -> Option<(Match, Match)> {
+        let matches = self.find_all(text);
+        let first = matches.iter().min_by_key(|token| (token.start, Reverse(token.end)))?;
+        let last = matches.iter().max_by_key(|token| (token.end, Reverse(token.start)))?;
+        Some((*first, *last))
+    }
+
+    fn step(&self, mut state: usize, byte: u8) -> usize {
+        loop {
+            if let Some(&next) = self.nodes[state].children.get(&byte) {
+                return next;
+            }
+            if state == 0 {
+                return 0;
+            }
+            state = self.nodes[state].failure;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vocabulary::{MatchOptions, Vocabulary};
+
+    fn english() -> DigitMatcher {
+        Vocabulary::english().matcher(&MatchOptions::default())
+    }
+
+    #[test]
+    fn test_overlapping_matches() {
+        let matcher = english();
+        let values: Vec<(usize, u32)> = matcher.find_all("eightwothree").iter().map(|token| (token.start, token.value)).collect();
+        assert_eq!(values, vec![(0, 8), (4, 2), (7, 3)]);
+
+        let values: Vec<u32> = matcher.find_all("oneight2sevenine").iter().map(|token| token.value).collect();
+        assert_eq!(values, vec![1, 8, 2, 7, 9]);
+    }
+
+    #[test]
+    fn test_patterns_inside_other_patterns() {
+        // "he" ends inside "she", which the failure links must still report
+        let matcher = DigitMatcher::new(&[("he", 1), ("she", 2), ("hers", 3)], false);
+        let found: Vec<(usize, usize, u32)> = matcher.find_all("ushers").iter().map(|token| (token.start, token.end, token.value)).collect();
+        assert_eq!(found, vec![(1, 4, 2), (2, 4, 1), (2, 6, 3)]);
+    }
+
+    #[test]
+    fn test_first_and_last() {
+        let matcher = english();
+        let (first, last) = matcher.first_and_last("xtwone3four").unwrap();
+        assert_eq!((first.start, first.value), (1, 2));
+        assert_eq!((last.start, last.end, last.value), (7, 11, 4));
+        assert!(matcher.first_and_last("abc").is_none());
+
+        // Nested tokens resolve to the longest one at either end
+        let roman = DigitMatcher::new(&[("I", 1), ("II", 2), ("III", 3), ("IV", 4), ("V", 5), ("VIII", 8)], false);
+        let (first, last) = roman.first_and_last("xIVyVIIIz").unwrap();
+        assert_eq!((first.value, last.value), (4, 8));
+    }
+
+    #[test]
+    fn test_ignore_case() {
+        let matcher = DigitMatcher::new(&[("One", 1), ("two", 2)], true);
+        let values: Vec<u32> = matcher.find_all("ONEtWo").iter().map(|token| token.value).collect();
+        assert_eq!(values, vec![1, 2]);
+        assert!(DigitMatcher::new(&[("One", 1)], false).find_all("ONE").is_empty());
+    }
+}
@@ -1,213 +1,156 @@
-use std::fs::File;
-use std::io::{self, BufRead};
+use std::io;
 use std::path::Path;
-use anyhow::{Result, anyhow};
+use anyhow::{Result, Context};
 use std::env;
 
+mod audit;
+mod digit_matcher;
+mod vocabulary;
+use audit::{AuditFormat, LineAudit};
+use digit_matcher::DigitMatcher;
+use vocabulary::{MatchOptions, Vocabulary};
+
 enum Part {
     Part1,
     Part2,
 }
 
+// Usage: day01 [part1|part2] [--vocab NAME|PATH] [--ignore-case] [--zero]
+//              [--audit table|json] [--skip-invalid] [input]
+//
+// The vocabulary options only apply to part 2. Lines without a digit fail
+// the run unless --skip-invalid is given, then they're reported and skipped.
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    let part = match args.get(1) {
-        Some(arg) => match arg.as_str() {
-            "part1" => Part::Part1,
-            "part2" => Part::Part2,
-            _ => Part::Part1, // Default to Part1 if the argument doesn't match
-        },
-        None => Part::Part1, // Default to Part1 if no argument is provided
-    };
-
-    let input_file_path = "./src/data.txt";
-    let sum = read_sum_from_file(input_file_path, part).expect("Failed to read input file");
-    println!("Calibration value {}", sum);
-}
-
-fn read_sum_from_file(file_path: &str, part: Part) -> Result<u32> {
-    let path = Path::new(file_path);
-    let file = File::open(&path)?;
-    let reader = io::BufReader::new(file);
-
-    let mut values = Vec::new();
-
-    for line in reader.lines() {
-        match line {
-            Ok(line) => {
-                match part {
-                    Part::Part1 => {
-                        let value = read_value_part_1(&line)?;
-                        values.push(value);
-                    },
-                    Part::Part2 => {
-                        let value = read_value_part_2(&line)?;
-                        println!("{} converts to {}", &line, &value);
-                        values.push(value);
+    let mut part = Part::Part1; // Default to Part1 if no argument is provided
+    let mut vocabulary = Vocabulary::english();
+    let mut options = MatchOptions::default();
+    let mut audit_format = None;
+    let mut skip_invalid = false;
+    let mut input_file_path = "./src/data.txt".to_owned();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "part1" => part = Part::Part1,
+            "part2" => part = Part::Part2,
+            "--vocab" => {
+                let name = args.next().unwrap_or_default();
+                vocabulary = match Vocabulary::load(&name) {
+                    Ok(vocabulary) => vocabulary,
+                    Err(err) => {
+                        eprintln!("{:#}", err);
+                        std::process::exit(1);
+                    }
+                };
+            },
+            "--ignore-case" => options.ignore_case = true,
+            "--zero" => options.zero = true,
+            "--audit" => {
+                audit_format = match AuditFormat::from_name(&args.next().unwrap_or_default()) {
+                    Ok(format) => Some(format),
+                    Err(err) => {
+                        eprintln!("{:#}", err);
+                        std::process::exit(1);
                     }
-                }
+                };
             },
-            Err(err) => return Err(anyhow!(err))
+            "--skip-invalid" => skip_invalid = true,
+            _ => input_file_path = arg,
         }
     }
-    let sum = values.iter().sum();
-
-    Ok(sum)
-}
-
-fn read_value_part_1(s: &str) -> Result<u32> {
-    let d1 = first_digit(s);
-    let d2 = last_digit(&s);
-    match (d1, d2) {
-        (Some(d1), Some(d2)) => {
-            Ok((d1.to_string() + &d2.to_string()).parse::<u32>()?)
-        },
-        _ => Err(anyhow!("Invalid digit"))
-    }
-}
-
-fn read_value_part_2(s: &str) -> Result<u32> {
-    let d1 = first_digit(&convert_digit_words_to_numbers_ltr(&s));
-    let d2 = last_digit(&convert_digit_words_to_numbers_rtl(&s));
-    match (d1, d2) {
-        (Some(d1), Some(d2)) => {
-            Ok((d1.to_string() + &d2.to_string()).parse::<u32>()?)
-        },
-        _ => Err(anyhow!("Invalid digit"))
-    }
-}
-
-fn convert_digit_words_to_numbers_ltr(s: &str) -> String {
-    let word_to_digit = [
-        ("one", '1'), ("two", '2'), ("three", '3'), ("four", '4'),
-        ("five", '5'), ("six", '6'), ("seven", '7'), ("eight", '8'), ("nine", '9'),
-    ];
-
-    let mut result = String::new();
-    let mut remaining = s;
 
-    while !remaining.is_empty() {
-        let mut found = false;
-
-        for (word, digit) in &word_to_digit {
-            if remaining.starts_with(word) {
-                result.push(*digit);
-                remaining = &remaining[word.len()..];
-                found = true;
-                break;
-            }
+    let matcher = vocabulary.matcher(&options);
+    let audits = match audit_file(&input_file_path, part, &matcher) {
+        Ok(audits) => audits,
+        Err(err) => {
+            eprintln!("{:#}", err);
+            std::process::exit(1);
         }
+    };
 
-        // If no number word is found at the start, move one character forward.
-        if !found {
-            result.push(remaining.chars().next().unwrap());
-            remaining = &remaining[1..];
-        }
+    if let Some(format) = audit_format {
+        print!("{}", audit::render(&audits, format));
     }
-
-    result
-}
-
-fn convert_digit_words_to_numbers_rtl(s: &str) -> String {
-    let word_to_digit = [
-        ("nine", '9'), ("eight", '8'), ("seven", '7'), ("six", '6'), 
-        ("five", '5'), ("four", '4'), ("three", '3'), ("two", '2'), ("one", '1'),
-    ];
-
-    let mut result = String::new();
-    let mut chars: Vec<char> = s.chars().collect();
-    
-    while !chars.is_empty() {
-        let mut found = false;
-
-        for (word, digit) in &word_to_digit {
-            let word_chars: Vec<char> = word.chars().collect();
-            if chars.ends_with(&word_chars) {
-                result.push(*digit);
-                for _ in 0..word.len() {
-                    chars.pop();
-                }
-                found = true;
-                break;
+    match audit::calibration_sum(&audits, skip_invalid) {
+        Ok(sum) if audit_format.is_none() => {
+            for invalid in audit::failures(&audits) {
+                eprintln!("{}, skipped", invalid);
             }
-        }
-
-        if !found {
-            result.push(chars.pop().unwrap());
+            println!("Calibration value {}", sum);
+        },
+        Ok(_) => {},
+        Err(err) => {
+            eprintln!("{:#}, use --skip-invalid to skip such lines", err);
+            std::process::exit(1);
         }
     }
-
-    result.chars().rev().collect()
 }
 
-fn first_digit(s: &str) -> Option<char> {
-    s.chars().find(|c| c.is_digit(10))
-}
+/// Audits every line of the file. Part 1 only reads digits, part 2 whatever
+/// `matcher` matches.
+fn audit_file(file_path: &str, part: Part, matcher: &DigitMatcher) -> Result<Vec<LineAudit>> {
+    let path = Path::new(file_path);
+    let file = advent_vault::open(path).with_context(|| format!("Failed to open {}", file_path))?;
+    let reader = io::BufReader::new(file);
 
-fn last_digit(s: &str) -> Option<char> {
-    s.chars().rev().find(|c| c.is_digit(10))
+    match part {
+        Part::Part1 => audit::audit_lines(reader, &DigitMatcher::digits()),
+        Part::Part2 => audit::audit_lines(reader, matcher),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use propcheck::strings;
 
-    #[test]
-    fn test_first_digit() {
-        let digit_in_beginning = "1abc2".to_owned();
-        let digit_in_middle = "abc1def2".to_owned();
-        let digit_in_end = "abc1".to_owned();
-        let multiple_digits = "abc1def2ghi3".to_owned();
-        let missing_digit = "abc".to_owned();
-        
-        assert_eq!(first_digit(&digit_in_beginning), Some('1'));
-        assert_eq!(first_digit(&digit_in_middle), Some('1'));
-        assert_eq!(first_digit(&digit_in_end), Some('1'));
-        assert_eq!(first_digit(&multiple_digits), Some('1'));
-        assert_eq!(first_digit(&missing_digit), None);
+    fn english() -> DigitMatcher {
+        Vocabulary::english().matcher(&MatchOptions::default())
+    }
+
+    fn values(matcher: &DigitMatcher, s: &str) -> Option<(u32, u32)> {
+        matcher.first_and_last(s).map(|(first, last)| (first.value, last.value))
+    }
+
+    fn read_value(matcher: &DigitMatcher, s: &str) -> Option<u32> {
+        audit::audit_line(1, s, matcher).value()
+    }
+
+    fn read_sum_from_file(file_path: &str, part: Part) -> Result<u32> {
+        audit::calibration_sum(&audit_file(file_path, part, &english())?, false)
     }
 
     #[test]
-    fn test_last_digit() {
-        let digit_in_end = "1abc2".to_owned();
-        let digit_in_middle = "abc12def".to_owned();
-        let digit_early = "12abcdef".to_owned();
-        let multiple_digits = "1abc2345def67".to_owned();
-        let missing_digit = "abc".to_owned();
-        
-        assert_eq!(last_digit(&digit_in_end), Some('2'));
-        assert_eq!(last_digit(&digit_in_middle), Some('2'));
-        assert_eq!(last_digit(&digit_early), Some('2'));
-        assert_eq!(last_digit(&multiple_digits), Some('7'));
-        assert_eq!(last_digit(&missing_digit), None);
+    fn test_first_and_last_digit() {
+        let digits = DigitMatcher::digits();
+        assert_eq!(values(&digits, "1abc2"), Some((1, 2)));
+        assert_eq!(values(&digits, "abc1def2"), Some((1, 2)));
+        assert_eq!(values(&digits, "abc12def"), Some((1, 2)));
+        assert_eq!(values(&digits, "abc1"), Some((1, 1)));
+        assert_eq!(values(&digits, "1abc2345def67"), Some((1, 7)));
+        assert_eq!(values(&digits, "abc"), None);
     }
 
     #[test]
     fn test_read_value() {
-        let valid_back_to_back_digits = "abc12def".to_owned();
-        let valid_separated_digits = "abc1def2".to_owned();
-        let valid_missing_second = "abc1".to_owned();
-        let missing_both = "abc".to_owned();
-        let valid_multiple_digits = "abc1def2ghi3".to_owned();
-
-        assert_eq!(read_value_part_1(&valid_back_to_back_digits).unwrap(), 12);
-        assert_eq!(read_value_part_1(&valid_separated_digits).unwrap(), 12);
-        assert_eq!(read_value_part_1(&valid_missing_second).unwrap(), 11);
-        assert_eq!(format!("{}", read_value_part_1(&missing_both).unwrap_err()), "Invalid digit");
-        assert_eq!(read_value_part_1(&valid_multiple_digits).unwrap(), 13);
+        let digits = DigitMatcher::digits();
+        assert_eq!(read_value(&digits, "abc12def"), Some(12));
+        assert_eq!(read_value(&digits, "abc1def2"), Some(12));
+        assert_eq!(read_value(&digits, "abc1"), Some(11));
+        assert_eq!(read_value(&digits, "abc1def2ghi3"), Some(13));
+        assert_eq!(audit::audit_line(1, "abc", &digits).failure(), Some(audit::Failure::NoDigit));
     }
 
+    /**
+     * Overlapping words are all found, so the first and last digit come from
+     * the leftmost and rightmost word even where they share letters.
+     */
     #[test]
-    fn test_convert_digit_words_to_numbers() {
-        let test_strings = [
-            ("123fourfive67", "1234567", "1234567"),
-            ("eightwothree", "8wo3", "eigh23"),
-            ("eighthree", "8hree", "eigh3")
-        ];
-        for (input, ltr_expected, rtl_expected) in test_strings.iter() {
-            assert_eq!(convert_digit_words_to_numbers_ltr(&input), *ltr_expected);
-            assert_eq!(convert_digit_words_to_numbers_rtl(&input), *rtl_expected);
-        }
+    fn test_overlapping_digit_words() {
+        let found: Vec<(usize, u32)> = english().find_all("eightwothree").iter().map(|token| (token.start, token.value)).collect();
+        assert_eq!(found, vec![(0, 8), (4, 2), (7, 3)]);
+        assert_eq!(values(&english(), "123fourfive67"), Some((1, 7)));
+        assert_eq!(values(&english(), "eighthree"), Some((8, 3)));
     }
 
     #[test]
@@ -223,7 +166,7 @@ mod tests {
         ];
 
         for (input, expected) in test_rows.iter() {
-            assert_eq!(read_value_part_2(input).unwrap(), *expected);
+            assert_eq!(read_value(&english(), input), Some(*expected));
         }
     }
 
@@ -238,4 +181,39 @@ mod tests {
         let sum = read_sum_from_file("./src/part2_test_data.txt", Part::Part2).expect("failed to read test_data.txt");
         assert_eq!(281, sum);
     }
+
+    const WORDS: [&str; 9] = ["one", "two", "three", "four", "five", "six", "seven", "eight", "nine"];
+
+    /// The digit or digit word `s` starts with.
+    fn digit_at_start(s: &str) -> Option<u32> {
+        if let Some(digit) = s.chars().next().and_then(|ch| ch.to_digit(10)) {
+            return Some(digit);
+        }
+        WORDS.iter().zip(1..).find(|(word, _)| s.starts_with(*word)).map(|(_, value)| value)
+    }
+
+    /// Every digit and digit word, from trying each position.
+    fn find_all_brute_force(s: &str) -> Vec<(usize, u32)> {
+        (0..s.len()).filter_map(|index| Some((index, digit_at_start(&s[index..])?))).collect()
+    }
+
+    /// First and last digit from trying every position.
+    fn read_value_brute_force(s: &str) -> Option<u32> {
+        let values: Vec<u32> = (0..s.len()).filter_map(|index| digit_at_start(&s[index..])).collect();
+        Some(values.first()? * 10 + values.last()?)
+    }
+
+    #[test]
+    fn test_digit_words_match_brute_force() {
+        // Letters of overlapping words such as "oneight" and "twone", plus two digits
+        let lines = strings("onetwhigrvs30", 0, 24);
+        let english = english();
+        propcheck::check_against(
+            "find_all",
+            &lines,
+            |s| english.find_all(s).iter().map(|token| (token.start, token.value)).collect::<Vec<_>>(),
+            |s| find_all_brute_force(s),
+        );
+        propcheck::check_against("part 2 value", &lines, |s| read_value(&english, s), |s| read_value_brute_force(s));
+    }
 }
@@ -0,0 +1,182 @@
+use std::fs;
+use std::path::Path;
+use anyhow::{Result, anyhow, Context};
+
+use crate::digit_matcher::DigitMatcher;
+
+/// How the words of a vocabulary are matched.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MatchOptions {
+    /// Match ASCII letters regardless of case.
+    pub ignore_case: bool,
+    /// Also match the vocabulary's word for zero, a `0` is always a digit.
+    pub zero: bool,
+}
+
+/// Words for the numbers one to nine, and optionally zero.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vocabulary {
+    pub name: String,
+    /// Words with their values, 1 to 9. A value may have several words.
+    pub words: Vec<(String, u32)>,
+    /// Only matched when zero is enabled.
+    pub zero: Option<String>,
+}
+
+pub const BUILT_IN: [&str; 4] = ["english", "finnish", "german", "roman"];
+
+impl Vocabulary {
+    fn from_words(name: &str, words: [&str; 9], zero: Option<&str>) -> Self {
+        Vocabulary {
+            name: name.to_owned(),
+            words: words.iter().zip(1..).map(|(word, value)| (word.to_string(), value)).collect(),
+            zero: zero.map(str::to_owned),
+        }
+    }
+
+    pub fn english() -> Self {
+        Vocabulary::from_words(
+            "english",
+            ["one", "two", "three", "four", "five", "six", "seven", "eight", "nine"],
+            Some("zero"),
+        )
+    }
+
+    pub fn finnish() -> Self {
+        Vocabulary::from_words(
+            "finnish",
+            ["yksi", "kaksi", "kolme", "neljä", "viisi", "kuusi", "seitsemän", "kahdeksan", "yhdeksän"],
+            Some("nolla"),
+        )
+    }
+
+    pub fn german() -> Self {
+        Vocabulary::from_words(
+            "german",
+            ["eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun"],
+            Some("null"),
+        )
+    }
+
+    /// Roman numerals, which have no zero.
+    pub fn roman() -> Self {
+        Vocabulary::from_words("roman", ["I", "II", "III", "IV", "V", "VI", "VII", "VIII", "IX"], None)
+    }
+
+    pub fn built_in(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "english" | "en" => Some(Vocabulary::english()),
+            "finnish" | "fi" => Some(Vocabulary::finnish()),
+            "german" | "de" => Some(Vocabulary::german()),
+            "roman" => Some(Vocabulary::roman()),
+            _ => None,
+        }
+    }
+
+    /// A built-in vocabulary by name, otherwise a vocabulary file.
+    pub fn load(name_or_path: &str) -> Result<Self> {
+        if let Some(vocabulary) = Vocabulary::built_in(name_or_path) {
+            return Ok(vocabulary);
+        }
+        let path = Path::new(name_or_path);
+        if !path.exists() {
+            return Err(anyhow!(
+                "Unknown vocabulary '{}', expected one of {} or a file",
+                name_or_path,
+                BUILT_IN.join(", ")
+            ));
+        }
+        let text = fs::read_to_string(path).with_context(|| format!("Failed to read vocabulary {}", name_or_path))?;
+        let name = path.file_stem().map_or(name_or_path.into(), |stem| stem.to_string_lossy());
+        Vocabulary::parse(&name, &text)
+    }
+
+    /// Parses `word = value` lines, `#` starts a comment. A word with value 0
+    /// is the word for zero.
+    pub fn parse(name: &str, text: &str) -> Result<Self> {
+        let mut words = Vec::new();
+        let mut zero = None;
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (word, value) = line
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Line {}: expected 'word = value', found '{}'", index + 1, line))?;
+            let word = word.trim();
+            if word.is_empty() {
+                return Err(anyhow!("Line {}: missing word", index + 1));
+            }
+            match value.trim().parse::<u32>() {
+                Ok(0) => zero = Some(word.to_owned()),
+                Ok(value @ 1..=9) => words.push((word.to_owned(), value)),
+                _ => return Err(anyhow!("Line {}: '{}' is not a value from 0 to 9", index + 1, value.trim())),
+            }
+        }
+
+        for value in 1..=9 {
+            if !words.iter().any(|&(_, word_value)| word_value == value) {
+                return Err(anyhow!("Vocabulary {} has no word for {}", name, value));
+            }
+        }
+
+        Ok(Vocabulary { name: name.to_owned(), words, zero })
+    }
+
+    /// A matcher for the vocabulary's words and the digits.
+    pub fn matcher(&self, options: &MatchOptions) -> DigitMatcher {
+        const DIGITS: [&str; 10] = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+
+        let mut patterns: Vec<(&str, u32)> = self.words.iter().map(|(word, value)| (word.as_str(), *value)).collect();
+        patterns.extend(DIGITS.iter().zip(0..).map(|(&digit, value)| (digit, value)));
+        if let Some(zero) = self.zero.as_ref().filter(|_| options.zero) {
+            patterns.push((zero.as_str(), 0));
+        }
+        DigitMatcher::new(&patterns, options.ignore_case)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(vocabulary: &Vocabulary, options: &MatchOptions, line: &str) -> Option<u32> {
+        let (first, last) = vocabulary.matcher(options).first_and_last(line)?;
+        Some(first.value * 10 + last.value)
+    }
+
+    #[test]
+    fn test_built_in_vocabularies() {
+        let options = MatchOptions::default();
+        assert_eq!(value(&Vocabulary::finnish(), &options, "xkolmeyhdeksänz"), Some(39));
+        assert_eq!(value(&Vocabulary::german(), &options, "achteins2fünf"), Some(85));
+        assert_eq!(value(&Vocabulary::roman(), &options, "aIXbVIIc"), Some(97));
+        assert_eq!(Vocabulary::load("DE").unwrap(), Vocabulary::german());
+    }
+
+    #[test]
+    fn test_zero_and_case() {
+        let english = Vocabulary::english();
+        assert_eq!(value(&english, &MatchOptions::default(), "zero5zero"), Some(55));
+        assert_eq!(value(&english, &MatchOptions { zero: true, ..Default::default() }, "zero5zero"), Some(0));
+        assert_eq!(value(&english, &MatchOptions { zero: true, ..Default::default() }, "0ab7"), Some(7));
+        assert_eq!(value(&english, &MatchOptions::default(), "a0b7"), Some(7));
+        assert_eq!(value(&english, &MatchOptions::default(), "zero0"), Some(0));
+        assert_eq!(value(&english, &MatchOptions::default(), "TWOxONE"), None);
+        assert_eq!(value(&english, &MatchOptions { ignore_case: true, ..Default::default() }, "TWOxONE"), Some(21));
+    }
+
+    #[test]
+    fn test_parse_vocabulary_file() {
+        let text = "# Dutch\nnul = 0\neen = 1\ntwee = 2\ndrie = 3\nvier = 4\nvijf = 5\nzes = 6\nzeven = 7\nacht = 8\nnegen = 9\n";
+        let dutch = Vocabulary::parse("dutch", text).unwrap();
+        assert_eq!(dutch.zero.as_deref(), Some("nul"));
+        assert_eq!(value(&dutch, &MatchOptions::default(), "tweeacht5zeven"), Some(27));
+
+        assert!(Vocabulary::parse("broken", "een = 1\n").unwrap_err().to_string().contains("no word for 2"));
+        assert!(Vocabulary::parse("broken", "een = 10\n").unwrap_err().to_string().starts_with("Line 1:"));
+        assert!(Vocabulary::load("klingon").is_err());
+    }
+}
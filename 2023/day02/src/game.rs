@@ -0,0 +1,242 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use anyhow::{Result, anyhow, Context};
+
+/// The cubes revealed at once, by colour.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GameSet {
+    cubes: BTreeMap<String, u32>,
+}
+
+/// The cubes in the bag, by colour. Colours that aren't listed have no cubes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BagContent {
+    cubes: BTreeMap<String, u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    pub id: u32,
+    pub game_sets: Vec<GameSet>,
+}
+
+/// Parses a fragment like `3 blue`, colours are case-insensitive.
+fn parse_cubes(fragment: &str) -> Result<(String, u32)> {
+    match fragment.split_whitespace().collect::<Vec<&str>>().as_slice() {
+        [quantity, color] => {
+            let quantity = quantity
+                .parse::<u32>()
+                .map_err(|_| anyhow!("invalid cube count '{}' in '{}'", quantity, fragment.trim()))?;
+            Ok((color.to_lowercase(), quantity))
+        }
+        _ => Err(anyhow!("expected '<count> <colour>', found '{}'", fragment.trim())),
+    }
+}
+
+impl GameSet {
+    pub fn new() -> Self {
+        GameSet::default()
+    }
+
+    /// Adds to the cubes of `color` shown, the count stops at `u32::MAX`.
+    pub fn add(&mut self, color: &str, quantity: u32) {
+        let count = self.cubes.entry(color.to_lowercase()).or_insert(0);
+        *count = count.saturating_add(quantity);
+    }
+
+    /// Number of cubes of `color` shown, 0 if there were none.
+    pub fn count(&self, color: &str) -> u32 {
+        self.cubes.get(color).copied().unwrap_or(0)
+    }
+
+    pub fn is_possible(&self, bag: &BagContent) -> bool {
+        self.cubes.keys().all(|color| bag.count(color) >= self.count(color))
+    }
+}
+
+impl BagContent {
+    pub fn new(cubes: &[(&str, u32)]) -> Self {
+        BagContent { cubes: cubes.iter().map(|&(color, quantity)| (color.to_lowercase(), quantity)).collect() }
+    }
+
+    /// The bag of the puzzle: 12 red, 13 green and 14 blue cubes.
+    pub fn standard() -> Self {
+        BagContent::new(&[("red", 12), ("green", 13), ("blue", 14)])
+    }
+
+    pub fn count(&self, color: &str) -> u32 {
+        self.cubes.get(color).copied().unwrap_or(0)
+    }
+
+    pub fn total(&self) -> u64 {
+        self.cubes.values().map(|&quantity| quantity as u64).sum()
+    }
+
+    pub fn colors(&self) -> impl Iterator<Item = &str> {
+        self.cubes.keys().map(String::as_str)
+    }
+
+    /// Parses a bag like `12 red, 13 green, 14 blue`. Fragments may also be
+    /// on separate lines and `#` starts a comment.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut cubes = BTreeMap::new();
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("");
+            for fragment in line.split(',').filter(|fragment| !fragment.trim().is_empty()) {
+                let (color, quantity) = parse_cubes(fragment).context("Bag")?;
+                if cubes.insert(color.clone(), quantity).is_some() {
+                    return Err(anyhow!("Bag: colour {} is listed twice", color));
+                }
+            }
+        }
+        if cubes.is_empty() {
+            return Err(anyhow!("Bag: no cubes listed"));
+        }
+        Ok(BagContent { cubes })
+    }
+
+    /// A bag file if `spec` names one, otherwise the bag written out.
+    pub fn load(spec: &str) -> Result<Self> {
+        let path = Path::new(spec);
+        if path.is_file() {
+            let text = fs::read_to_string(path).with_context(|| format!("Failed to read bag {}", spec))?;
+            BagContent::parse(&text).with_context(|| format!("In {}", spec))
+        } else {
+            BagContent::parse(spec)
+        }
+    }
+}
+
+impl fmt::Display for BagContent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cubes: Vec<String> = self.cubes.iter().map(|(color, quantity)| format!("{} {}", quantity, color)).collect();
+        write!(f, "{}", cubes.join(", "))
+    }
+}
+
+impl Game {
+    /// Parses a line like `Game 1: 3 blue, 4 red; 1 red, 2 green`.
+    pub fn parse(description: &str) -> Result<Self> {
+        let (id_part, sets_part) = description
+            .split_once(':')
+            .ok_or_else(|| anyhow!("expected 'Game <id>: <sets>', found '{}'", description.trim()))?;
+        let id = match id_part.split_whitespace().collect::<Vec<&str>>().as_slice() {
+            ["Game", id] => id.parse::<u32>().ok(),
+            _ => None,
+        }
+        .ok_or_else(|| anyhow!("expected 'Game <id>', found '{}'", id_part.trim()))?;
+
+        let mut game_sets = Vec::new();
+        for set_desc in sets_part.split(';') {
+            let mut game_set = GameSet::new();
+            for component in set_desc.split(',') {
+                let (color, quantity) = parse_cubes(component).with_context(|| format!("Game {}", id))?;
+                game_set.add(&color, quantity);
+            }
+            game_sets.push(game_set);
+        }
+        Ok(Game { id, game_sets })
+    }
+
+    pub fn is_possible(&self, bag: &BagContent) -> bool {
+        self.game_sets.iter().all(|game_set| game_set.is_possible(bag))
+    }
+
+    /// The smallest bag every set of the game could have been drawn from.
+    pub fn fewest_cubes(&self) -> BagContent {
+        let mut cubes = BTreeMap::new();
+        for game_set in &self.game_sets {
+            for (color, &quantity) in &game_set.cubes {
+                let fewest = cubes.entry(color.clone()).or_insert(0);
+                *fewest = quantity.max(*fewest);
+            }
+        }
+        BagContent { cubes }
+    }
+
+    /// Product of the fewest cubes needed of each of the bag's colours, a
+    /// colour the game never shows makes it 0. `None` if it overflows.
+    pub fn get_power(&self, bag: &BagContent) -> Option<u64> {
+        let fewest_cubes = self.fewest_cubes();
+        bag.colors().try_fold(1u64, |power, color| power.checked_mul(fewest_cubes.count(color) as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(game_set: &GameSet) -> (u32, u32, u32) {
+        (game_set.count("red"), game_set.count("green"), game_set.count("blue"))
+    }
+
+    fn fewest(game: &Game) -> (u32, u32, u32) {
+        let bag = game.fewest_cubes();
+        (bag.count("red"), bag.count("green"), bag.count("blue"))
+    }
+
+    #[test]
+    fn test_parse_game() {
+        let standard = BagContent::standard();
+
+        let a = Game::parse("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green").unwrap();
+        assert_eq!(a.id, 1);
+        assert_eq!(a.game_sets.iter().map(counts).collect::<Vec<_>>(), vec![(4, 0, 3), (1, 2, 6), (0, 2, 0)]);
+        assert_eq!(fewest(&a), (4, 2, 6));
+        assert_eq!(a.get_power(&standard), Some(48));
+
+        let b = Game::parse("Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue").unwrap();
+        assert_eq!(b.id, 2);
+        assert_eq!(b.game_sets.iter().map(counts).collect::<Vec<_>>(), vec![(0, 2, 1), (1, 3, 4), (0, 1, 1)]);
+        assert_eq!(fewest(&b), (1, 3, 4));
+        assert_eq!(b.get_power(&standard), Some(12));
+
+        let c = Game::parse("Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red").unwrap();
+        assert_eq!(c.id, 3);
+        assert_eq!(c.game_sets.iter().map(counts).collect::<Vec<_>>(), vec![(20, 8, 6), (4, 13, 5), (1, 5, 0)]);
+        assert_eq!(fewest(&c), (20, 13, 6));
+        assert_eq!(c.get_power(&standard), Some(1560));
+
+        let d = Game::parse("Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red").unwrap();
+        assert_eq!(d.id, 4);
+        assert_eq!(d.game_sets.iter().map(counts).collect::<Vec<_>>(), vec![(3, 1, 6), (6, 3, 0), (14, 3, 15)]);
+        assert_eq!(fewest(&d), (14, 3, 15));
+        assert_eq!(d.get_power(&standard), Some(630));
+
+        let e = Game::parse("Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green").unwrap();
+        assert_eq!(e.id, 5);
+        assert_eq!(e.game_sets.iter().map(counts).collect::<Vec<_>>(), vec![(6, 3, 1), (1, 2, 2)]);
+        assert_eq!(fewest(&e), (6, 3, 2));
+        assert_eq!(e.get_power(&standard), Some(36));
+    }
+
+    #[test]
+    fn test_other_colours() {
+        let game = Game::parse("Game 7: 2 Yellow, 1 red; 3 yellow").unwrap();
+        assert_eq!(game.game_sets[0].count("yellow"), 2);
+        assert!(!game.is_possible(&BagContent::standard()));
+
+        let bag = BagContent::parse("1 red\n3 yellow # and nothing else").unwrap();
+        assert!(game.is_possible(&bag));
+        assert_eq!(bag.to_string(), "1 red, 3 yellow");
+        assert_eq!(game.get_power(&bag), Some(3));
+        assert_eq!(game.get_power(&BagContent::standard()), Some(0));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |line: &str| format!("{:#}", Game::parse(line).unwrap_err());
+        assert_eq!(error("Game 3: 1 red; blue"), "Game 3: expected '<count> <colour>', found 'blue'");
+        assert_eq!(error("Game 3: 1 red, x blue"), "Game 3: invalid cube count 'x' in 'x blue'");
+        assert_eq!(error("Game 3: 1 red;; 2 blue"), "Game 3: expected '<count> <colour>', found ''");
+        assert_eq!(error("Game three: 1 red"), "expected 'Game <id>', found 'Game three'");
+        assert_eq!(error("1 red, 2 blue"), "expected 'Game <id>: <sets>', found '1 red, 2 blue'");
+
+        let bag_error = |text: &str| format!("{:#}", BagContent::parse(text).unwrap_err());
+        assert_eq!(bag_error("12 red, lots green"), "Bag: invalid cube count 'lots' in 'lots green'");
+        assert_eq!(bag_error("1 red, 2 RED"), "Bag: colour red is listed twice");
+        assert_eq!(bag_error("# empty"), "Bag: no cubes listed");
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::BTreeSet;
+use anyhow::{Result, anyhow};
+
+use crate::game::{BagContent, Game};
+
+/// What a bag split is chosen for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Objective {
+    /// As many possible games as possible.
+    Games,
+    /// The highest sum of the ids of the possible games.
+    IdSum,
+}
+
+impl Objective {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "games" => Ok(Objective::Games),
+            "ids" => Ok(Objective::IdSum),
+            _ => Err(anyhow!("Unknown objective '{}', expected games or ids", name)),
+        }
+    }
+
+    fn score(&self, game: &Game) -> u64 {
+        match self {
+            Objective::Games => 1,
+            Objective::IdSum => game.id as u64,
+        }
+    }
+}
+
+/// A bag chosen for a fixed number of cubes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Split {
+    pub bag: BagContent,
+    /// Cubes of the total the bag doesn't need, they may be of any colour.
+    pub spare: u32,
+    /// Ids of the games the bag makes possible.
+    pub games: Vec<u32>,
+    pub score: u64,
+}
+
+/// The smallest bag that makes every game possible.
+pub fn minimum_bag(games: &[Game]) -> BagContent {
+    let fewest: Vec<BagContent> = games.iter().map(Game::fewest_cubes).collect();
+    let colors: BTreeSet<&str> = fewest.iter().flat_map(BagContent::colors).collect();
+    let cubes: Vec<(&str, u32)> = colors
+        .into_iter()
+        .map(|color| (color, fewest.iter().map(|bag| bag.count(color)).max().unwrap_or(0)))
+        .collect();
+    BagContent::new(&cubes)
+}
+
+/// The largest set of games a bag of `total` cubes can make possible.
+pub fn largest_subset(games: &[Game], total: u32) -> Split {
+    best_split(games, total, Objective::Games)
+}
+
+/// The split of `total` cubes over the games' colours that scores best.
+///
+/// A best bag always holds, of every colour, exactly as many cubes as one of
+/// the games needs (or none), so only those counts are tried. Ties go to the
+/// bag needing the fewest cubes.
+pub fn best_split(games: &[Game], total: u32, objective: Objective) -> Split {
+    let fewest: Vec<BagContent> = games.iter().map(Game::fewest_cubes).collect();
+    let colors: Vec<&str> = fewest.iter().flat_map(BagContent::colors).collect::<BTreeSet<_>>().into_iter().collect();
+    let needs: Vec<Vec<u32>> = fewest.iter().map(|bag| colors.iter().map(|color| bag.count(color)).collect()).collect();
+    let candidates: Vec<Vec<u32>> = (0..colors.len())
+        .map(|index| {
+            let counts: BTreeSet<u32> = needs.iter().map(|need| need[index]).chain([0]).collect();
+            counts.into_iter().collect()
+        })
+        .collect();
+
+    let mut search = Search { games, objective, needs: &needs, candidates: &candidates, total, best: None };
+    search.visit(&mut Vec::new(), 0);
+    let (score, used, counts) = search.best.unwrap_or((0, 0, vec![0; colors.len()]));
+
+    let possible = |need: &Vec<u32>| need.iter().zip(&counts).all(|(need, count)| need <= count);
+    let cubes: Vec<(&str, u32)> = colors.iter().copied().zip(counts.iter().copied()).collect();
+    Split {
+        bag: BagContent::new(&cubes),
+        spare: total - used,
+        games: games.iter().zip(&needs).filter(|(_, need)| possible(need)).map(|(game, _)| game.id).collect(),
+        score,
+    }
+}
+
+struct Search<'a> {
+    games: &'a [Game],
+    objective: Objective,
+    /// Fewest cubes of each colour per game.
+    needs: &'a [Vec<u32>],
+    /// Counts worth trying per colour, ascending.
+    candidates: &'a [Vec<u32>],
+    total: u32,
+    /// Score, cubes used and counts of the best bag so far.
+    best: Option<(u64, u32, Vec<u32>)>,
+}
+
+impl Search<'_> {
+    fn visit(&mut self, counts: &mut Vec<u32>, used: u32) {
+        let index = counts.len();
+        if index == self.candidates.len() {
+            let score = self
+                .games
+                .iter()
+                .zip(self.needs)
+                .filter(|(_, need)| need.iter().zip(counts.iter()).all(|(need, count)| need <= count))
+                .map(|(game, _)| self.objective.score(game))
+                .sum();
+            let better = match &self.best {
+                Some((best_score, best_used, _)) => (score, *best_used) > (*best_score, used),
+                None => true,
+            };
+            if better {
+                self.best = Some((score, used, counts.clone()));
+            }
+            return;
+        }
+
+        for &count in &self.candidates[index] {
+            // Checked against what is left so that huge counts can't overflow
+            if count > self.total - used {
+                break;
+            }
+            counts.push(count);
+            self.visit(counts, used + count);
+            counts.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example_games() -> Vec<Game> {
+        [
+            "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green",
+            "Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue",
+            "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red",
+            "Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red",
+            "Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green",
+        ]
+        .iter()
+        .map(|line| Game::parse(line).unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn test_minimum_bag() {
+        let games = example_games();
+        let bag = minimum_bag(&games);
+        assert_eq!(bag.to_string(), "15 blue, 13 green, 20 red");
+        assert!(games.iter().all(|game| game.is_possible(&bag)));
+        assert_eq!(minimum_bag(&[]), BagContent::default());
+    }
+
+    #[test]
+    fn test_largest_subset() {
+        let games = example_games();
+        let split = largest_subset(&games, 12);
+        assert_eq!((split.games.clone(), split.spare), (vec![2], 4));
+        assert_eq!(split.bag.to_string(), "4 blue, 3 green, 1 red");
+
+        // Games 1 and 2 or games 2 and 5, the bag for the latter comes first
+        let split = largest_subset(&games, 13);
+        assert_eq!((split.games, split.score, split.spare), (vec![2, 5], 2, 0));
+
+        let split = largest_subset(&games, 48);
+        assert_eq!(split.games, vec![1, 2, 3, 4, 5]);
+        assert_eq!(split.bag, minimum_bag(&games));
+
+        assert_eq!(largest_subset(&games, 7).games, Vec::<u32>::new());
+    }
+
+    #[test]
+    fn test_best_split_by_ids() {
+        let games = example_games();
+        let split = best_split(&games, 12, Objective::IdSum);
+        assert_eq!((split.games, split.score, split.spare), (vec![5], 5, 1));
+
+        let split = best_split(&games, 31, Objective::IdSum);
+        assert_eq!((split.games, split.score), (vec![1, 2, 5], 8));
+        let split = best_split(&games, 32, Objective::IdSum);
+        assert_eq!((split.games, split.score), (vec![1, 2, 4, 5], 12));
+        assert_eq!(split.bag.to_string(), "15 blue, 3 green, 14 red");
+    }
+
+    #[test]
+    fn test_best_split_with_huge_counts() {
+        let games: Vec<Game> = ["Game 1: 1 blue", "Game 2: 4294967295 red"].iter().map(|line| Game::parse(line).unwrap()).collect();
+        let split = best_split(&games, 5, Objective::Games);
+        assert_eq!((split.games, split.spare), (vec![1], 4));
+
+        let split = best_split(&games, u32::MAX, Objective::IdSum);
+        assert_eq!((split.games, split.score, split.spare), (vec![2], 2, 0));
+    }
+}
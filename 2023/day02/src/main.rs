@@ -1,251 +1,252 @@
-use std::fs::File;
 use std::io::{self, BufRead};
-use std::path::Path;
-use anyhow::{Result, anyhow};
+use std::path::{Path, PathBuf};
+use anyhow::{Result, Context, anyhow};
 use std::env;
+use std::process;
+use advent_config::Config;
+use serde::{Deserialize, Deserializer};
+
+mod game;
+mod inference;
+use game::{BagContent, Game};
+use inference::Objective;
 
 enum Part {
     Part1,
     Part2,
 }
 
-struct GameSet {
-    red: u32,
-    green: u32,
-    blue: u32
-}
-
-struct Game {
-    id: u32,
-    game_sets: Vec<GameSet>,
+/// Questions about all games at once, asked instead of a part.
+enum Query {
+    MinBag,
+    LargestSubset(u32),
+    BestSplit(u32),
 }
 
-struct BagContent {
-    red: u32,
-    green: u32,
-    blue: u32
+/// This day's settings from `advent.toml`, flags override them.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct Settings {
+    /// Relative to advent.toml, the working directory when unset.
+    input_dir: Option<PathBuf>,
+    /// Written like `--bag`.
+    #[serde(deserialize_with = "deserialize_bag")]
+    bag: BagContent,
 }
 
-impl GameSet {
-    fn new() -> Self {
-        GameSet { red: 0, green: 0, blue: 0 }
-    }
-
-    fn add(&mut self, color: &str, quantity: u32) {
-        match color {
-            "red" => self.red += quantity,
-            "green" => self.green += quantity,
-            "blue" => self.blue += quantity,
-            // _ => Err(anyhow!("Invalid color"))
-            _ => {}
-        }
-    }
-
-    fn is_possible(&self, bag: &BagContent) -> bool {
-        bag.red >= self.red && bag.green >= self.green && bag.blue >= self.blue
+impl Default for Settings {
+    fn default() -> Self {
+        Settings { input_dir: None, bag: BagContent::standard() }
     }
 }
 
-impl Game {
-    fn is_possible(&self, bag: &BagContent) -> bool {
-        self.game_sets.iter().all(|game_set| game_set.is_possible(bag))
-    }
-
-    fn fewest_cubes(&self) -> BagContent {
-        let mut min_red = u32::MIN;
-        let mut min_green = u32::MIN;
-        let mut min_blue = u32::MIN;
+fn deserialize_bag<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<BagContent, D::Error> {
+    let spec = String::deserialize(deserializer)?;
+    BagContent::load(&spec).map_err(|err| serde::de::Error::custom(format!("{:#}", err)))
+}
 
-        for game_set in &self.game_sets {
-            min_red = min_red.max(game_set.red);
-            min_green = min_green.max(game_set.green);
-            min_blue = min_blue.max(game_set.blue);
+// Usage: day02 [part1|part2] [--bag CUBES|PATH] [input]
+//        day02 min-bag [input]
+//        day02 largest-subset TOTAL [input]
+//        day02 best-split TOTAL [--by games|ids] [input]
+//
+// The bag is written like a game set, e.g. "12 red, 13 green, 14 blue", or
+// read from a file listing its cubes. It and the directory holding data.txt
+// default to the ones in advent.toml.
+fn main() {
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
         }
-
-        BagContent {
-            red: min_red,
-            green: min_green,
-            blue: min_blue,
+    };
+    let mut settings: Settings = match config.day(2023, 2) {
+        Ok(settings) => settings,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    };
+    let mut part = Part::Part1; // Default to Part1 if no argument is provided
+    let mut query = None;
+    let mut objective = Objective::Games;
+    let mut input_file_path = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "part1" => part = Part::Part1,
+            "part2" => part = Part::Part2,
+            "min-bag" => query = Some(Query::MinBag),
+            "largest-subset" | "best-split" => {
+                let total = match args.next().and_then(|total| total.parse::<u32>().ok()) {
+                    Some(total) => total,
+                    None => {
+                        eprintln!("{} expects a total number of cubes", arg);
+                        process::exit(1);
+                    }
+                };
+                query = Some(if arg == "largest-subset" { Query::LargestSubset(total) } else { Query::BestSplit(total) });
+            },
+            "--by" => {
+                objective = match Objective::from_name(&args.next().unwrap_or_default()) {
+                    Ok(objective) => objective,
+                    Err(err) => {
+                        eprintln!("{:#}", err);
+                        process::exit(1);
+                    }
+                };
+            },
+            "--bag" => {
+                settings.bag = match BagContent::load(&args.next().unwrap_or_default()) {
+                    Ok(bag) => bag,
+                    Err(err) => {
+                        eprintln!("{:#}", err);
+                        process::exit(1);
+                    }
+                };
+            },
+            _ => input_file_path = Some(arg),
         }
     }
+    let input_dir = settings.input_dir.map_or_else(|| PathBuf::from("."), |dir| config.resolve(&dir));
+    let input_file_path = input_file_path.unwrap_or_else(|| input_dir.join("data.txt").to_string_lossy().into_owned());
 
-    fn get_power(&self) -> u32 {
-        let fewest_cubes = self.fewest_cubes();
-        fewest_cubes.red * fewest_cubes.green * fewest_cubes.blue
+    let result = match query {
+        Some(query) => answer_query(&input_file_path, query, objective),
+        None => read_from_file(&input_file_path, &settings.bag, part).map(|sum| println!("id_sum is {}", sum)),
+    };
+    if let Err(err) = result {
+        eprintln!("{:#}", err);
+        process::exit(1);
     }
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    let part = match args.get(1) {
-        Some(arg) => match arg.as_str() {
-            "part1" => Part::Part1,
-            "part2" => Part::Part2,
-            _ => Part::Part1, // Default to Part1 if the argument doesn't match
+fn answer_query(file_path: &str, query: Query, objective: Objective) -> Result<()> {
+    let games = read_games(file_path)?;
+    let split = match query {
+        Query::MinBag => {
+            let bag = inference::minimum_bag(&games);
+            println!("Minimum bag is {} ({} cubes)", bag, bag.total());
+            return Ok(());
         },
-        None => Part::Part1, // Default to Part1 if no argument is provided
+        Query::LargestSubset(total) => inference::largest_subset(&games, total),
+        Query::BestSplit(total) => inference::best_split(&games, total, objective),
     };
 
-    let input_file_path = "./data.txt";
-    let bag = BagContent {
-        red: 12,
-        green: 13,
-        blue: 14,
-    };
-    let sum = read_from_file(input_file_path, &bag, part).expect("Failed to read input file");
-    println!("id_sum is {}", sum);
+    println!("Bag {} with {} cubes to spare", split.bag, split.spare);
+    let ids: Vec<String> = split.games.iter().map(u32::to_string).collect();
+    println!("{} possible games, id sum {}: {}", split.games.len(), split.games.iter().map(|&id| id as u64).sum::<u64>(), ids.join(", "));
+    Ok(())
 }
 
-fn read_from_file(file_path: &str, bag: &BagContent, part: Part) -> Result<u32> {
+fn read_games(file_path: &str) -> Result<Vec<Game>> {
     let path = Path::new(file_path);
-    let file = File::open(&path)?;
-    let reader = io::BufReader::new(file);
+    let file = advent_vault::open(path).with_context(|| format!("Failed to open {}", file_path))?;
+    parse_games(io::BufReader::new(file))
+}
 
+/// One game per line, blank lines are skipped.
+fn parse_games<R: BufRead>(reader: R) -> Result<Vec<Game>> {
     let mut games = Vec::new();
-
     for line in reader.lines() {
-        match line {
-            Ok(line) => {
-                let game = read_game_part_1(&line);
-                games.push(game);
-            },
-            Err(err) => return Err(anyhow!(err))
-        }
-    }
-    match part {
-        Part::Part1 => {
-            let sum = games.iter().filter(|game| game.is_possible(bag)).map(|game| game.id).sum();
-            return Ok(sum)
-        },
-        Part::Part2 => {
-            let sum = games.iter().map(|game| game.get_power()).sum();
-            return Ok(sum)
+        let line = line?;
+        if !line.trim().is_empty() {
+            games.push(Game::parse(&line)?);
         }
     }
+    Ok(games)
 }
 
-fn read_game_part_1(description: &str) -> Game {
-    let parts: Vec<&str> = description.split(':').collect();
-    let id_part = parts[0];
-    let sets_part = parts[1];
-
-    let id: u32 = id_part.trim().split_whitespace().last().unwrap().parse().unwrap(); // TODO: remove unwrap and do it properly.
-    let sets_descriptions = sets_part.split(';');
-    let mut game_sets = Vec::new();
-
-    for set_desc in sets_descriptions {
-        let mut game_set = GameSet::new();
-        let components = set_desc.split(',').collect::<Vec<&str>>();
-        for component in components {
-            let details = component.trim().split_whitespace().collect::<Vec<&str>>();
-            let quantity = details[0].parse::<u32>().unwrap();
-            let color = details[1].to_lowercase();
-            game_set.add(&color, quantity);
-        }
-        game_sets.push(game_set);
+fn read_from_file(file_path: &str, bag: &BagContent, part: Part) -> Result<u64> {
+    answer(&read_games(file_path)?, bag, part)
+}
+
+fn answer(games: &[Game], bag: &BagContent, part: Part) -> Result<u64> {
+    match part {
+        Part::Part1 => Ok(games.iter().filter(|game| game.is_possible(bag)).map(|game| game.id as u64).sum()),
+        Part::Part2 => games.iter().try_fold(0u64, |sum, game| {
+            game.get_power(bag)
+                .and_then(|power| sum.checked_add(power))
+                .ok_or_else(|| anyhow!("Game {}: the sum of powers doesn't fit in 64 bits", game.id))
+        }),
     }
-    Game { id: id, game_sets: game_sets }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use propcheck::Fuzzer;
 
     #[test]
-    fn test_read_game_part_1() {
-        let a = read_game_part_1("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green");
-        assert_eq!(a.id, 1);
-        assert_eq!(a.game_sets.len(), 3);
-        assert_eq!(a.game_sets[0].red, 4);
-        assert_eq!(a.game_sets[0].blue, 3);
-        assert_eq!(a.game_sets[0].green, 0);
-        assert_eq!(a.game_sets[1].red, 1);
-        assert_eq!(a.game_sets[1].blue, 6);
-        assert_eq!(a.game_sets[1].green, 2);
-        assert_eq!(a.game_sets[2].red, 0);
-        assert_eq!(a.game_sets[2].blue, 0);
-        assert_eq!(a.game_sets[2].green, 2);
-        assert_eq!(a.fewest_cubes().red, 4);
-        assert_eq!(a.fewest_cubes().green, 2);
-        assert_eq!(a.fewest_cubes().blue, 6);
-        assert_eq!(a.get_power(), 48);
-
-        let b = read_game_part_1("Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue");
-        assert_eq!(b.id, 2);
-        assert_eq!(b.game_sets.len(), 3);
-        assert_eq!(b.game_sets[0].red, 0);
-        assert_eq!(b.game_sets[0].blue, 1);
-        assert_eq!(b.game_sets[0].green, 2);
-        assert_eq!(b.game_sets[1].red, 1);
-        assert_eq!(b.game_sets[1].blue, 4);
-        assert_eq!(b.game_sets[1].green, 3);
-        assert_eq!(b.game_sets[2].red, 0);
-        assert_eq!(b.game_sets[2].blue, 1);
-        assert_eq!(b.game_sets[2].green, 1);
-        assert_eq!(b.fewest_cubes().red, 1);
-        assert_eq!(b.fewest_cubes().green, 3);
-        assert_eq!(b.fewest_cubes().blue, 4);
-        assert_eq!(b.get_power(), 12);
-
-        let c = read_game_part_1("Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red");
-        assert_eq!(c.id, 3);
-        assert_eq!(c.game_sets.len(), 3);
-        assert_eq!(c.game_sets[0].red, 20);
-        assert_eq!(c.game_sets[0].blue, 6);
-        assert_eq!(c.game_sets[0].green, 8);
-        assert_eq!(c.game_sets[1].red, 4);
-        assert_eq!(c.game_sets[1].blue, 5);
-        assert_eq!(c.game_sets[1].green, 13);
-        assert_eq!(c.game_sets[2].red, 1);
-        assert_eq!(c.game_sets[2].blue, 0);
-        assert_eq!(c.game_sets[2].green, 5);
-        assert_eq!(c.fewest_cubes().red, 20);
-        assert_eq!(c.fewest_cubes().green, 13);
-        assert_eq!(c.fewest_cubes().blue, 6);
-        assert_eq!(c.get_power(), 1560);
-        
-        let d = read_game_part_1("Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red");
-        assert_eq!(d.id, 4);
-        assert_eq!(d.game_sets.len(), 3);
-        assert_eq!(d.game_sets[0].red, 3);
-        assert_eq!(d.game_sets[0].blue, 6);
-        assert_eq!(d.game_sets[0].green, 1);
-        assert_eq!(d.game_sets[1].red, 6);
-        assert_eq!(d.game_sets[1].blue, 0);
-        assert_eq!(d.game_sets[1].green, 3);
-        assert_eq!(d.game_sets[2].red, 14);
-        assert_eq!(d.game_sets[2].blue, 15);
-        assert_eq!(d.game_sets[2].green, 3);
-        assert_eq!(d.fewest_cubes().red, 14);
-        assert_eq!(d.fewest_cubes().green, 3);
-        assert_eq!(d.fewest_cubes().blue, 15);
-        assert_eq!(d.get_power(), 630);
-
-        let e = read_game_part_1("Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green");
-        assert_eq!(e.id, 5);
-        assert_eq!(e.game_sets.len(), 2);
-        assert_eq!(e.game_sets[0].red, 6);
-        assert_eq!(e.game_sets[0].blue, 1);
-        assert_eq!(e.game_sets[0].green, 3);
-        assert_eq!(e.game_sets[1].red, 1);
-        assert_eq!(e.game_sets[1].blue, 2);
-        assert_eq!(e.game_sets[1].green, 2);
-        assert_eq!(e.fewest_cubes().red, 6);
-        assert_eq!(e.fewest_cubes().green, 3);
-        assert_eq!(e.fewest_cubes().blue, 2);
-        assert_eq!(e.get_power(), 36);
-    }
-
-     #[test]
     fn test_parts() {
-        let bag = BagContent {
-            red: 12,
-            green: 13,
-            blue: 14,
-        };
+        let bag = BagContent::standard();
         let part_1 = read_from_file("./part1_test_data.txt", &bag, Part::Part1);
         assert_eq!(part_1.unwrap(), 8);
         let part_2 = read_from_file("./part1_test_data.txt", &bag, Part::Part2);
         assert_eq!(part_2.unwrap(), 2286);
     }
+
+    #[test]
+    fn test_power_overflow_is_an_error() {
+        let games = parse_games("Game 1: 4294967295 red, 4294967295 green, 4294967295 blue\n".as_bytes()).unwrap();
+        let error = answer(&games, &BagContent::standard(), Part::Part2).unwrap_err();
+        assert_eq!(error.to_string(), "Game 1: the sum of powers doesn't fit in 64 bits");
+    }
+
+    #[test]
+    fn test_fuzz_games() {
+        let example = std::fs::read_to_string("./part1_test_data.txt").unwrap();
+        Fuzzer::new("games")
+            .seed_input(&example)
+            .seed_input("Game 4294967295: 4294967295 red, 1 red; 2 Green\n")
+            .run(|bytes| {
+                let games = parse_games(bytes)?;
+                answer(&games, &BagContent::standard(), Part::Part1)?;
+                answer(&games, &BagContent::standard(), Part::Part2)
+            });
+    }
+
+    #[test]
+    fn test_fuzz_inference() {
+        let example = std::fs::read_to_string("./part1_test_data.txt").unwrap();
+        Fuzzer::new("inference")
+            .seed_input(&example)
+            .seed_input("Game 1: 1 blue\nGame 4294967295: 4294967295 red\nGame 4294967295: 2 red\n")
+            .max_len(256)
+            .run(|bytes| {
+                let games = parse_games(bytes)?;
+                inference::minimum_bag(&games).total();
+                for total in [0, 5, 13, u32::MAX] {
+                    inference::largest_subset(&games, total);
+                    inference::best_split(&games, total, Objective::IdSum);
+                }
+                Ok::<_, anyhow::Error>(())
+            });
+    }
+
+    #[test]
+    fn test_fuzz_bags() {
+        Fuzzer::new("bags")
+            .seed_input("12 red, 13 green, 14 blue")
+            .seed_input("# spare bag\n1 red\n3 yellow, 4294967295 blue\n")
+            .run(|bytes| {
+                let bag = BagContent::parse(&String::from_utf8_lossy(bytes))?;
+                bag.total();
+                Ok::<_, anyhow::Error>(bag.to_string())
+            });
+    }
+
+    #[test]
+    fn test_bag_from_config() {
+        let config = Config::parse("[2023.day2]\nbag = \"1 red, 2 blue\"\n").unwrap();
+        let settings: Settings = config.day(2023, 2).unwrap();
+        assert_eq!(settings.bag, BagContent::new(&[("red", 1), ("blue", 2)]));
+        assert_eq!(settings.input_dir, None);
+
+        let config = Config::parse("[2023.day2]\nbag = \"12 red, 3 red\"\n").unwrap();
+        let err = config.day::<Settings>(2023, 2).unwrap_err();
+        assert!(err.message.contains("colour red is listed twice"), "{}", err);
+    }
 }
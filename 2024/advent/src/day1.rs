@@ -1,21 +1,23 @@
 use std::collections::HashMap;
+use std::io;
 use crate::errors::ParseError;
 use crate::utils::read_lines;
 use num::abs;
 
-pub fn process_1(file_content: Result<(Vec<i32>, Vec<i32>), ParseError>) -> Result<i32, ParseError> {
+/// Totals are `i64` as location IDs can be anywhere in the `i32` range.
+pub fn process_1(file_content: Result<(Vec<i32>, Vec<i32>), ParseError>) -> Result<i64, ParseError> {
     let (mut left, mut right) = file_content?;
     left.sort();
     right.sort();
     let mut total_distance = 0;
-    for (l, r) in left.iter().zip(right.iter()) {
-        total_distance += abs(l - r)
+    for (&l, &r) in left.iter().zip(right.iter()) {
+        total_distance += abs(l as i64 - r as i64)
     }
 
     Ok(total_distance)
 }
 
-pub fn process_2(file_content: Result<(Vec<i32>, Vec<i32>), ParseError>) -> Result<i32, ParseError> {
+pub fn process_2(file_content: Result<(Vec<i32>, Vec<i32>), ParseError>) -> Result<i64, ParseError> {
     let (left, right) = file_content?;
     let mut right_map = HashMap::new();
     for r in right.iter() {
@@ -23,8 +25,8 @@ pub fn process_2(file_content: Result<(Vec<i32>, Vec<i32>), ParseError>) -> Resu
     }
     let mut total_similarity = 0;
     for l in left.iter() {
-        if let Some(count) = right_map.get(l) {
-            total_similarity += l * count;
+        if let Some(&count) = right_map.get(l) {
+            total_similarity += *l as i64 * count;
         }
     }
 
@@ -32,27 +34,30 @@ pub fn process_2(file_content: Result<(Vec<i32>, Vec<i32>), ParseError>) -> Resu
 }
 
 pub fn read_and_parse_file(file_path: &str) -> Result<(Vec<i32>, Vec<i32>), ParseError> {
+    let lines = read_lines(file_path).map_err(|_| ParseError::ReadError)?;
+    parse_location_lists(lines)
+}
+
+/// Splits lines of two location IDs into the left and right lists.
+pub fn parse_location_lists<I>(lines: I) -> Result<(Vec<i32>, Vec<i32>), ParseError>
+where
+    I: Iterator<Item = io::Result<String>>,
+{
     let mut left = Vec::new();
     let mut right = Vec::new();
 
-    if let Ok(lines) = read_lines(file_path) {
-        for line in lines {
-            if let Ok(row) = line {
-                let numbers: Vec<i32> =
-                    row.split_whitespace().map(|s| s.parse().unwrap()).collect();
-                if numbers.len() == 2 {
-                    left.push(numbers[0]);
-                    right.push(numbers[1]);
-                } else {
-                    return Err(ParseError::InvalidInput(format!(
-                        "expected two numbers per line, but got: {}",
-                        row
-                    )));
-                }
-            }
+    for line in lines {
+        let row = line.map_err(|_| ParseError::ReadError)?;
+        let numbers = row.split_whitespace().map(str::parse).collect::<Result<Vec<i32>, _>>()?;
+        if numbers.len() == 2 {
+            left.push(numbers[0]);
+            right.push(numbers[1]);
+        } else {
+            return Err(ParseError::InvalidInput(format!(
+                "expected two numbers per line, but got: {}",
+                row
+            )));
         }
-    } else {
-        return Err(ParseError::ReadError);
     }
 
     Ok((left, right))
@@ -61,6 +66,8 @@ pub fn read_and_parse_file(file_path: &str) -> Result<(Vec<i32>, Vec<i32>), Pars
 #[cfg(test)]
 mod tests {
     use super::*;
+    use propcheck::Fuzzer;
+    use std::io::BufRead;
 
     #[test]
     fn test_read_and_parse_file() {
@@ -79,8 +86,8 @@ mod tests {
     fn test_process_1() {
         let file_content = read_and_parse_file("data/test_input_1.txt");
         match process_1(file_content) {
-            Ok(i32) => {
-                assert_eq!(i32, 11);
+            Ok(total) => {
+                assert_eq!(total, 11);
             }
             Err(e) => {
                 panic!("Error processing file: {}", e);
@@ -92,12 +99,25 @@ mod tests {
     fn test_process_2() {
         let file_content = read_and_parse_file("data/test_input_1.txt");
         match process_2(file_content) {
-            Ok(i32) => {
-                assert_eq!(i32, 31);
+            Ok(total) => {
+                assert_eq!(total, 31);
             }
             Err(e) => {
                 panic!("Error processing file: {}", e);
             }
         }
     }
+
+    #[test]
+    fn test_fuzz_location_lists() {
+        let example = std::fs::read_to_string("data/test_input_1.txt").unwrap();
+        Fuzzer::new("day1_location_lists")
+            .seed_input(&example)
+            .seed_input("3   4\n")
+            .run(|bytes| {
+                let lists = parse_location_lists(bytes.lines());
+                process_1(parse_location_lists(bytes.lines()))?;
+                process_2(lists)
+            });
+    }
 }
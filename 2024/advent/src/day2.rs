@@ -1,6 +1,6 @@
 use crate::utils::read_lines;
 use crate::errors::ParseError;
-use num::abs;
+use std::io;
 use std::ops::Sub;
 use std::fmt;
 use serde::{Serialize, Deserialize};
@@ -14,7 +14,7 @@ impl Sub for Level {
     type Output = i32;
 
     fn sub(self, other: Level) -> i32 {
-        self.value - other.value
+        self.value.saturating_sub(other.value)
     }
 }
 
@@ -25,132 +25,153 @@ impl fmt::Display for Level {
 }
 
 impl Level {
-    pub fn is_safe(&self, previous_level: &Level) -> bool {
-        self.value == previous_level.value || abs(self.value - previous_level.value) > 3
+    /// Whether going from `previous` to this level is a safe step in the given
+    /// direction: a change of 1 to 3.
+    pub fn is_safe_step(&self, previous_level: &Level, increasing: bool) -> bool {
+        let change = if increasing { *self - *previous_level } else { *previous_level - *self };
+        (1..=3).contains(&change)
     }
 }
 
 pub struct Report {
-    safe: Option<bool>, // to not need reprocessing
     levels: Vec<Level>,
 }
 
 impl Report {
-    fn is_safe(&mut self, ignore_level_id: Option<usize>) -> bool {
-        if let Some(safe) = self.safe {
-            return safe;
-        }
-
+    /// Whether the levels all increase or all decrease by 1 to 3. With the
+    /// dampener, one level may be removed to get there.
+    ///
+    /// Runs in linear time: for each direction it marks which prefixes and
+    /// suffixes are safe, then removing level `k` is safe when the prefix
+    /// before it and the suffix after it are, and its neighbours make a safe
+    /// step across the gap.
+    pub fn is_safe(&self, dampener: bool) -> bool {
         if self.levels.is_empty() {
             return false;
         }
+        [true, false].into_iter().any(|increasing| self.is_safe_in_direction(increasing, dampener))
+    }
 
-        let mut increasing = true;
-        let mut decreasing = true;
-        let mut not_same_or_not_close = true;
-
-        for i in 1..self.levels.len() {
-            let mut previous = i - 1; // start normally by comparing against previous
-            if let Some(ignore_level_id) = ignore_level_id {
-                if ignore_level_id > self.levels.len() {
-                    return false; // Can't ignore something that does not exist
-                }
-                if i == ignore_level_id {
-                    continue; // if this level is to be ignored, skip it
-                }
-                if i - 1 == ignore_level_id { // if previous level is to be ignored, compare to the one before
-                    if i == 1 { // but if the one before does not exist, skip this. This one will be compared in the next loop
-                        continue;
-                    }
-                    previous = i - 2; // If previous is to be ignored, compare to the one before
-                }
-            }
-            //
-            // the actual comparisons
-            //
-            if self.levels[i].is_safe(&self.levels[previous]) {
-                // return immediately if adjacent levels are the same or are not within 3
-                println!("{}: not safe levels {:?}", i, self.levels);
-                not_same_or_not_close = false;
-                break;
-            }
-            // Check if not decreasing or increasing
-            if self.levels[i].value > self.levels[previous].value {
-                println!("{}: not decreasing {:?}", i, self.levels);
-                decreasing = false;
-            }
-            if self.levels[i].value < self.levels[previous].value {
-                println!("{}: not increasing {:?}", i, self.levels);
-                increasing = false;
-            }
-        }
+    fn is_safe_in_direction(&self, increasing: bool, dampener: bool) -> bool {
+        let levels = &self.levels;
+        let n = levels.len();
+        let step = |from: usize, to: usize| levels[to].is_safe_step(&levels[from], increasing);
 
-        if (increasing || decreasing) && not_same_or_not_close {
-            self.safe = Some(true);
-            true
-        } else {
-            // if not increasing or decreasing, or if levels are the same or not within 3, it is not safe
-            if let Some(ignore_level_id) = ignore_level_id {
-                return self.is_safe(Some(ignore_level_id + 1));
-            }
-            self.safe = Some(false);
-            false
+        // safe_prefix[i]: levels[..i] are safe, safe_suffix[i]: levels[i..] are safe
+        let mut safe_prefix = vec![true; n + 1];
+        for i in 2..=n {
+            safe_prefix[i] = safe_prefix[i - 1] && step(i - 2, i - 1);
+        }
+        if safe_prefix[n] {
+            return true;
+        }
+        if !dampener {
+            return false;
         }
+        let mut safe_suffix = vec![true; n + 1];
+        for i in (0..n.saturating_sub(1)).rev() {
+            safe_suffix[i] = safe_suffix[i + 1] && step(i, i + 1);
+        }
+
+        (0..n).any(|k| {
+            let bridged = k == 0 || k == n - 1 || step(k - 1, k + 1);
+            safe_prefix[k] && safe_suffix[k + 1] && bridged
+        })
     }
 }
 
 impl fmt::Display for Report {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Report {{ is_safe: {:?}, levels: {:?} }}", self.safe, self.levels)
+        write!(f, "Report {{ levels: {:?} }}", self.levels)
     }
 }
 
 pub fn count_safe_levels(reports: Result<Vec<Report>, ParseError>, challenge_number: usize) -> Result<u32, ParseError> {
-    let ignore_level = match challenge_number {
-        1 => None,
-        2 => Some(0),
-        _ => None,
-    };
+    let dampener = challenge_number == 2;
     let reports = reports?;
-    let mut safe_count = 0;
-    for mut report in reports {
-        println!("Checking report: {}", report);
-        if report.is_safe(ignore_level) {
-            println!("Report is safe \n");
-            safe_count += 1;
-        } else {
-            println!("Report is not safe \n");
-        }
-    }
-    Ok(safe_count)
+    Ok(reports.iter().filter(|report| report.is_safe(dampener)).count() as u32)
 }
 
 pub fn read_and_parse_file(file_path: &str) -> Result<Vec<Report>, ParseError> {
-    if let Ok(lines) = read_lines(file_path) {
-        let mut reports = Vec::new();
-        for line in lines {
-            let mut levels = Vec::new();
-            if let Ok(row) = line {
-                for value in row.split_whitespace() {
-                    let value: i32 = value.parse().map_err(ParseError::from)?;
-                    let level = Level { value };
-                    levels.push(level);
-                }
-            }
-            reports.push(Report {
-                safe: None,
-                levels
-            });
+    let lines = read_lines(file_path).map_err(|_| ParseError::ReadError)?;
+    parse_reports(lines)
+}
+
+/// Reads one report of whitespace-separated levels per line.
+pub fn parse_reports<I>(lines: I) -> Result<Vec<Report>, ParseError>
+where
+    I: Iterator<Item = io::Result<String>>,
+{
+    let mut reports = Vec::new();
+    for line in lines {
+        let row = line.map_err(|_| ParseError::ReadError)?;
+        let mut levels = Vec::new();
+        for value in row.split_whitespace() {
+            let value: i32 = value.parse().map_err(ParseError::from)?;
+            let level = Level { value };
+            levels.push(level);
         }
-        Ok(reports)
-    } else {
-        return Err(ParseError::ReadError);
+        reports.push(Report { levels });
     }
+    Ok(reports)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use propcheck::{bools, ints, vecs, Config, Fuzzer};
+    use std::io::BufRead;
+
+    fn report(values: &[i32]) -> Report {
+        Report { levels: values.iter().map(|&value| Level { value }).collect() }
+    }
+
+    /// The obvious check: try the report as is, then without each level in turn.
+    fn is_safe_brute_force(values: &[i32], dampener: bool) -> bool {
+        let strictly_safe = |values: &[i32]| {
+            let changes: Vec<i32> = values.windows(2).map(|pair| pair[1] - pair[0]).collect();
+            !values.is_empty()
+                && (changes.iter().all(|change| (1..=3).contains(change))
+                    || changes.iter().all(|change| (-3..=-1).contains(change)))
+        };
+        strictly_safe(values)
+            || dampener
+                && (0..values.len()).any(|skip| {
+                    let remaining: Vec<i32> = values.iter().enumerate().filter(|&(i, _)| i != skip).map(|(_, &v)| v).collect();
+                    !remaining.is_empty() && strictly_safe(&remaining)
+                })
+    }
+
+    #[test]
+    fn test_dampener_edge_cases() {
+        assert!(report(&[1, 2, 3]).is_safe(false));
+        assert!(!report(&[]).is_safe(true));
+        assert!(report(&[5]).is_safe(false));
+        // The first or the last level is the bad one
+        assert!(report(&[9, 1, 2, 3]).is_safe(true));
+        assert!(report(&[1, 2, 3, 9]).is_safe(true));
+        // Dropping the second level fixes the direction
+        assert!(report(&[5, 9, 4, 3]).is_safe(true));
+        assert!(!report(&[1, 5, 9, 13]).is_safe(true));
+        // A removal must not leave a gap that is too wide
+        assert!(!report(&[1, 2, 3, 7, 8]).is_safe(true));
+        assert!(report(&[1, 2, 2, 3]).is_safe(true));
+    }
+
+    #[test]
+    fn test_is_safe_matches_brute_force() {
+        // Levels as a start plus small changes, so that safe and almost-safe reports are common
+        let strategy = (ints(0, 20), vecs(ints(-4, 4), 0, 8), bools());
+        let levels = |(start, changes, _): &(i64, Vec<i64>, bool)| -> Vec<i32> {
+            changes.iter().scan(*start, |level, change| { *level += change; Some(*level as i32) }).collect()
+        };
+        Config::default().cases(20_000).check_against(
+            "is_safe matches brute force",
+            &strategy,
+            |input| report(&levels(input)).is_safe(input.2),
+            |input| is_safe_brute_force(&levels(input), input.2),
+        );
+    }
 
     #[test]
     fn test_read_and_parse_file() {
@@ -165,4 +186,13 @@ mod tests {
         let safe_count = count_safe_levels(reports, 2).unwrap();
         assert_eq!(safe_count, 4);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_fuzz_reports() {
+        let example = std::fs::read_to_string("data/test_input_2.txt").unwrap();
+        Fuzzer::new("day2_reports")
+            .seed_input(&example)
+            .seed_input("1 3 6 7 9\n")
+            .run(|bytes| count_safe_levels(parse_reports(bytes.lines()), 2));
+    }
+}
@@ -1,5 +1,7 @@
 use thiserror::Error;
+use std::io;
 use std::num::ParseIntError;
+use std::path::PathBuf;
 
 #[derive(Debug, Error)]
 pub enum ParseError {
@@ -9,4 +11,20 @@ pub enum ParseError {
     ReadError,
     #[error("Failed to parse integer: {0}")]
     ParseIntError(#[from] ParseIntError),
-}
\ No newline at end of file
+}
+#[derive(Debug, Error)]
+pub enum GenerateError {
+    #[error("No generator for {year} day {day}")]
+    NoGenerator { year: u32, day: u32 },
+    #[error("Invalid option: {0}")]
+    InvalidOption(String),
+}
+#[derive(Debug, Error)]
+pub enum ExampleError {
+    #[error("Failed to access {}: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+    #[error("No example found in {}", .0.display())]
+    NoExamples(PathBuf),
+    #[error("Invalid answers file {}: {reason}", path.display())]
+    InvalidAnswers { path: PathBuf, reason: String },
+}
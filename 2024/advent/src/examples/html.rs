@@ -0,0 +1,208 @@
+//! Just enough of an HTML reader for saved puzzle pages: headings,
+//! paragraphs, list items and `<pre>` blocks, with `<code>` and `<em>` marks
+//! on the text. Everything outside those is skipped.
+
+use super::{push_span, trim_spans, Block, Span};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Heading,
+    Paragraph,
+    Item,
+    Pre,
+}
+
+#[derive(Debug, Default)]
+struct Reader {
+    blocks: Vec<Block>,
+    open: Option<Kind>,
+    spans: Vec<Span>,
+    pre: String,
+    code: usize,
+    emphasis: usize,
+}
+
+pub fn parse(html: &str) -> Vec<Block> {
+    let mut reader = Reader::default();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        reader.text(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+            continue;
+        }
+        let end = tag_end(rest);
+        let (closing, name) = tag_name(&rest[1..end]);
+        rest = &rest[(end + 1).min(rest.len())..];
+        if !closing && (name == "script" || name == "style") {
+            let close = format!("</{}", name);
+            rest = rest.to_ascii_lowercase().find(&close).map_or("", |end| &rest[end..]);
+            continue;
+        }
+        reader.tag(&name, closing);
+    }
+    reader.text(rest);
+    reader.close();
+    reader.blocks
+}
+
+/// Index of the `>` closing the tag at the start of `html`, quoted attribute
+/// values may contain one.
+fn tag_end(html: &str) -> usize {
+    let mut quote = None;
+    for (index, byte) in html.bytes().enumerate().skip(1) {
+        match (quote, byte) {
+            (None, b'>') => return index,
+            (None, b'"' | b'\'') => quote = Some(byte),
+            (Some(open), _) if open == byte => quote = None,
+            _ => {}
+        }
+    }
+    html.len()
+}
+
+fn tag_name(tag: &str) -> (bool, String) {
+    let tag = tag.trim_start();
+    let (closing, tag) = match tag.strip_prefix('/') {
+        Some(tag) => (true, tag),
+        None => (false, tag),
+    };
+    let name = tag.chars().take_while(char::is_ascii_alphanumeric).collect::<String>();
+    (closing, name.to_ascii_lowercase())
+}
+
+impl Reader {
+    fn text(&mut self, raw: &str) {
+        match self.open {
+            None => {}
+            Some(Kind::Pre) => self.pre.push_str(&decode(raw)),
+            Some(_) => {
+                let mut text = String::with_capacity(raw.len());
+                for ch in decode(raw).chars() {
+                    match ch.is_whitespace() {
+                        true if text.ends_with(' ') => {}
+                        true => text.push(' '),
+                        false => text.push(ch),
+                    }
+                }
+                push_span(&mut self.spans, text, self.code > 0, self.emphasis > 0);
+            }
+        }
+    }
+
+    fn tag(&mut self, name: &str, closing: bool) {
+        let kind = match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => Kind::Heading,
+            "p" => Kind::Paragraph,
+            "li" => Kind::Item,
+            "pre" => Kind::Pre,
+            "code" | "em" => {
+                let depth = if name == "code" { &mut self.code } else { &mut self.emphasis };
+                *depth = if closing { depth.saturating_sub(1) } else { *depth + 1 };
+                return;
+            }
+            _ => return,
+        };
+        if closing {
+            if self.open == Some(kind) {
+                self.close();
+            }
+        } else {
+            self.close();
+            self.open = Some(kind);
+        }
+    }
+
+    fn close(&mut self) {
+        let spans = std::mem::take(&mut self.spans);
+        let block = match self.open.take() {
+            None => return,
+            Some(Kind::Heading) => Block::Heading(spans.iter().map(|span| span.text.as_str()).collect::<String>().trim().to_string()),
+            Some(Kind::Paragraph) => Block::Paragraph(trim_spans(spans)),
+            Some(Kind::Item) => Block::Item(trim_spans(spans)),
+            Some(Kind::Pre) => {
+                let pre = std::mem::take(&mut self.pre);
+                Block::Code(pre.strip_prefix('\n').unwrap_or(&pre).to_string())
+            }
+        };
+        self.blocks.push(block);
+    }
+}
+
+/// Replaces character references such as `&lt;` and `&#62;`, unknown ones are
+/// kept as they are.
+fn decode(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+        let entity = rest.find(';').filter(|&end| end <= 10).and_then(|end| Some((end, entity_char(&rest[..end])?)));
+        match entity {
+            Some((end, ch)) => {
+                decoded.push(ch);
+                rest = &rest[end + 1..];
+            }
+            None => decoded.push('&'),
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+fn entity_char(name: &str) -> Option<char> {
+    match name {
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "amp" => Some('&'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        _ => {
+            let number = name.strip_prefix('#')?;
+            let code = match number.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => number.parse().ok()?,
+            };
+            char::from_u32(code)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode("a &lt;b&gt; &amp;&#65;&#x42; &unknown; & c"), "a <b> &AB &unknown; & c");
+    }
+
+    #[test]
+    fn test_parse_blocks() {
+        let html = "<h2 id=\"part2\">--- Part Two ---</h2><!-- <p>hidden</p> -->\
+                    <p title=\"a > b\">See <code>x</code> and <em>this</em>.</p>\
+                    <pre><code>1 &lt; 2\n  <em>3</em>\n</code></pre><script>let p = '<p>';</script>\
+                    <ul><li><code>abc</code>: <code><em>5</em></code></li></ul>";
+        assert_eq!(
+            parse(html),
+            vec![
+                Block::Heading("--- Part Two ---".to_string()),
+                Block::Paragraph(vec![
+                    Span { text: "See ".to_string(), code: false, emphasis: false },
+                    Span { text: "x".to_string(), code: true, emphasis: false },
+                    Span { text: " and ".to_string(), code: false, emphasis: false },
+                    Span { text: "this".to_string(), code: false, emphasis: true },
+                    Span { text: ".".to_string(), code: false, emphasis: false },
+                ]),
+                Block::Code("1 < 2\n  3\n".to_string()),
+                Block::Item(vec![
+                    Span { text: "abc".to_string(), code: true, emphasis: false },
+                    Span { text: ": ".to_string(), code: false, emphasis: false },
+                    Span { text: "5".to_string(), code: true, emphasis: true },
+                ]),
+            ]
+        );
+    }
+}
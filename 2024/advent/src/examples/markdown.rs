@@ -0,0 +1,144 @@
+//! Reads puzzle statements pasted into markdown, like the 2022 `Readme.md`
+//! files: `#` headings, paragraphs, list items and fenced code blocks, with
+//! backtick code and `*`/`**` emphasis inside the text. Fenced blocks tagged
+//! with a language other than `text` hold source code and are skipped.
+//! Lines like `` `input`: answer `` are read as list items too, statements
+//! pasted as plain text lose the list markers of such examples.
+
+use super::{push_span, trim_spans, Block, Span};
+
+pub fn parse(markdown: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut text: Option<(bool, String)> = None;
+    let mut lines = markdown.lines();
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+        if let Some(language) = line.strip_prefix("```") {
+            flush(&mut blocks, &mut text);
+            let mut code = String::new();
+            for line in lines.by_ref() {
+                if line.trim_start().starts_with("```") {
+                    break;
+                }
+                code.push_str(line);
+                code.push('\n');
+            }
+            if matches!(language.trim(), "" | "text" | "txt" | "plain") {
+                blocks.push(Block::Code(code));
+            }
+        } else if line.is_empty() {
+            flush(&mut blocks, &mut text);
+        } else if line.starts_with('#') {
+            flush(&mut blocks, &mut text);
+            blocks.push(Block::Heading(line.trim_matches('#').trim().to_string()));
+        } else if let Some(item) = list_item(line).or_else(|| example_line(line)) {
+            flush(&mut blocks, &mut text);
+            text = Some((true, item.to_string()));
+        } else if let Some((_, paragraph)) = text.as_mut() {
+            paragraph.push(' ');
+            paragraph.push_str(line);
+        } else {
+            text = Some((false, line.to_string()));
+        }
+    }
+    flush(&mut blocks, &mut text);
+    blocks
+}
+
+fn flush(blocks: &mut Vec<Block>, text: &mut Option<(bool, String)>) {
+    match text.take() {
+        Some((true, item)) => blocks.push(Block::Item(inline(&item))),
+        Some((false, paragraph)) => blocks.push(Block::Paragraph(inline(&paragraph))),
+        None => {}
+    }
+}
+
+/// The text of a `-`, `*`, `+` or `1.` list item.
+fn list_item(line: &str) -> Option<&str> {
+    let marker = line.find(' ')?;
+    let numbered = marker > 1 && line[..marker - 1].bytes().all(|byte| byte.is_ascii_digit()) && line[..marker].ends_with('.');
+    (matches!(&line[..marker], "-" | "*" | "+") || numbered).then(|| line[marker..].trim_start())
+}
+
+/// A line starting with inline code followed by a colon.
+fn example_line(line: &str) -> Option<&str> {
+    let code = line.strip_prefix('`').filter(|code| !code.starts_with('`'))?;
+    let end = code.find('`')?;
+    code[end + 1..].starts_with(':').then_some(line)
+}
+
+fn inline(text: &str) -> Vec<Span> {
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut emphasis = false;
+    let mut rest = text;
+    while let Some(ch) = rest.chars().next() {
+        if ch == '`' || ch == '*' {
+            let run = rest.len() - rest.trim_start_matches(ch).len();
+            let (delimiter, after) = rest.split_at(run);
+            if ch == '`' {
+                if let Some(end) = after.find(delimiter) {
+                    push_span(&mut spans, std::mem::take(&mut plain), false, emphasis);
+                    push_span(&mut spans, after[..end].trim().to_string(), true, emphasis);
+                    rest = &after[end + run..];
+                    continue;
+                }
+            } else if emphasis || after.starts_with(|next: char| !next.is_whitespace()) {
+                push_span(&mut spans, std::mem::take(&mut plain), false, emphasis);
+                emphasis = !emphasis;
+                rest = after;
+                continue;
+            }
+            plain.push_str(delimiter);
+            rest = after;
+        } else {
+            plain.push(ch);
+            rest = &rest[ch.len_utf8()..];
+        }
+    }
+    push_span(&mut spans, plain, false, emphasis);
+    trim_spans(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(text: &str, code: bool, emphasis: bool) -> Span {
+        Span { text: text.to_string(), code, emphasis }
+    }
+
+    #[test]
+    fn test_inline() {
+        assert_eq!(
+            inline("total is **`11`**, or *31* and `a*b` 2 * 3"),
+            vec![
+                span("total is ", false, false),
+                span("11", true, true),
+                span(", or ", false, false),
+                span("31", false, true),
+                span(" and ", false, false),
+                span("a*b", true, false),
+                span(" 2 * 3", false, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_blocks() {
+        let markdown = "### Day 1\nFor example,\nthis list:\n\n```\n  1 2\n3 4\n```\n```rust\nfn main() {}\n```\n\
+                        - `abc`: *5*\n2. two\n`def`: 6\n\n`ghi` 7\n";
+        assert_eq!(
+            parse(markdown),
+            vec![
+                Block::Heading("Day 1".to_string()),
+                Block::Paragraph(vec![span("For example, this list:", false, false)]),
+                Block::Code("  1 2\n3 4\n".to_string()),
+                Block::Item(vec![span("abc", true, false), span(": ", false, false), span("5", false, true)]),
+                Block::Item(vec![span("two", false, false)]),
+                Block::Item(vec![span("def", true, false), span(": 6", false, false)]),
+                Block::Paragraph(vec![span("ghi", true, false), span(" 7", false, false)]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,390 @@
+mod html;
+mod markdown;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::ExampleError;
+
+/// Name of the file listing the expected answers next to the example inputs.
+pub const ANSWERS_FILE: &str = "answers.toml";
+
+/// A run of text with the same formatting.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub text: String,
+    pub code: bool,
+    pub emphasis: bool,
+}
+
+/// The parts of a puzzle statement the extraction looks at.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Block {
+    Heading(String),
+    Paragraph(Vec<Span>),
+    Item(Vec<Span>),
+    Code(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Html,
+    Markdown,
+}
+
+impl Format {
+    /// HTML for `.html` and `.htm` files, markdown otherwise.
+    pub fn of(path: &Path) -> Format {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("html") || extension.eq_ignore_ascii_case("htm") => {
+                Format::Html
+            }
+            _ => Format::Markdown,
+        }
+    }
+}
+
+/// An example input with the answers the statement gives for it, if any.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Example {
+    pub input: String,
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+impl Example {
+    fn answer_mut(&mut self, part: usize) -> &mut Option<String> {
+        if part == 1 { &mut self.part1 } else { &mut self.part2 }
+    }
+}
+
+/// One entry of `answers.toml`, `input` is relative to the file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExampleAnswer {
+    pub day: u32,
+    pub input: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub part1: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub part2: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct AnswersFile {
+    #[serde(default)]
+    example: Vec<ExampleAnswer>,
+}
+
+/// Finds the examples of a puzzle statement laid out like the ones on
+/// adventofcode.com. The statement is split in two parts at the "Part Two"
+/// heading. In each part:
+///
+/// - the example input is the first code block, or paragraph of nothing but
+///   inline code, right after a paragraph saying "for example", part two
+///   reuses the one from part one if it has none,
+/// - its answer is the last value outside list items,
+/// - list items starting with inline code and ending with a value are extra
+///   examples, like the datastreams of 2022 day 6.
+///
+/// Values are emphasised code, or emphasised numbers in markdown. Statements
+/// pasted as plain text have no emphasis, there a value is a number or a word
+/// in capitals stated after a phrase like "is", "the value" or "after
+/// character", and the second part may start at a paragraph saying "next
+/// part" instead of a heading.
+pub fn extract(document: &str, format: Format) -> Vec<Example> {
+    let blocks = match format {
+        Format::Html => html::parse(document),
+        Format::Markdown => markdown::parse(document),
+    };
+    let emphasised = blocks.iter().any(|block| match block {
+        Block::Paragraph(spans) | Block::Item(spans) => spans.iter().any(|span| span.emphasis),
+        _ => false,
+    });
+    let value = if emphasised { last_value } else { stated_value };
+    let part_two = blocks.iter().position(|block| match block {
+        Block::Heading(title) => title.to_ascii_lowercase().contains("part two"),
+        Block::Paragraph(spans) if !emphasised => plain_text(spans).to_ascii_lowercase().contains("next part"),
+        _ => false,
+    });
+    let (first, second) = blocks.split_at(part_two.unwrap_or(blocks.len()));
+
+    let mut examples: Vec<Example> = Vec::new();
+    let mut first_input = None;
+    for (part, blocks) in [(1, first), (2, second)] {
+        let mut input = None;
+        let mut answer = None;
+        let mut inline = Vec::new();
+        let mut introduced = false;
+        for block in blocks {
+            match block {
+                Block::Paragraph(spans) => {
+                    if let [code] = spans.as_slice() {
+                        if code.code && introduced && input.is_none() {
+                            input = Some(normalize(&code.text));
+                        }
+                    }
+                    introduced = plain_text(spans).to_ascii_lowercase().contains("for example");
+                    answer = value(spans).or(answer);
+                }
+                Block::Code(code) => {
+                    if introduced && input.is_none() {
+                        input = Some(normalize(code));
+                    }
+                    introduced = false;
+                }
+                Block::Item(spans) => {
+                    let code = spans.first().filter(|span| span.code && !span.emphasis);
+                    if let (Some(code), Some(value)) = (code, value(&spans[1..])) {
+                        inline.push((normalize(&code.text), value));
+                    }
+                    introduced = false;
+                }
+                Block::Heading(_) => introduced = false,
+            }
+        }
+        if part == 1 {
+            first_input = input.clone();
+        }
+        if let Some(input) = input.or_else(|| first_input.clone()) {
+            record(&mut examples, input, part, answer);
+        }
+        for (input, value) in inline {
+            record(&mut examples, input, part, Some(value));
+        }
+    }
+    examples
+}
+
+/// Adds `answer` to the example with `input`, adding the example if it's new.
+fn record(examples: &mut Vec<Example>, input: String, part: usize, answer: Option<String>) {
+    let index = match examples.iter().position(|example| example.input == input) {
+        Some(index) => index,
+        None => {
+            examples.push(Example { input, ..Example::default() });
+            examples.len() - 1
+        }
+    };
+    if answer.is_some() {
+        *examples[index].answer_mut(part) = answer;
+    }
+}
+
+fn last_value(spans: &[Span]) -> Option<String> {
+    spans
+        .iter()
+        .rev()
+        .filter(|span| span.emphasis)
+        .map(|span| (span.code, span.text.trim()))
+        .find(|&(code, text)| !text.is_empty() && (code || text.parse::<i64>().is_ok()))
+        .map(|(_, text)| text.to_string())
+}
+
+/// The last number or capitalised word following one of the phrases that
+/// state an answer in plain text, e.g. "the sum of these is 157" or "first
+/// marker after character 5".
+fn stated_value(spans: &[Span]) -> Option<String> {
+    const BEFORE_ANSWER: [&str; 6] = ["is", "of", "are", "value", "character", "message"];
+
+    let text = plain_text(spans);
+    let words: Vec<&str> = text.split_whitespace().map(|word| word.trim_matches(|ch: char| ",.;:!?()".contains(ch))).collect();
+    words
+        .windows(2)
+        .rev()
+        .find(|pair| {
+            let answer = pair[1];
+            let capitals = answer.len() > 1 && answer.bytes().all(|byte| byte.is_ascii_uppercase());
+            BEFORE_ANSWER.contains(&pair[0].to_ascii_lowercase().as_str()) && (capitals || answer.parse::<i64>().is_ok())
+        })
+        .map(|pair| pair[1].to_string())
+}
+
+/// The text outside inline code.
+fn plain_text(spans: &[Span]) -> String {
+    spans.iter().filter(|span| !span.code).map(|span| span.text.as_str()).collect::<Vec<_>>().join(" ")
+}
+
+/// Input text ending with exactly one newline.
+fn normalize(input: &str) -> String {
+    format!("{}\n", input.trim_end_matches('\n'))
+}
+
+/// Appends `text` to the last span if it has the same formatting.
+fn push_span(spans: &mut Vec<Span>, text: String, code: bool, emphasis: bool) {
+    if text.is_empty() {
+        return;
+    }
+    match spans.last_mut() {
+        Some(last) if !code && !last.code && last.emphasis == emphasis => last.text.push_str(&text),
+        _ => spans.push(Span { text, code, emphasis }),
+    }
+}
+
+/// Drops the whitespace around a paragraph.
+fn trim_spans(mut spans: Vec<Span>) -> Vec<Span> {
+    if let Some(first) = spans.first_mut() {
+        first.text = first.text.trim_start().to_string();
+    }
+    if let Some(last) = spans.last_mut() {
+        last.text = last.text.trim_end().to_string();
+    }
+    spans.retain(|span| !span.text.is_empty());
+    spans
+}
+
+pub fn read_answers(path: &Path) -> Result<Vec<ExampleAnswer>, ExampleError> {
+    let text = fs::read_to_string(path).map_err(|source| ExampleError::Io { path: path.to_path_buf(), source })?;
+    let file: AnswersFile = toml::from_str(&text)
+        .map_err(|err| ExampleError::InvalidAnswers { path: path.to_path_buf(), reason: err.to_string() })?;
+    Ok(file.example)
+}
+
+/// Writes the inputs of `examples` to `<name>.txt`, `<name>_2.txt` and so on
+/// in `dir` and their answers to `answers.toml` there, replacing the entries
+/// for the same files. Returns the paths written.
+pub fn write_fixtures(dir: &Path, day: u32, name: &str, examples: &[Example]) -> Result<Vec<PathBuf>, ExampleError> {
+    let io_error = |path: &Path| {
+        let path = path.to_path_buf();
+        move |source| ExampleError::Io { path, source }
+    };
+    fs::create_dir_all(dir).map_err(io_error(dir))?;
+    let answers_path = dir.join(ANSWERS_FILE);
+    let mut answers = if answers_path.exists() { read_answers(&answers_path)? } else { Vec::new() };
+
+    let mut written = Vec::new();
+    for (index, example) in examples.iter().enumerate() {
+        let file = match index {
+            0 => format!("{}.txt", name),
+            _ => format!("{}_{}.txt", name, index + 1),
+        };
+        let path = dir.join(&file);
+        fs::write(&path, &example.input).map_err(io_error(&path))?;
+        answers.retain(|answer| answer.input != file);
+        answers.push(ExampleAnswer { day, input: file, part1: example.part1.clone(), part2: example.part2.clone() });
+        written.push(path);
+    }
+
+    let text = toml::to_string(&AnswersFile { example: answers })
+        .map_err(|err| ExampleError::InvalidAnswers { path: answers_path.clone(), reason: err.to_string() })?;
+    fs::write(&answers_path, text).map_err(io_error(&answers_path))?;
+    written.push(answers_path);
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn example(input: &str, part1: Option<&str>, part2: Option<&str>) -> Example {
+        Example { input: input.to_string(), part1: part1.map(str::to_string), part2: part2.map(str::to_string) }
+    }
+
+    #[test]
+    fn test_extract_html() {
+        let html = r#"<article class="day-desc"><h2>--- Day 6: Tuning Trouble ---</h2>
+            <p>To be able to communicate, the device needs to lock on to their signal.</p>
+            <p>For example, suppose you receive the following datastream buffer:</p>
+            <pre><code>mjqjpqmgbljsphdztnvjfqwrcgsmlb</code></pre>
+            <p>In this case, your subroutine should report the value <code><em>7</em></code>, because the
+            first marker is complete after <em>7 characters</em> have been processed.</p>
+            <p>Here are a few more examples:</p>
+            <ul>
+            <li><code>bvwbjplbgvbhsrlpgdmjqwftvncz</code>: first marker after character <code><em>5</em></code></li>
+            <li><code>nppdvjthqldpwncqszvftbrmjlhg</code>: first marker after character <code><em>6</em></code></li>
+            </ul>
+            </article>
+            <article class="day-desc"><h2 id="part2">--- Part Two ---</h2>
+            <p>A start-of-message marker is just like a start-of-packet marker, except it consists of
+            <em>14 distinct characters</em> rather than 4.</p>
+            <ul>
+            <li><code>mjqjpqmgbljsphdztnvjfqwrcgsmlb</code>: first marker after character <code><em>19</em></code></li>
+            <li><code>bvwbjplbgvbhsrlpgdmjqwftvncz</code>: first marker after character <code><em>23</em></code></li>
+            <li><code>zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw</code>: first marker after character <code><em>26</em></code></li>
+            </ul>
+            </article>"#;
+        assert_eq!(
+            extract(html, Format::Html),
+            vec![
+                example("mjqjpqmgbljsphdztnvjfqwrcgsmlb\n", Some("7"), Some("19")),
+                example("bvwbjplbgvbhsrlpgdmjqwftvncz\n", Some("5"), Some("23")),
+                example("nppdvjthqldpwncqszvftbrmjlhg\n", Some("6"), None),
+                example("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw\n", None, Some("26")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_markdown() {
+        let markdown = "## Day 1: Historian Hysteria\n\nFor example:\n\n```\n3   4\n4   3\n2   5\n```\n\n\
+                        In the example above, the total distance is **`11`**.\n\n## Part Two\n\n\
+                        So, for these example lists, the similarity score at the end of this process is *31*.\n";
+        assert_eq!(extract(markdown, Format::Markdown), vec![example("3   4\n4   3\n2   5\n", Some("11"), Some("31"))]);
+    }
+
+    #[test]
+    fn test_extract_needs_an_introduced_block() {
+        let markdown = "Some code:\n\n```\n1 2\n```\n\nThe answer is **3**.\n";
+        assert_eq!(extract(markdown, Format::Markdown), vec![]);
+    }
+
+    #[test]
+    fn test_extract_2022_readmes() {
+        // The Readmes of days 4 and 5 stop before part two
+        let answers = [
+            (1, Some("24000"), Some("45000")),
+            (2, Some("15"), Some("12")),
+            (3, Some("157"), Some("70")),
+            (4, Some("2"), None),
+            (5, Some("CMZ"), None),
+        ];
+        for (day, part1, part2) in answers {
+            let dir = format!("../../2022/day{:02}", day);
+            let readme = fs::read_to_string(format!("{}/Readme.md", dir)).unwrap();
+            let expected = fs::read_to_string(format!("{}/test_data/example_data.txt", dir)).unwrap();
+            let examples = extract(&readme, Format::Markdown);
+            assert_eq!(examples, vec![example(&normalize(&expected), part1, part2)], "2022 day {}", day);
+        }
+
+        // Day 6 gives its examples as inline code in plain text
+        let readme = fs::read_to_string("../../2022/day06/Readme.md").unwrap();
+        assert_eq!(
+            extract(&readme, Format::Markdown),
+            vec![
+                example("mjqjpqmgbljsphdztnvjfqwrcgsmlb\n", Some("7"), Some("19")),
+                example("bvwbjplbgvbhsrlpgdmjqwftvncz\n", Some("5"), Some("23")),
+                example("nppdvjthqldpwncqszvftbrmjlhg\n", Some("6"), Some("23")),
+                example("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg\n", Some("10"), Some("29")),
+                example("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw\n", Some("11"), Some("26")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stated_value() {
+        let spans = |text: &str| vec![Span { text: text.to_string(), code: false, emphasis: false }];
+        assert_eq!(stated_value(&spans("the top crates are C, so the message is CMZ.")), Some("CMZ".to_string()));
+        assert_eq!(stated_value(&spans("report the value 7, because it is complete after 7 characters")), Some("7".to_string()));
+        assert_eq!(stated_value(&spans("Your puzzle answer was 71502.")), None);
+    }
+
+    #[test]
+    fn test_write_fixtures() {
+        let dir = env::temp_dir().join(format!("advent-examples-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let examples = [example("1 2\n", Some("3"), None), example("4 5\n", None, Some("9"))];
+        write_fixtures(&dir, 7, "test_input_7", &examples).unwrap();
+        write_fixtures(&dir, 7, "test_input_7", &examples[..1]).unwrap();
+
+        assert_eq!(fs::read_to_string(dir.join("test_input_7_2.txt")).unwrap(), "4 5\n");
+        assert_eq!(
+            read_answers(&dir.join(ANSWERS_FILE)).unwrap(),
+            vec![
+                ExampleAnswer { day: 7, input: "test_input_7_2.txt".to_string(), part1: None, part2: Some("9".to_string()) },
+                ExampleAnswer { day: 7, input: "test_input_7.txt".to_string(), part1: Some("3".to_string()), part2: None },
+            ]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,79 @@
+mod year2021;
+mod year2022;
+mod year2023;
+mod year2024;
+
+use crate::errors::GenerateError;
+use advent_rng::Rng;
+
+/// Settings shared by all generators.
+#[derive(Debug, Clone)]
+pub struct GenOptions {
+    /// Number of records to write: lines for most days, elves for 2022 day 1,
+    /// groups of three rucksacks for 2022 day 3, moves for 2022 day 5 and
+    /// characters for 2022 day 6.
+    pub size: usize,
+    pub seed: u64,
+    /// Share of unsafe reports for 2024 day 2, from 0 to 1.
+    pub unsafe_ratio: f64,
+}
+
+impl Default for GenOptions {
+    fn default() -> Self {
+        GenOptions { size: 100, seed: 0, unsafe_ratio: 0.5 }
+    }
+}
+
+/// A valid puzzle input for the given day, the same for the same options.
+pub fn generate(year: u32, day: u32, options: &GenOptions) -> Result<String, GenerateError> {
+    let mut rng = Rng::new(options.seed);
+    let size = options.size;
+    match (year, day) {
+        (2021, 1) => Ok(year2021::depths(&mut rng, size)),
+        (2021, 2) => Ok(year2021::commands(&mut rng, size)),
+        (2021, 3) => year2021::diagnostics(&mut rng, size),
+        (2022, 1) => Ok(year2022::calories(&mut rng, size)),
+        (2022, 2) => Ok(year2022::strategy_guide(&mut rng, size)),
+        (2022, 3) => Ok(year2022::rucksacks(&mut rng, size)),
+        (2022, 4) => Ok(year2022::assignments(&mut rng, size)),
+        (2022, 5) => Ok(year2022::crates(&mut rng, size)),
+        (2022, 6) => year2022::datastream(&mut rng, size),
+        (2023, 1) => Ok(year2023::calibration(&mut rng, size)),
+        (2023, 2) => Ok(year2023::games(&mut rng, size)),
+        (2024, 1) => Ok(year2024::location_ids(&mut rng, size)),
+        (2024, 2) => year2024::reports(&mut rng, size, options.unsafe_ratio),
+        _ => Err(GenerateError::NoGenerator { year, day }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAYS: [(u32, u32); 13] = [
+        (2021, 1), (2021, 2), (2021, 3),
+        (2022, 1), (2022, 2), (2022, 3), (2022, 4), (2022, 5), (2022, 6),
+        (2023, 1), (2023, 2),
+        (2024, 1), (2024, 2),
+    ];
+
+    #[test]
+    fn test_deterministic_per_seed() {
+        for (year, day) in DAYS {
+            let options = GenOptions::default();
+            let first = generate(year, day, &options).unwrap();
+            assert_eq!(first, generate(year, day, &options).unwrap(), "{} day {}", year, day);
+            let reseeded = GenOptions { seed: 1, ..options };
+            assert_ne!(first, generate(year, day, &reseeded).unwrap(), "{} day {}", year, day);
+            assert!(first.ends_with('\n'));
+        }
+    }
+
+    #[test]
+    fn test_unknown_day() {
+        assert!(matches!(
+            generate(2024, 25, &GenOptions::default()),
+            Err(GenerateError::NoGenerator { year: 2024, day: 25 })
+        ));
+    }
+}
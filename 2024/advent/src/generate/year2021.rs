@@ -0,0 +1,82 @@
+use crate::errors::GenerateError;
+use advent_rng::Rng;
+
+/// Sonar depths, a random walk that mostly goes deeper.
+pub fn depths(rng: &mut Rng, size: usize) -> String {
+    let mut depth = rng.range(100, 200);
+    let mut input = String::new();
+    for _ in 0..size {
+        input += &format!("{}\n", depth);
+        depth = (depth + rng.range(-10, 20)).max(0);
+    }
+    input
+}
+
+/// Submarine commands. `up` never takes the depth, or the aim, below zero.
+pub fn commands(rng: &mut Rng, size: usize) -> String {
+    let mut depth = 0;
+    let mut input = String::new();
+    for _ in 0..size {
+        let line = match rng.below(3) {
+            0 => format!("forward {}", rng.range(1, 9)),
+            1 if depth > 0 => {
+                let units = rng.range(1, depth.min(9));
+                depth -= units;
+                format!("up {}", units)
+            }
+            _ => {
+                let units = rng.range(1, 9);
+                depth += units;
+                format!("down {}", units)
+            }
+        };
+        input += &line;
+        input.push('\n');
+    }
+    input
+}
+
+/// Distinct 12-bit diagnostic numbers.
+pub fn diagnostics(rng: &mut Rng, size: usize) -> Result<String, GenerateError> {
+    const WIDTH: usize = 12;
+    if size > 1 << WIDTH {
+        return Err(GenerateError::InvalidOption(format!(
+            "at most {} distinct diagnostic numbers fit in {} bits",
+            1 << WIDTH,
+            WIDTH
+        )));
+    }
+    let mut numbers: Vec<u32> = (0..1 << WIDTH).collect();
+    rng.shuffle(&mut numbers);
+    Ok(numbers[..size].iter().map(|number| format!("{:0width$b}\n", number, width = WIDTH)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_depth_never_goes_negative() {
+        let input = commands(&mut Rng::new(3), 500);
+        let mut depth = 0;
+        for line in input.lines() {
+            match line.split_once(' ').unwrap() {
+                ("down", units) => depth += units.parse::<i64>().unwrap(),
+                ("up", units) => depth -= units.parse::<i64>().unwrap(),
+                _ => {}
+            }
+            assert!(depth >= 0);
+        }
+    }
+
+    #[test]
+    fn test_diagnostics_are_distinct() {
+        let input = diagnostics(&mut Rng::new(3), 300).unwrap();
+        let mut lines: Vec<&str> = input.lines().collect();
+        assert!(lines.iter().all(|line| line.len() == 12));
+        lines.sort();
+        lines.dedup();
+        assert_eq!(lines.len(), 300);
+        assert!(diagnostics(&mut Rng::new(3), 5000).is_err());
+    }
+}
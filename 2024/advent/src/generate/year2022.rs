@@ -0,0 +1,183 @@
+use crate::errors::GenerateError;
+use advent_rng::Rng;
+
+const LETTERS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Calorie counts, one elf's items per block.
+pub fn calories(rng: &mut Rng, elves: usize) -> String {
+    let blocks: Vec<String> = (0..elves)
+        .map(|_| (0..rng.range(1, 10)).map(|_| format!("{}\n", rng.range(1000, 9999))).collect())
+        .collect();
+    blocks.join("\n")
+}
+
+/// Rock paper scissors rounds.
+pub fn strategy_guide(rng: &mut Rng, size: usize) -> String {
+    (0..size).map(|_| format!("{} {}\n", *rng.choose(b"ABC") as char, *rng.choose(b"XYZ") as char)).collect()
+}
+
+/// Groups of three rucksacks. In every rucksack exactly one item type is in
+/// both compartments, and in every group exactly one item type, the badge,
+/// is in all three rucksacks.
+pub fn rucksacks(rng: &mut Rng, groups: usize) -> String {
+    let mut input = String::new();
+    for _ in 0..groups {
+        let mut letters = LETTERS.to_vec();
+        rng.shuffle(&mut letters);
+        let badge = letters.pop().unwrap_or(b'a');
+
+        // Each rucksack draws its other items from its own 17 letters
+        for pool in letters.chunks(17) {
+            let (shared, rest) = pool.split_first().unwrap_or((&b'a', &[]));
+            let (first_pool, second_pool) = rest.split_at(rest.len() / 2);
+            let extra = rng.range(2, 14) as usize;
+
+            let mut first: Vec<u8> = vec![badge, *shared];
+            first.extend((0..extra).map(|_| *rng.choose(first_pool)));
+            let mut second: Vec<u8> = vec![*shared];
+            second.extend((0..=extra).map(|_| *rng.choose(second_pool)));
+            rng.shuffle(&mut first);
+            rng.shuffle(&mut second);
+
+            input += &String::from_utf8_lossy(&first);
+            input += &String::from_utf8_lossy(&second);
+            input.push('\n');
+        }
+    }
+    input
+}
+
+/// Pairs of section assignments.
+pub fn assignments(rng: &mut Rng, size: usize) -> String {
+    let mut range = || {
+        let start = rng.range(1, 99);
+        format!("{}-{}", start, rng.range(start, 99))
+    };
+    (0..size).map(|_| format!("{},{}\n", range(), range())).collect()
+}
+
+/// A drawing of nine stacks followed by `moves` moves that never take more
+/// crates than a stack holds.
+pub fn crates(rng: &mut Rng, moves: usize) -> String {
+    const STACKS: usize = 9;
+    let uppercase = &LETTERS[26..];
+    let mut stacks: Vec<Vec<u8>> =
+        (0..STACKS).map(|_| (0..rng.range(1, 8)).map(|_| *rng.choose(uppercase)).collect()).collect();
+
+    let height = stacks.iter().map(Vec::len).max().unwrap_or(0);
+    let mut input = String::new();
+    for level in (0..height).rev() {
+        let cells: Vec<String> = stacks
+            .iter()
+            .map(|stack| stack.get(level).map_or("   ".to_owned(), |&label| format!("[{}]", label as char)))
+            .collect();
+        input += &cells.join(" ");
+        input.push('\n');
+    }
+    let labels: Vec<String> = (1..=STACKS).map(|number| format!(" {} ", number)).collect();
+    input += &labels.join(" ");
+    input += "\n\n";
+
+    for _ in 0..moves {
+        let sources: Vec<usize> = (0..STACKS).filter(|&index| !stacks[index].is_empty()).collect();
+        let from = *rng.choose(&sources);
+        let to = (from + rng.range(1, STACKS as i64 - 1) as usize) % STACKS;
+        let count = rng.range(1, stacks[from].len().min(5) as i64) as usize;
+        let remaining = stacks[from].len() - count;
+        let lifted = stacks[from].split_off(remaining);
+        stacks[to].extend(lifted.iter().rev());
+        input += &format!("move {} from {} to {}\n", count, from + 1, to + 1);
+    }
+    input
+}
+
+/// A datastream of `size` characters with the start-of-packet marker ending
+/// a third of the way in and the start-of-message marker two thirds in.
+///
+/// The filler uses three letters, too few for either marker. Each marker is
+/// planted right after a doubled filler letter, so no window ends on a
+/// marker before the planted one is complete.
+pub fn datastream(rng: &mut Rng, size: usize) -> Result<String, GenerateError> {
+    const FILLER: &[u8] = b"abc";
+    if size < 60 {
+        return Err(GenerateError::InvalidOption("a datastream needs at least 60 characters".to_owned()));
+    }
+
+    let mut stream = Vec::with_capacity(size + 1);
+    for (marker_length, ends_at) in [(4, size / 3), (14, 2 * size / 3)] {
+        while stream.len() < ends_at - marker_length {
+            stream.push(*rng.choose(FILLER));
+        }
+        let doubled = *stream.last().unwrap_or(&b'a');
+        stream.push(doubled);
+        let mut fresh: Vec<u8> = LETTERS[..26].iter().copied().filter(|&letter| letter != doubled).collect();
+        rng.shuffle(&mut fresh);
+        stream.extend(&fresh[..marker_length - 1]);
+    }
+    while stream.len() < size {
+        stream.push(*rng.choose(FILLER));
+    }
+    stream.push(b'\n');
+    Ok(String::from_utf8_lossy(&stream).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn first_marker(stream: &str, length: usize) -> Option<usize> {
+        let bytes = stream.trim_end().as_bytes();
+        (length..=bytes.len()).find(|&end| bytes[end - length..end].iter().collect::<HashSet<_>>().len() == length)
+    }
+
+    #[test]
+    fn test_datastream_markers() {
+        for seed in 0..50 {
+            let stream = datastream(&mut Rng::new(seed), 300).unwrap();
+            assert_eq!(stream.trim_end().len(), 300);
+            assert_eq!(first_marker(&stream, 4), Some(100));
+            assert_eq!(first_marker(&stream, 14), Some(200));
+        }
+        assert!(datastream(&mut Rng::new(0), 10).is_err());
+    }
+
+    #[test]
+    fn test_rucksack_badges() {
+        let input = rucksacks(&mut Rng::new(5), 40);
+        let lines: Vec<&str> = input.lines().collect();
+        assert_eq!(lines.len(), 120);
+        let items = |text: &str| text.bytes().collect::<HashSet<u8>>();
+        for line in &lines {
+            let (first, second) = line.split_at(line.len() / 2);
+            assert_eq!(items(first).intersection(&items(second)).count(), 1, "{}", line);
+        }
+        for group in lines.chunks(3) {
+            let common: HashSet<u8> = items(group[0]).intersection(&items(group[1])).copied().collect();
+            assert_eq!(common.intersection(&items(group[2])).count(), 1);
+        }
+    }
+
+    #[test]
+    fn test_crate_moves_are_legal() {
+        let input = crates(&mut Rng::new(9), 200);
+        let (drawing, moves) = input.split_once("\n\n").unwrap();
+        let rows: Vec<&[u8]> = drawing.lines().map(str::as_bytes).collect();
+        let mut heights = [0usize; 9];
+        for row in &rows[..rows.len() - 1] {
+            for (index, height) in heights.iter_mut().enumerate() {
+                if row.get(index * 4 + 1).is_some_and(|label| label.is_ascii_uppercase()) {
+                    *height += 1;
+                }
+            }
+        }
+        for line in moves.lines() {
+            let words: Vec<usize> = line.split(' ').filter_map(|word| word.parse().ok()).collect();
+            let (count, from, to) = (words[0], words[1] - 1, words[2] - 1);
+            assert!(count <= heights[from] && from != to, "{}", line);
+            heights[from] -= count;
+            heights[to] += count;
+        }
+        assert_eq!(moves.lines().count(), 200);
+    }
+}
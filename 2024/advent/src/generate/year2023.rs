@@ -0,0 +1,61 @@
+use advent_rng::Rng;
+
+const DIGIT_WORDS: [&str; 9] = ["one", "two", "three", "four", "five", "six", "seven", "eight", "nine"];
+
+/// Calibration lines of letters, digits and digit words, with at least one
+/// digit in every line.
+pub fn calibration(rng: &mut Rng, size: usize) -> String {
+    let mut input = String::new();
+    for _ in 0..size {
+        let mut line = String::new();
+        let pieces = rng.range(1, 6);
+        let digit_at = rng.range(0, pieces - 1);
+        for piece in 0..pieces {
+            match rng.below(3) {
+                _ if piece == digit_at => line += &rng.range(1, 9).to_string(),
+                0 => line += *rng.choose(&DIGIT_WORDS),
+                1 => line += &rng.range(1, 9).to_string(),
+                _ => line.extend((0..rng.range(1, 5)).map(|_| (b'a' + rng.below(26) as u8) as char)),
+            }
+        }
+        input += &line;
+        input.push('\n');
+    }
+    input
+}
+
+/// Cube games with one to six sets of red, green and blue cubes.
+pub fn games(rng: &mut Rng, size: usize) -> String {
+    let mut input = String::new();
+    for id in 1..=size {
+        let sets: Vec<String> = (0..rng.range(1, 6))
+            .map(|_| {
+                let mut colors = ["red", "green", "blue"];
+                rng.shuffle(&mut colors);
+                let shown = rng.range(1, 3) as usize;
+                let cubes: Vec<String> =
+                    colors[..shown].iter().map(|color| format!("{} {}", rng.range(1, 20), color)).collect();
+                cubes.join(", ")
+            })
+            .collect();
+        input += &format!("Game {}: {}\n", id, sets.join("; "));
+    }
+    input
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_calibration_line_has_a_digit() {
+        let input = calibration(&mut Rng::new(11), 500);
+        assert!(input.lines().all(|line| line.bytes().any(|byte| byte.is_ascii_digit())));
+    }
+
+    #[test]
+    fn test_games_are_numbered() {
+        let input = games(&mut Rng::new(11), 20);
+        assert!(input.lines().zip(1..).all(|(line, id)| line.starts_with(&format!("Game {}: ", id))));
+    }
+}
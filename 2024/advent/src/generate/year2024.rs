@@ -0,0 +1,78 @@
+use crate::errors::GenerateError;
+use advent_rng::Rng;
+
+/// Two columns of location ids. About a third of the right column repeats ids
+/// from the left one, so the similarity score isn't zero.
+pub fn location_ids(rng: &mut Rng, size: usize) -> String {
+    let left: Vec<i64> = (0..size).map(|_| rng.range(10000, 99999)).collect();
+    let mut input = String::new();
+    for &id in &left {
+        let right = if rng.chance(0.3) { *rng.choose(&left) } else { rng.range(10000, 99999) };
+        input += &format!("{}   {}\n", id, right);
+    }
+    input
+}
+
+/// Level reports of which `unsafe_ratio` are unsafe without the dampener.
+///
+/// Safe reports step 1 to 3 in one direction. Unsafe ones get one step
+/// replaced by a flat step, a jump or a step the wrong way.
+pub fn reports(rng: &mut Rng, size: usize, unsafe_ratio: f64) -> Result<String, GenerateError> {
+    if !(0.0..=1.0).contains(&unsafe_ratio) {
+        return Err(GenerateError::InvalidOption(format!("unsafe ratio {} is not between 0 and 1", unsafe_ratio)));
+    }
+    let unsafe_count = (size as f64 * unsafe_ratio).round() as usize;
+    let mut is_unsafe: Vec<bool> = (0..size).map(|index| index < unsafe_count).collect();
+    rng.shuffle(&mut is_unsafe);
+
+    let mut input = String::new();
+    for is_unsafe in is_unsafe {
+        let direction = if rng.chance(0.5) { 1 } else { -1 };
+        let mut steps: Vec<i64> = (0..rng.range(4, 7)).map(|_| direction * rng.range(1, 3)).collect();
+        if is_unsafe {
+            let bad_step = match rng.below(3) {
+                0 => 0,
+                1 => direction * rng.range(4, 6),
+                _ => -direction * rng.range(1, 3),
+            };
+            let index = rng.below(steps.len() as u64) as usize;
+            steps[index] = bad_step;
+        }
+
+        let mut level = rng.range(30, 70);
+        let mut levels = vec![level.to_string()];
+        for step in steps {
+            level += step;
+            levels.push(level.to_string());
+        }
+        input += &levels.join(" ");
+        input.push('\n');
+    }
+    Ok(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day2::{count_safe_levels, read_and_parse_file};
+
+    #[test]
+    fn test_unsafe_ratio() {
+        let input = reports(&mut Rng::new(4), 200, 0.25).unwrap();
+        let path = std::env::temp_dir().join(format!("advent_gen_reports_{}.txt", std::process::id()));
+        std::fs::write(&path, input).unwrap();
+        let safe = count_safe_levels(read_and_parse_file(path.to_str().unwrap()), 1).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(safe, 150);
+
+        assert!(reports(&mut Rng::new(4), 10, 1.5).is_err());
+    }
+
+    #[test]
+    fn test_location_ids_overlap() {
+        let input = location_ids(&mut Rng::new(4), 100);
+        let pairs: Vec<(&str, &str)> = input.lines().map(|line| line.split_once("   ").unwrap()).collect();
+        assert_eq!(pairs.len(), 100);
+        assert!(pairs.iter().any(|(_, right)| pairs.iter().any(|(left, _)| left == right)));
+    }
+}
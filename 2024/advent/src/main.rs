@@ -1,46 +1,186 @@
 mod day1;
 mod day2;
 mod errors;
+mod examples;
+mod generate;
 mod utils;
 
-use std::result;
-
 use crate::day1::{process_1, process_2, read_and_parse_file as day1_read_and_parse_file};
 use crate::day2::{count_safe_levels, read_and_parse_file as day2_read_and_parse_file};
-use clap::Parser;
+use crate::errors::ExampleError;
+use crate::examples::{extract, write_fixtures, Format};
+use crate::generate::{generate, GenOptions};
+use advent_config::Config;
+use advent_vault::{Status, Vault, VaultError};
+use clap::{Parser, Subcommand};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    #[arg(short, long, default_value_t = String::from("1"))]
+    /// Day to run, the one in advent.toml by default
+    #[arg(short, long)]
+    day: Option<String>,
+
+    /// Directory holding input_<day>.txt, the one in advent.toml by default
+    #[arg(long)]
+    input_dir: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print a random puzzle input for a solved day
+    Gen {
+        #[arg(long)]
+        year: u32,
+        #[arg(long)]
+        day: u32,
+        /// Number of lines, or of elves, groups, moves or characters depending on the day
+        #[arg(long, default_value_t = 100)]
+        size: usize,
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        /// Share of unsafe reports for 2024 day 2
+        #[arg(long, default_value_t = 0.5)]
+        unsafe_ratio: f64,
+    },
+    /// Extract example inputs and answers from a saved puzzle page or Readme
+    Examples {
+        /// Puzzle statement saved as HTML or markdown
+        puzzle: PathBuf,
+        /// Directory to write the inputs and answers.toml to, they are printed otherwise
+        #[arg(long, requires = "day")]
+        out: Option<PathBuf>,
+        /// Day the answers are for in answers.toml
+        #[arg(long)]
+        day: Option<u32>,
+        /// Input file name without extension, test_input_<day> by default
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Keep puzzle inputs encrypted in vault/ at the root of the repository
+    /// so they can be committed
+    Vault {
+        /// Key file, ADVENT_VAULT_KEY or .vault.key next to vault/ by default
+        #[arg(long)]
+        key: Option<PathBuf>,
+        #[command(subcommand)]
+        action: VaultAction,
+    },
+}
+
+/// The settings of the year from `advent.toml`, flags override them.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct Settings {
+    /// Relative to advent.toml, data/ in the working directory when unset.
+    input_dir: Option<PathBuf>,
     day: String,
 }
 
+impl Default for Settings {
+    fn default() -> Self {
+        Settings { input_dir: None, day: String::from("1") }
+    }
+}
+
+impl Settings {
+    fn input(&self, day: u32) -> String {
+        let input_dir = self.input_dir.as_deref().unwrap_or(Path::new("data"));
+        input_dir.join(format!("input_{}.txt", day)).to_string_lossy().into_owned()
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum VaultAction {
+    /// Encrypt inputs into the vault, creating the key file if there is none
+    Add {
+        #[arg(required = true)]
+        inputs: Vec<PathBuf>,
+    },
+    /// Check that every entry decrypts and matches its plain input if present
+    Verify,
+}
+
 fn main() {
     let args = Args::parse();
 
-    match args.day.as_str() {
-        "1" => run_day1(),
-        "2" => run_day2(),
-        _ => println!("Day {} is not implemented yet", args.day),
+    match args.command {
+        Some(Command::Gen { year, day, size, seed, unsafe_ratio }) => {
+            match generate(year, day, &GenOptions { size, seed, unsafe_ratio }) {
+                Ok(input) => print!("{}", input),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(Command::Examples { puzzle, out, day, name }) => {
+            if let Err(e) = run_examples(puzzle, out, day, name) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(Command::Vault { key, action }) => {
+            match run_vault(key, action) {
+                Ok(true) => {}
+                Ok(false) => std::process::exit(1),
+                Err(e) => {
+                    eprintln!("Error: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        None => {}
+    }
+
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let mut settings: Settings = match config.year(2024) {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    settings.day = args.day.unwrap_or(settings.day);
+    settings.input_dir = args.input_dir.or_else(|| settings.input_dir.map(|dir| config.resolve(&dir)));
+
+    match settings.day.as_str() {
+        "1" => run_day1(&settings),
+        "2" => run_day2(&settings),
+        _ => println!("Day {} is not implemented yet", settings.day),
     }
 }
 
-fn run_day1() {
-    let file_content = day1_read_and_parse_file("data/input_1.txt");
+fn run_day1(settings: &Settings) {
+    let file_content = day1_read_and_parse_file(&settings.input(1));
     match process_1(file_content) {
-        Ok(i32) => {
-            println!("Success! Total distance: {}", i32);
+        Ok(total) => {
+            println!("Success! Total distance: {}", total);
         }
         Err(e) => {
             println!("Error: {}", e);
         }
     }
 
-    let file_content = day1_read_and_parse_file("data/input_1.txt");
+    let file_content = day1_read_and_parse_file(&settings.input(1));
     match process_2(file_content) {
-        Ok(i32) => {
-            println!("Success! Total similarity: {}", i32);
+        Ok(total) => {
+            println!("Success! Total similarity: {}", total);
         }
         Err(e) => {
             println!("Error: {}", e);
@@ -48,8 +188,8 @@ fn run_day1() {
     }
 }
 
-fn run_day2() {
-    let reports = day2_read_and_parse_file("data/input_2.txt");
+fn run_day2(settings: &Settings) {
+    let reports = day2_read_and_parse_file(&settings.input(2));
     let mut result1: String = String::new();
     let mut result2: String = String::new();
     match count_safe_levels(reports, 1) {
@@ -62,7 +202,7 @@ fn run_day2() {
         }
     }
 
-    let reports = day2_read_and_parse_file("data/input_2.txt");
+    let reports = day2_read_and_parse_file(&settings.input(2));
     match count_safe_levels(reports, 2) {
         Ok(u32) => {
             println!("Success! Total safe reports: {}", u32);
@@ -74,3 +214,105 @@ fn run_day2() {
     }
     println!("Results: {}, {}", result1, result2);
 }
+
+fn run_examples(puzzle: PathBuf, out: Option<PathBuf>, day: Option<u32>, name: Option<String>) -> Result<(), ExampleError> {
+    let document = fs::read_to_string(&puzzle).map_err(|source| ExampleError::Io { path: puzzle.clone(), source })?;
+    let examples = extract(&document, Format::of(&puzzle));
+    if examples.is_empty() {
+        return Err(ExampleError::NoExamples(puzzle));
+    }
+    match (out, day) {
+        (Some(dir), Some(day)) => {
+            let name = name.unwrap_or_else(|| format!("test_input_{}", day));
+            for path in write_fixtures(&dir, day, &name, &examples)? {
+                println!("Wrote {}", path.display());
+            }
+        }
+        _ => {
+            for (index, example) in examples.iter().enumerate() {
+                let part1 = example.part1.as_deref().unwrap_or("?");
+                let part2 = example.part2.as_deref().unwrap_or("?");
+                println!("Example {} (part 1: {}, part 2: {}):", index + 1, part1, part2);
+                print!("{}", example.input);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns whether every entry checked out.
+fn run_vault(key: Option<PathBuf>, action: VaultAction) -> Result<bool, VaultError> {
+    let cwd = std::env::current_dir().map_err(|source| VaultError::Io { path: PathBuf::from("."), source })?;
+    let root = advent_vault::find_root(&cwd).ok_or_else(|| VaultError::Io {
+        path: cwd.join(advent_vault::VAULT_DIR),
+        source: std::io::Error::new(std::io::ErrorKind::NotFound, "no vault/ here or above, create one at the repository root"),
+    })?;
+    let key = key.unwrap_or_else(|| advent_vault::default_key_path(&root));
+    match action {
+        VaultAction::Add { inputs } => {
+            if Vault::create_key(&key)? {
+                println!("Created key {}, keep it out of the repository", key.display());
+            }
+            let vault = Vault::open(root, &key)?;
+            for input in inputs {
+                println!("Wrote {}", vault.add(&vault.input_name(&input)?)?.display());
+            }
+            Ok(true)
+        }
+        VaultAction::Verify => {
+            let statuses = Vault::open(root, &key)?.verify()?;
+            for (input, status) in &statuses {
+                match status {
+                    Status::Ok => println!("ok       {}", input.display()),
+                    Status::Differs => println!("differs  {}", input.display()),
+                    Status::Broken(reason) => println!("broken   {}: {}", input.display(), reason),
+                }
+            }
+            Ok(statuses.iter().all(|(_, status)| *status == Status::Ok))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ParseError;
+    use crate::examples::{read_answers, ANSWERS_FILE};
+
+    /// The answer to a part of a day for the input at `path`, `None` for days
+    /// without a solution yet.
+    fn solve(day: u32, part: usize, path: &str) -> Option<Result<String, ParseError>> {
+        match day {
+            1 => {
+                let lists = day1_read_and_parse_file(path);
+                let total = if part == 1 { process_1(lists) } else { process_2(lists) };
+                Some(total.map(|total| total.to_string()))
+            }
+            2 => Some(count_safe_levels(day2_read_and_parse_file(path), part).map(|count| count.to_string())),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_example_answers() {
+        let dir = Path::new("data");
+        for example in read_answers(&dir.join(ANSWERS_FILE)).unwrap() {
+            let input = dir.join(&example.input);
+            for (part, expected) in [(1, &example.part1), (2, &example.part2)] {
+                let (Some(expected), Some(answer)) = (expected, solve(example.day, part, input.to_str().unwrap())) else {
+                    continue;
+                };
+                assert_eq!(&answer.unwrap(), expected, "day {} part {} on {}", example.day, part, example.input);
+            }
+        }
+    }
+
+    #[test]
+    fn test_settings_from_config() {
+        let config = Config::parse("input_dir = \"inputs\"\n[2024]\nday = \"2\"\n").unwrap();
+        let settings: Settings = config.year(2024).unwrap();
+        assert_eq!(settings.day, "2");
+        assert_eq!(settings.input(2), Path::new("inputs").join("input_2.txt").to_string_lossy());
+        assert_eq!(Settings::default().input(1), Path::new("data").join("input_1.txt").to_string_lossy());
+    }
+}
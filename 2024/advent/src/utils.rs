@@ -1,11 +1,12 @@
-use std::fs::File;
 use std::io::{self, BufRead};
 use std::path::Path;
 
-pub fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
+/// Lines of `filename`, read from the input vault if the plain file isn't
+/// there but the vault has it.
+pub fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<Box<dyn io::Read>>>>
 where
     P: AsRef<Path>,
 {
-    let file = File::open(filename)?;
+    let file = advent_vault::open(filename)?;
     Ok(io::BufReader::new(file).lines())
 }
@@ -0,0 +1,247 @@
+//! Settings for the solvers, read from `advent.toml` at the root of the
+//! repository. [`Config::load`] finds it by walking up from the working
+//! directory, so a solver run from its own crate picks it up, and
+//! `ADVENT_CONFIG` can name another file. It has a section per year and one
+//! per day inside it:
+//!
+//! ```toml
+//! [2022.day6]
+//! input_dir = "2022/day06"
+//! packet_marker = 4
+//! ```
+//!
+//! A solver asks for its day's settings as a typed struct. A setting is
+//! looked up in the day's section, then in the year's, then at the top of the
+//! file, and the struct's defaults fill in whatever is missing. Command line
+//! flags are applied on top by the solver. Paths in settings are relative to
+//! the file they're in, [`Config::resolve`] makes them usable from the
+//! solver's working directory.
+//!
+//! ```ignore
+//! #[derive(Deserialize, Default)]
+//! #[serde(default)]
+//! struct Settings { input_dir: Option<PathBuf>, packet_marker: usize }
+//!
+//! let config = advent_config::Config::load()?;
+//! let settings: Settings = config.day(2022, 6)?;
+//! let input_dir = settings.input_dir.map(|dir| config.resolve(&dir));
+//! ```
+
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use toml::{Table, Value};
+
+pub const FILE_NAME: &str = "advent.toml";
+
+/// The contents of an `advent.toml`, empty if there is none.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+    path: Option<PathBuf>,
+    table: Table,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    pub path: Option<PathBuf>,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "{}: {}", path.display(), self.message),
+            None => write!(f, "{}: {}", FILE_NAME, self.message),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+impl Config {
+    /// The file named by `ADVENT_CONFIG`, or the closest `advent.toml` in the
+    /// working directory or above it.
+    pub fn load() -> Result<Config, ConfigError> {
+        if let Some(path) = env::var_os("ADVENT_CONFIG") {
+            return Config::read(Path::new(&path));
+        }
+        let found = env::current_dir().ok().and_then(|dir| Config::find(&dir));
+        found.map_or_else(|| Ok(Config::default()), |path| Config::read(&path))
+    }
+
+    pub fn find(start: &Path) -> Option<PathBuf> {
+        start.ancestors().map(|dir| dir.join(FILE_NAME)).find(|path| path.is_file())
+    }
+
+    pub fn read(path: &Path) -> Result<Config, ConfigError> {
+        let error = |message: String| ConfigError { path: Some(path.to_path_buf()), message };
+        let text = fs::read_to_string(path).map_err(|err| error(err.to_string()))?;
+        let table = Config::parse(&text).map_err(|err| error(err.message))?.table;
+        Ok(Config { path: Some(path.to_path_buf()), table })
+    }
+
+    pub fn parse(text: &str) -> Result<Config, ConfigError> {
+        let table = text.parse::<Table>().map_err(|err| ConfigError { path: None, message: err.message().to_owned() })?;
+        Ok(Config { path: None, table })
+    }
+
+    /// Where the settings were read from, `None` for defaults.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// A path from a setting, taken relative to the directory of the file it
+    /// was read from. Absolute paths and paths of parsed settings are kept.
+    pub fn resolve(&self, path: &Path) -> PathBuf {
+        match self.path.as_deref().and_then(Path::parent) {
+            Some(dir) => dir.join(path),
+            None => path.to_path_buf(),
+        }
+    }
+
+    /// The settings of a whole year, without its days' sections.
+    pub fn year<T: DeserializeOwned>(&self, year: u32) -> Result<T, ConfigError> {
+        self.settings(&[&year.to_string()])
+    }
+
+    /// The settings of a day, falling back to its year's and the top-level
+    /// ones.
+    pub fn day<T: DeserializeOwned>(&self, year: u32, day: u32) -> Result<T, ConfigError> {
+        self.settings(&[&year.to_string(), &format!("day{}", day)])
+    }
+
+    /// Merges the plain settings of each section along `sections`, the
+    /// innermost last so that it wins.
+    fn settings<T: DeserializeOwned>(&self, sections: &[&str]) -> Result<T, ConfigError> {
+        let mut merged = Table::new();
+        let mut table = Some(&self.table);
+        let mut name = Vec::new();
+        for depth in 0..=sections.len() {
+            let Some(current) = table else { break };
+            for (key, value) in current {
+                if !is_section(key, depth) {
+                    merged.insert(key.clone(), value.clone());
+                }
+            }
+            if let Some(&section) = sections.get(depth) {
+                name.push(section);
+                table = match current.get(section) {
+                    Some(Value::Table(inner)) => Some(inner),
+                    Some(_) => return Err(self.error(format!("[{}] must be a section", name.join(".")))),
+                    None => None,
+                };
+            }
+        }
+        Value::Table(merged)
+            .try_into()
+            .map_err(|err: toml::de::Error| self.error(format!("in [{}]: {}", sections.join("."), err.message())))
+    }
+
+    fn error(&self, message: String) -> ConfigError {
+        ConfigError { path: self.path.clone(), message }
+    }
+}
+
+/// Years at the top of the file and `dayN` inside them are sections, not
+/// settings.
+fn is_section(key: &str, depth: usize) -> bool {
+    let digits = |text: &str| !text.is_empty() && text.bytes().all(|byte| byte.is_ascii_digit());
+    match depth {
+        0 => digits(key),
+        1 => key.strip_prefix("day").is_some_and(digits),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(default)]
+    struct Settings {
+        input_dir: String,
+        marker: usize,
+        day: String,
+    }
+
+    impl Default for Settings {
+        fn default() -> Self {
+            Settings { input_dir: "data".to_owned(), marker: 4, day: "1".to_owned() }
+        }
+    }
+
+    const CONFIG: &str = r#"
+        input_dir = "inputs"
+
+        [2022]
+        input_dir = "."
+        marker = 5
+
+        [2022.day6]
+        marker = 14
+
+        [2024]
+        day = "2"
+    "#;
+
+    #[test]
+    fn test_innermost_section_wins() {
+        let config = Config::parse(CONFIG).unwrap();
+        let day6: Settings = config.day(2022, 6).unwrap();
+        assert_eq!(day6, Settings { input_dir: ".".to_owned(), marker: 14, day: "1".to_owned() });
+        let day5: Settings = config.day(2022, 5).unwrap();
+        assert_eq!(day5.marker, 5);
+        let year: Settings = config.year(2024).unwrap();
+        assert_eq!(year, Settings { input_dir: "inputs".to_owned(), marker: 4, day: "2".to_owned() });
+        let other: Settings = config.day(2023, 2).unwrap();
+        assert_eq!(other.input_dir, "inputs");
+    }
+
+    #[test]
+    fn test_missing_file_gives_defaults() {
+        let config = Config::default();
+        assert_eq!(config.day::<Settings>(2022, 6).unwrap(), Settings::default());
+        assert_eq!(config.path(), None);
+    }
+
+    #[test]
+    fn test_errors_name_the_section() {
+        let config = Config::parse("[2022.day6]\nmarker = \"four\"\n").unwrap();
+        let err = config.day::<Settings>(2022, 6).unwrap_err();
+        assert!(err.message.starts_with("in [2022.day6]: "), "{}", err);
+
+        let config = Config::parse("2022 = 1\n").unwrap();
+        assert_eq!(config.day::<Settings>(2022, 6).unwrap_err().message, "[2022] must be a section");
+
+        assert!(Config::parse("[2022\n").is_err());
+    }
+
+    #[test]
+    fn test_find_walks_up() {
+        let root = env::temp_dir().join(format!("advent-config-{}", std::process::id()));
+        let nested = root.join("2022").join("day06");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join(FILE_NAME), CONFIG).unwrap();
+
+        let path = Config::find(&nested).unwrap();
+        assert_eq!(path, root.join(FILE_NAME));
+        let config = Config::read(&path).unwrap();
+        assert_eq!(config.path(), Some(path.as_path()));
+        assert_eq!(config.day::<Settings>(2022, 6).unwrap().marker, 14);
+        assert_eq!(config.resolve(Path::new("2022/day06")), root.join("2022/day06"));
+        assert_eq!(config.resolve(&root.join("inputs")), root.join("inputs"));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_parsed_paths_are_kept() {
+        let config = Config::parse(CONFIG).unwrap();
+        assert_eq!(config.resolve(Path::new("inputs")), PathBuf::from("inputs"));
+    }
+}
@@ -0,0 +1,89 @@
+//! A seeded random number generator for property tests and generated puzzle
+//! inputs. The same seed gives the same sequence on every platform and every
+//! build, so a failing case or a generated input can be reproduced from it.
+
+/// SplitMix64: tiny, fast and the same sequence for a seed everywhere.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..bound`, `bound` must not be 0.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        ((self.next_u64() as u128 * bound as u128) >> 64) as u64
+    }
+
+    /// A number in `low..=high`.
+    pub fn range(&mut self, low: i64, high: i64) -> i64 {
+        match high.abs_diff(low).checked_add(1) {
+            Some(count) => low.wrapping_add(self.below(count) as i64),
+            // The whole of i64
+            None => self.next_u64() as i64,
+        }
+    }
+
+    /// True with probability `p`.
+    pub fn chance(&mut self, p: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+
+    /// A random element, `items` must not be empty.
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+
+    /// Fisher-Yates shuffle.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i as u64 + 1) as usize);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let first: Vec<u64> = (0..5).scan(Rng::new(7), |rng, _| Some(rng.next_u64())).collect();
+        let second: Vec<u64> = (0..5).scan(Rng::new(7), |rng, _| Some(rng.next_u64())).collect();
+        assert_eq!(first, second);
+        assert_ne!(Rng::new(8).next_u64(), first[0]);
+    }
+
+    #[test]
+    fn test_range_bounds() {
+        let mut rng = Rng::new(1);
+        let values: Vec<i64> = (0..1000).map(|_| rng.range(-2, 3)).collect();
+        assert!(values.iter().all(|value| (-2..=3).contains(value)));
+        assert!(values.contains(&-2) && values.contains(&3));
+
+        // Spans that don't fit in an i64 must not overflow
+        assert!(rng.range(i64::MIN + 1, i64::MAX) > i64::MIN);
+        rng.range(i64::MIN, i64::MAX);
+        assert_eq!(rng.range(i64::MAX, i64::MAX), i64::MAX);
+    }
+
+    #[test]
+    fn test_shuffle_keeps_items() {
+        let mut items: Vec<u32> = (0..20).collect();
+        Rng::new(3).shuffle(&mut items);
+        assert_ne!(items, (0..20).collect::<Vec<_>>());
+        items.sort();
+        assert_eq!(items, (0..20).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,444 @@
+//! Puzzle inputs kept encrypted under `vault/` at the root of the repository
+//! so they can be committed without redistributing them.
+//! `vault/2022/day04/data.txt.enc` holds `2022/day04/data.txt`, sealed with
+//! XChaCha20-Poly1305 under a key that only lives in the local key file. The
+//! entry's path is authenticated too, so an entry can't be passed off as
+//! another input. `vault/README.md` covers sealing the inputs with
+//! `advent vault add` and handing the key over.
+//!
+//! Solvers open their inputs with [`open`] instead of `File::open`. It reads
+//! the plain file if there is one and the input's entry otherwise, found
+//! through the closest `vault/` above the working directory:
+//!
+//! ```ignore
+//! let reader = BufReader::new(advent_vault::open("data.txt")?);
+//! ```
+//!
+//! The 2021 Node solutions keep their inputs in JavaScript modules and don't
+//! go through the vault.
+
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Cursor, Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+
+pub const VAULT_DIR: &str = "vault";
+/// Key file next to `vault/`, used unless `ADVENT_VAULT_KEY` names another
+/// one.
+pub const KEY_FILE: &str = ".vault.key";
+const EXTENSION: &str = "enc";
+const MAGIC: &[u8] = b"advent-vault-1\n";
+const NONCE_LEN: usize = 24;
+
+#[derive(Debug)]
+pub enum VaultError {
+    Io { path: PathBuf, source: io::Error },
+    BadKey(PathBuf),
+    InvalidInputPath(PathBuf),
+    NotInVault(PathBuf),
+    Encrypt(PathBuf),
+    Decrypt(PathBuf),
+}
+
+impl fmt::Display for VaultError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VaultError::Io { path, source } => write!(f, "Failed to access {}: {}", path.display(), source),
+            VaultError::BadKey(path) => write!(f, "Invalid vault key in {}, expected 64 hex digits", path.display()),
+            VaultError::InvalidInputPath(path) => {
+                write!(f, "Inputs must be inside the repository, got {}", path.display())
+            }
+            VaultError::NotInVault(path) => write!(f, "{} is not in the vault", path.display()),
+            VaultError::Encrypt(path) => write!(f, "Failed to encrypt {}", path.display()),
+            VaultError::Decrypt(path) => write!(f, "Failed to decrypt {}, wrong key or damaged entry", path.display()),
+        }
+    }
+}
+
+impl Error for VaultError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            VaultError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Opens the input at `path` like `File::open`, falling back to its entry in
+/// the vault when there is no such file.
+pub fn open(path: impl AsRef<Path>) -> io::Result<Box<dyn Read>> {
+    let cwd = env::current_dir()?;
+    open_from(&cwd, path.as_ref())
+}
+
+pub fn read_to_string(path: impl AsRef<Path>) -> io::Result<String> {
+    let mut text = String::new();
+    open(path)?.read_to_string(&mut text)?;
+    Ok(text)
+}
+
+/// `open` with `path` taken relative to `cwd`.
+fn open_from(cwd: &Path, path: &Path) -> io::Result<Box<dyn Read>> {
+    match File::open(cwd.join(path)) {
+        Ok(file) => Ok(Box::new(file)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            // The message already names the cause, keep it from being printed twice
+            match read_entry(cwd, path).map_err(|vault_err| io::Error::other(vault_err.to_string()))? {
+                Some(contents) => Ok(Box::new(Cursor::new(contents))),
+                None => Err(err),
+            }
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// The decrypted entry for `path`, `None` without a vault or such an entry.
+fn read_entry(cwd: &Path, path: &Path) -> Result<Option<Vec<u8>>, VaultError> {
+    let Some(root) = find_root(cwd) else { return Ok(None) };
+    let Ok(input) = input_name(&root, &cwd.join(path)) else { return Ok(None) };
+    if !entry_path(&root.join(VAULT_DIR), &input).is_ok_and(|entry| entry.is_file()) {
+        return Ok(None);
+    }
+    Vault::open(&root, &default_key_path(&root))?.read(&input).map(Some)
+}
+
+/// The closest directory holding a `vault/`, from `start` upwards.
+pub fn find_root(start: &Path) -> Option<PathBuf> {
+    start.ancestors().find(|dir| dir.join(VAULT_DIR).is_dir()).map(Path::to_path_buf)
+}
+
+/// The key of the vault under `root`.
+pub fn default_key_path(root: &Path) -> PathBuf {
+    env::var_os("ADVENT_VAULT_KEY").map_or_else(|| root.join(KEY_FILE), PathBuf::from)
+}
+
+/// What `verify` found for one entry.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Status {
+    Ok,
+    /// The entry decrypts, but the plain input next to it is different.
+    Differs,
+    Broken(String),
+}
+
+/// The vault under `root`, holding inputs given relative to `root`.
+pub struct Vault {
+    root: PathBuf,
+    dir: PathBuf,
+    cipher: XChaCha20Poly1305,
+}
+
+impl Vault {
+    pub fn open(root: impl Into<PathBuf>, key_path: &Path) -> Result<Vault, VaultError> {
+        let text = fs::read_to_string(key_path).map_err(|source| VaultError::Io { path: key_path.to_path_buf(), source })?;
+        let key = decode_hex(text.trim()).ok_or_else(|| VaultError::BadKey(key_path.to_path_buf()))?;
+        let cipher = XChaCha20Poly1305::new_from_slice(&key).map_err(|_| VaultError::BadKey(key_path.to_path_buf()))?;
+        let root = root.into();
+        Ok(Vault { dir: root.join(VAULT_DIR), root, cipher })
+    }
+
+    /// Writes a new random key to `key_path`, readable by the owner only.
+    /// Does nothing if the file exists. Returns whether a key was written.
+    pub fn create_key(key_path: &Path) -> Result<bool, VaultError> {
+        let io_error = |source| VaultError::Io { path: key_path.to_path_buf(), source };
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = match options.open(key_path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => return Ok(false),
+            Err(err) => return Err(io_error(err)),
+        };
+        let key = XChaCha20Poly1305::generate_key(&mut OsRng);
+        writeln!(file, "{}", encode_hex(&key)).map_err(io_error)?;
+        Ok(true)
+    }
+
+    /// The input at `path`, relative to the working directory, as the vault
+    /// names it: relative to the root.
+    pub fn input_name(&self, path: &Path) -> Result<PathBuf, VaultError> {
+        let cwd = env::current_dir().map_err(|source| VaultError::Io { path: PathBuf::from("."), source })?;
+        input_name(&cwd.join(&self.root), &cwd.join(path))
+    }
+
+    /// Encrypts the input at `input`, like `2022/day04/data.txt`, into the
+    /// vault. Returns the entry's path.
+    pub fn add(&self, input: &Path) -> Result<PathBuf, VaultError> {
+        let entry = self.entry_path(input)?;
+        let path = self.root.join(input);
+        let plain = fs::read(&path).map_err(|source| VaultError::Io { path, source })?;
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let sealed = self
+            .cipher
+            .encrypt(&nonce, Payload { msg: &plain, aad: associated_data(input).as_bytes() })
+            .map_err(|_| VaultError::Encrypt(input.to_path_buf()))?;
+
+        let mut contents = Vec::with_capacity(MAGIC.len() + NONCE_LEN + sealed.len());
+        contents.extend_from_slice(MAGIC);
+        contents.extend_from_slice(&nonce);
+        contents.extend_from_slice(&sealed);
+        let io_error = |source| VaultError::Io { path: entry.clone(), source };
+        if let Some(parent) = entry.parent() {
+            fs::create_dir_all(parent).map_err(io_error)?;
+        }
+        fs::write(&entry, contents).map_err(io_error)?;
+        Ok(entry)
+    }
+
+    pub fn contains(&self, input: &Path) -> bool {
+        self.entry_path(input).is_ok_and(|entry| entry.is_file())
+    }
+
+    /// The decrypted contents of the input at `input`.
+    pub fn read(&self, input: &Path) -> Result<Vec<u8>, VaultError> {
+        let entry = self.entry_path(input)?;
+        let contents = match fs::read(&entry) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Err(VaultError::NotInVault(input.to_path_buf())),
+            Err(source) => return Err(VaultError::Io { path: entry, source }),
+        };
+        let sealed = contents.strip_prefix(MAGIC).filter(|rest| rest.len() >= NONCE_LEN);
+        let Some((nonce, sealed)) = sealed.map(|rest| rest.split_at(NONCE_LEN)) else {
+            return Err(VaultError::Decrypt(entry));
+        };
+        self.cipher
+            .decrypt(XNonce::from_slice(nonce), Payload { msg: sealed, aad: associated_data(input).as_bytes() })
+            .map_err(|_| VaultError::Decrypt(entry))
+    }
+
+    /// The inputs in the vault, sorted.
+    pub fn inputs(&self) -> Result<Vec<PathBuf>, VaultError> {
+        let mut inputs = Vec::new();
+        let mut pending = vec![self.dir.clone()];
+        while let Some(dir) = pending.pop() {
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(err) if err.kind() == io::ErrorKind::NotFound && dir == self.dir => break,
+                Err(source) => return Err(VaultError::Io { path: dir, source }),
+            };
+            for entry in entries {
+                let path = entry.map_err(|source| VaultError::Io { path: dir.clone(), source })?.path();
+                if path.is_dir() {
+                    pending.push(path);
+                } else if path.extension().is_some_and(|extension| extension == EXTENSION) {
+                    let input = path.strip_prefix(&self.dir).unwrap_or(&path).with_extension("");
+                    inputs.push(input);
+                }
+            }
+        }
+        inputs.sort();
+        Ok(inputs)
+    }
+
+    /// Checks that every entry decrypts and matches the plain input if that
+    /// is there too.
+    pub fn verify(&self) -> Result<Vec<(PathBuf, Status)>, VaultError> {
+        let statuses = self.inputs()?.into_iter().map(|input| {
+            let status = match (self.read(&input), fs::read(self.root.join(&input))) {
+                (Err(err), _) => Status::Broken(err.to_string()),
+                (Ok(decrypted), Ok(plain)) if decrypted != plain => Status::Differs,
+                (Ok(_), _) => Status::Ok,
+            };
+            (input, status)
+        });
+        Ok(statuses.collect())
+    }
+
+    fn entry_path(&self, input: &Path) -> Result<PathBuf, VaultError> {
+        entry_path(&self.dir, input)
+    }
+}
+
+/// `path` relative to `root`, both absolute, with `.` and `..` resolved
+/// without following links.
+fn input_name(root: &Path, path: &Path) -> Result<PathBuf, VaultError> {
+    let input = normalize(path).strip_prefix(normalize(root)).map(Path::to_path_buf);
+    input.map_err(|_| VaultError::InvalidInputPath(path.to_path_buf()))
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    let mut normal = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normal.pop();
+            }
+            other => normal.push(other),
+        }
+    }
+    normal
+}
+
+/// `<dir>/<input>.enc`, inputs must be relative paths inside the repository.
+fn entry_path(dir: &Path, input: &Path) -> Result<PathBuf, VaultError> {
+    let inside = input.components().all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if !inside || input.file_name().is_none() {
+        return Err(VaultError::InvalidInputPath(input.to_path_buf()));
+    }
+    let mut entry = dir.join(input).into_os_string();
+    entry.push(".");
+    entry.push(EXTENSION);
+    Ok(entry.into())
+}
+
+/// The input's path with `/` separators, the same on every platform.
+fn associated_data(input: &Path) -> String {
+    let parts = input.components().filter_map(|component| match component {
+        Component::Normal(part) => Some(part.to_string_lossy()),
+        _ => None,
+    });
+    parts.collect::<Vec<_>>().join("/")
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) || !text.is_ascii() {
+        return None;
+    }
+    (0..text.len()).step_by(2).map(|index| u8::from_str_radix(&text[index..index + 2], 16).ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A scratch repository with a key and a `vault/`, removed on drop.
+    struct Scratch(PathBuf);
+
+    impl Scratch {
+        fn new(name: &str) -> Scratch {
+            let root = env::temp_dir().join(format!("advent-vault-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&root);
+            fs::create_dir_all(root.join("2024/data")).unwrap();
+            fs::create_dir_all(root.join(VAULT_DIR)).unwrap();
+            assert!(Vault::create_key(&root.join(KEY_FILE)).unwrap());
+            Scratch(root)
+        }
+
+        fn vault(&self) -> Vault {
+            Vault::open(&self.0, &self.0.join(KEY_FILE)).unwrap()
+        }
+
+        fn write(&self, input: &str, contents: &str) -> PathBuf {
+            fs::write(self.0.join(input), contents).unwrap();
+            PathBuf::from(input)
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let scratch = Scratch::new("round-trip");
+        let vault = scratch.vault();
+        let input = scratch.write("2024/data/input_1.txt", "3   4\n4   3\n");
+        let entry = vault.add(&input).unwrap();
+
+        assert_eq!(entry, scratch.0.join("vault/2024/data/input_1.txt.enc"));
+        assert!(!fs::read(&entry).unwrap().windows(5).any(|window| window == b"3   4"));
+        assert_eq!(vault.read(&input).unwrap(), b"3   4\n4   3\n");
+        assert_eq!(vault.inputs().unwrap(), vec![input.clone()]);
+        assert_eq!(vault.verify().unwrap(), vec![(input.clone(), Status::Ok)]);
+
+        fs::remove_file(scratch.0.join(&input)).unwrap();
+        assert_eq!(vault.verify().unwrap(), vec![(input.clone(), Status::Ok)]);
+        scratch.write("2024/data/input_1.txt", "changed\n");
+        assert_eq!(vault.verify().unwrap(), vec![(input, Status::Differs)]);
+        assert!(matches!(vault.read(Path::new("2024/data/input_2.txt")), Err(VaultError::NotInVault(_))));
+    }
+
+    #[test]
+    fn test_open_falls_back_to_the_vault() {
+        let scratch = Scratch::new("open");
+        let input = scratch.write("2024/data/input_1.txt", "1 2\n");
+        let solver = scratch.0.join("2024");
+        let read = |path: &str| {
+            let mut text = String::new();
+            open_from(&solver, Path::new(path))?.read_to_string(&mut text)?;
+            Ok::<_, io::Error>(text)
+        };
+
+        assert_eq!(read("data/input_1.txt").unwrap(), "1 2\n");
+        scratch.vault().add(&input).unwrap();
+        fs::remove_file(scratch.0.join(&input)).unwrap();
+        assert_eq!(read("data/input_1.txt").unwrap(), "1 2\n");
+        assert_eq!(read("./data/../data/input_1.txt").unwrap(), "1 2\n");
+        assert_eq!(read("data/input_2.txt").unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_inputs_are_named_from_the_root() {
+        let root = Path::new("/repo");
+        let name = |path: &str| input_name(root, Path::new(path)).ok();
+        assert_eq!(name("/repo/2022/day04/./data.txt"), Some(PathBuf::from("2022/day04/data.txt")));
+        assert_eq!(name("/repo/2022/day04/../day05/data.txt"), Some(PathBuf::from("2022/day05/data.txt")));
+        assert_eq!(name("/repo/2022/../../etc/passwd"), None);
+        assert_eq!(name("/elsewhere/data.txt"), None);
+    }
+
+    #[test]
+    fn test_key_is_kept() {
+        let scratch = Scratch::new("key");
+        let key = fs::read_to_string(scratch.0.join(KEY_FILE)).unwrap();
+        assert!(!Vault::create_key(&scratch.0.join(KEY_FILE)).unwrap());
+        assert_eq!(fs::read_to_string(scratch.0.join(KEY_FILE)).unwrap(), key);
+        assert_eq!(decode_hex(key.trim()).map(|key| key.len()), Some(32));
+    }
+
+    #[test]
+    fn test_rejects_other_keys_and_tampering() {
+        let scratch = Scratch::new("tamper");
+        let vault = scratch.vault();
+        let input = scratch.write("2024/data/input_1.txt", "1 2\n");
+        let entry = vault.add(&input).unwrap();
+
+        let other_key = scratch.0.join("other.key");
+        Vault::create_key(&other_key).unwrap();
+        let other = Vault::open(&scratch.0, &other_key).unwrap();
+        assert!(matches!(other.read(&input), Err(VaultError::Decrypt(_))));
+
+        let mut contents = fs::read(&entry).unwrap();
+        *contents.last_mut().unwrap() ^= 1;
+        fs::write(&entry, &contents).unwrap();
+        assert!(matches!(vault.read(&input), Err(VaultError::Decrypt(_))));
+        assert!(matches!(&vault.verify().unwrap()[0].1, Status::Broken(_)));
+    }
+
+    #[test]
+    fn test_entries_are_bound_to_their_path() {
+        let scratch = Scratch::new("swap");
+        let vault = scratch.vault();
+        let first = scratch.write("2024/data/input_1.txt", "1\n");
+        let second = scratch.write("2024/data/input_2.txt", "2\n");
+        let first_entry = vault.add(&first).unwrap();
+        let second_entry = vault.add(&second).unwrap();
+
+        fs::copy(&first_entry, &second_entry).unwrap();
+        assert!(matches!(vault.read(&second), Err(VaultError::Decrypt(_))));
+    }
+
+    #[test]
+    fn test_invalid_inputs() {
+        let scratch = Scratch::new("invalid");
+        let vault = scratch.vault();
+        for input in ["../input.txt", "/etc/passwd", "2024/..", "2024/../../input.txt"] {
+            assert!(matches!(vault.add(Path::new(input)), Err(VaultError::InvalidInputPath(_))), "{}", input);
+        }
+        fs::write(scratch.0.join(KEY_FILE), "not hex\n").unwrap();
+        assert!(matches!(Vault::open(&scratch.0, &scratch.0.join(KEY_FILE)), Err(VaultError::BadKey(_))));
+    }
+}
@@ -0,0 +1,457 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::panic;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Once;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use advent_rng::Rng;
+use crate::{panic_message, Config};
+
+/// Pieces that tend to break parsers: numbers that overflow, separators,
+/// line endings, multi-byte characters and invalid UTF-8.
+const TOKENS: &[&[u8]] = &[
+    b"0",
+    b"-1",
+    b"2147483647",
+    b"-2147483648",
+    b"4294967296",
+    b"18446744073709551616",
+    b"99999999999999999999999",
+    b"\n",
+    b"\n\n",
+    b"\r\n",
+    b" ",
+    b"\t",
+    b",",
+    b"-",
+    b":",
+    b";",
+    b"[",
+    b"]",
+    "é".as_bytes(),
+    "🎄".as_bytes(),
+    b"\xff",
+    b"\0",
+];
+
+const WORKER_PREFIX: &str = "fuzz ";
+
+/// Upper bound on the parser runs spent minimising one crashing input.
+const MAX_MINIMIZE_STEPS: usize = 2000;
+
+/// Throws random and mutated byte strings at a parser for a time budget and
+/// fails if one makes it panic or keep running past `input_timeout`.
+///
+/// Crashing inputs are saved under `fuzz/regressions/<name>/` in the crate
+/// being tested and replayed before anything else on every later run, so a
+/// committed crash file is a regression test.
+///
+/// ```ignore
+/// propcheck::Fuzzer::new("parse_game")
+///     .seed_input("Game 1: 3 blue, 4 red; 2 green")
+///     .run(|bytes| Game::parse(&String::from_utf8_lossy(bytes)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Fuzzer {
+    name: String,
+    seeds: Vec<Vec<u8>>,
+    budget: Duration,
+    input_timeout: Duration,
+    max_len: usize,
+    rng_seed: u64,
+    regressions: PathBuf,
+}
+
+/// An input the parser didn't return for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Crash {
+    pub input: Vec<u8>,
+    /// How the parser failed, a panic message or a timeout.
+    pub message: String,
+    /// The saved file the input came from, if it was a known crash.
+    pub regression: Option<PathBuf>,
+    /// Number of inputs run before the crash, saved crashes included.
+    pub inputs_tried: usize,
+}
+
+enum Outcome {
+    Returned,
+    Panicked(String),
+    Hung,
+}
+
+impl Fuzzer {
+    /// A fuzzer with a budget of one second, or `PROPCHECK_FUZZ_MS`
+    /// milliseconds, that replays and saves crashes for the crate whose
+    /// tests are running.
+    pub fn new(name: &str) -> Self {
+        let budget = env::var("PROPCHECK_FUZZ_MS").ok().and_then(|ms| ms.parse().ok()).unwrap_or(1000);
+        let crate_dir = env::var_os("CARGO_MANIFEST_DIR").map_or_else(|| PathBuf::from("."), PathBuf::from);
+        let file_name: String =
+            name.chars().map(|ch| if ch.is_ascii_alphanumeric() || ch == '-' { ch } else { '_' }).collect();
+        Fuzzer {
+            name: name.to_owned(),
+            seeds: Vec::new(),
+            budget: Duration::from_millis(budget),
+            input_timeout: Duration::from_secs(1),
+            max_len: 64,
+            rng_seed: Config::default().seed,
+            regressions: crate_dir.join("fuzz").join("regressions").join(file_name),
+        }
+    }
+
+    /// Adds a well-formed input to mutate, such as a line of a puzzle input.
+    pub fn seed_input(mut self, input: impl AsRef<[u8]>) -> Self {
+        self.seeds.push(input.as_ref().to_vec());
+        self
+    }
+
+    pub fn budget(self, budget: Duration) -> Self {
+        Fuzzer { budget, ..self }
+    }
+
+    pub fn input_timeout(self, input_timeout: Duration) -> Self {
+        Fuzzer { input_timeout, ..self }
+    }
+
+    /// Longest purely random input, mutated seeds may grow past it.
+    pub fn max_len(self, max_len: usize) -> Self {
+        Fuzzer { max_len, ..self }
+    }
+
+    /// Where crashing inputs are saved and replayed from.
+    pub fn regressions(self, regressions: impl Into<PathBuf>) -> Self {
+        Fuzzer { regressions: regressions.into(), ..self }
+    }
+
+    /// Replays the saved crashes, runs each seed as is and then fuzzes until
+    /// the budget is spent. Returns the first crash, minimised if it was a
+    /// panic. A parser that hangs is left running on its own thread.
+    pub fn find_crash<F, T, E>(&self, target: F) -> Option<Crash>
+    where
+        F: Fn(&[u8]) -> Result<T, E> + Send + 'static,
+    {
+        let runner = Runner::spawn(&self.name, target, self.input_timeout);
+        let mut inputs_tried = 0;
+        let crash = |input: Vec<u8>, outcome: Outcome, regression: Option<PathBuf>, tried: usize| {
+            let (input, message) = match outcome {
+                Outcome::Returned => return None,
+                Outcome::Panicked(message) if regression.is_none() => runner.minimize(input, message),
+                Outcome::Panicked(message) => (input, message),
+                Outcome::Hung => (input, format!("still running after {:?}", self.input_timeout)),
+            };
+            Some(Crash { input, message, regression, inputs_tried: tried })
+        };
+
+        for path in saved_crashes(&self.regressions) {
+            let input = fs::read(&path).unwrap_or_default();
+            inputs_tried += 1;
+            let outcome = runner.run(&input);
+            if let Some(found) = crash(input, outcome, Some(path), inputs_tried) {
+                return Some(found);
+            }
+        }
+        for seed in &self.seeds {
+            inputs_tried += 1;
+            let outcome = runner.run(seed);
+            if let Some(found) = crash(seed.clone(), outcome, None, inputs_tried) {
+                return Some(found);
+            }
+        }
+
+        let mut rng = Rng::new(self.rng_seed);
+        let started = Instant::now();
+        while started.elapsed() < self.budget {
+            let input = self.next_input(&mut rng);
+            inputs_tried += 1;
+            let outcome = runner.run(&input);
+            if let Some(found) = crash(input, outcome, None, inputs_tried) {
+                return Some(found);
+            }
+        }
+        None
+    }
+
+    /// Panics if [`Fuzzer::find_crash`] finds a crash, after saving a new
+    /// one to the regressions directory.
+    pub fn run<F, T, E>(&self, target: F)
+    where
+        F: Fn(&[u8]) -> Result<T, E> + Send + 'static,
+    {
+        let Some(crash) = self.find_crash(target) else {
+            return;
+        };
+        let saved = match &crash.regression {
+            Some(path) => format!("saved crash {} still fails", path.display()),
+            None => match self.save(&crash.input) {
+                Ok(path) => format!("saved to {}, commit it to keep it as a regression test", path.display()),
+                Err(err) => format!("could not save it to {}: {}", self.regressions.display(), err),
+            },
+        };
+        panic!(
+            "fuzz target '{}' crashed after {} inputs (seed {}, set PROPCHECK_SEED to replay)\n\
+             input: {:?}\n{}\n{}",
+            self.name,
+            crash.inputs_tried,
+            self.rng_seed,
+            String::from_utf8_lossy(&crash.input),
+            crash.message,
+            saved
+        );
+    }
+
+    fn save(&self, input: &[u8]) -> std::io::Result<PathBuf> {
+        fs::create_dir_all(&self.regressions)?;
+        let path = self.regressions.join(format!("crash-{:016x}", fnv1a(input)));
+        fs::write(&path, input)?;
+        Ok(path)
+    }
+
+    /// Random bytes a quarter of the time or when there are no seeds,
+    /// otherwise a seed with one to four mutations.
+    fn next_input(&self, rng: &mut Rng) -> Vec<u8> {
+        if self.seeds.is_empty() || rng.below(4) == 0 {
+            let len = rng.below(self.max_len as u64 + 1) as usize;
+            let mut input = Vec::with_capacity(len);
+            while input.len() < len {
+                match rng.below(3) {
+                    0 => input.push(rng.below(256) as u8),
+                    1 => input.push(b' ' + rng.below(95) as u8),
+                    _ => input.extend_from_slice(TOKENS[rng.below(TOKENS.len() as u64) as usize]),
+                }
+            }
+            return input;
+        }
+
+        let mut input = self.seeds[rng.below(self.seeds.len() as u64) as usize].clone();
+        for _ in 0..=rng.below(4) {
+            self.mutate(&mut input, rng);
+        }
+        input
+    }
+
+    fn mutate(&self, input: &mut Vec<u8>, rng: &mut Rng) {
+        let position = |rng: &mut Rng, input: &[u8]| rng.below(input.len() as u64 + 1) as usize;
+        let span = |rng: &mut Rng, input: &[u8]| {
+            let start = position(rng, input);
+            (start, start + rng.below((input.len() - start) as u64 + 1) as usize)
+        };
+        match rng.below(6) {
+            0 if !input.is_empty() => {
+                let at = rng.below(input.len() as u64) as usize;
+                input[at] = rng.below(256) as u8;
+            }
+            1 => {
+                let at = position(rng, input);
+                let token = TOKENS[rng.below(TOKENS.len() as u64) as usize];
+                input.splice(at..at, token.iter().copied());
+            }
+            2 => {
+                let (start, end) = span(rng, input);
+                input.drain(start..end);
+            }
+            3 => {
+                let (start, end) = span(rng, input);
+                let copy = input[start..end].to_vec();
+                let at = position(rng, input);
+                input.splice(at..at, copy);
+            }
+            4 => {
+                let other = &self.seeds[rng.below(self.seeds.len() as u64) as usize];
+                let (keep, from) = (position(rng, input), rng.below(other.len() as u64 + 1) as usize);
+                input.truncate(keep);
+                input.extend_from_slice(&other[from..]);
+            }
+            _ => {
+                let at = position(rng, input);
+                input.truncate(at);
+            }
+        }
+        input.truncate(self.max_len.max(self.seeds.iter().map(Vec::len).max().unwrap_or(0)) * 4);
+    }
+}
+
+/// Runs the parser on its own thread so that an input it hangs on can be
+/// reported instead of stalling the test.
+struct Runner {
+    inputs: Sender<Vec<u8>>,
+    outcomes: Receiver<Option<String>>,
+    timeout: Duration,
+}
+
+impl Runner {
+    fn spawn<F, T, E>(name: &str, target: F, timeout: Duration) -> Runner
+    where
+        F: Fn(&[u8]) -> Result<T, E> + Send + 'static,
+    {
+        silence_worker_panics();
+        let (inputs, worker_inputs) = mpsc::channel::<Vec<u8>>();
+        let (worker_outcomes, outcomes) = mpsc::channel();
+        thread::Builder::new()
+            .name(format!("{}{}", WORKER_PREFIX, name))
+            .spawn(move || {
+                for input in worker_inputs {
+                    let outcome = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+                        let _ = target(&input);
+                    }));
+                    if worker_outcomes.send(outcome.err().map(panic_message)).is_err() {
+                        break;
+                    }
+                }
+            })
+            .expect("failed to start the fuzz worker thread");
+        Runner { inputs, outcomes, timeout }
+    }
+
+    fn run(&self, input: &[u8]) -> Outcome {
+        if self.inputs.send(input.to_vec()).is_err() {
+            return Outcome::Hung;
+        }
+        match self.outcomes.recv_timeout(self.timeout) {
+            Ok(None) => Outcome::Returned,
+            Ok(Some(message)) => Outcome::Panicked(message),
+            Err(_) => Outcome::Hung,
+        }
+    }
+
+    /// Removes ever smaller chunks of `input` as long as it still panics.
+    fn minimize(&self, mut input: Vec<u8>, mut message: String) -> (Vec<u8>, String) {
+        let mut chunk = input.len().div_ceil(2);
+        let mut steps = 0;
+        while chunk > 0 && steps < MAX_MINIMIZE_STEPS {
+            let mut start = 0;
+            let mut removed = false;
+            while start < input.len() && steps < MAX_MINIMIZE_STEPS {
+                let mut candidate = input.clone();
+                candidate.drain(start..(start + chunk).min(input.len()));
+                steps += 1;
+                match self.run(&candidate) {
+                    Outcome::Panicked(candidate_message) => {
+                        input = candidate;
+                        message = candidate_message;
+                        removed = true;
+                    }
+                    Outcome::Returned => start += chunk,
+                    Outcome::Hung => return (input, message),
+                }
+            }
+            if !removed {
+                chunk /= 2;
+            }
+        }
+        (input, message)
+    }
+}
+
+/// Keeps the default panic output for every thread but the fuzz workers,
+/// whose panics end up in the report of the crash.
+fn silence_worker_panics() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !thread::current().name().is_some_and(|name| name.starts_with(WORKER_PREFIX)) {
+                previous(info);
+            }
+        }));
+    });
+}
+
+fn saved_crashes(directory: &Path) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(directory)
+        .map(|entries| entries.filter_map(|entry| Some(entry.ok()?.path())).filter(|path| path.is_file()).collect())
+        .unwrap_or_default();
+    paths.sort();
+    paths
+}
+
+/// FNV-1a, to name crash files after their content.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("propcheck-fuzz-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    /// Takes the number before the first comma, panicking if there is none.
+    fn fragile_parse(bytes: &[u8]) -> Result<u32, std::num::ParseIntError> {
+        let text = String::from_utf8_lossy(bytes);
+        text[..text.find(',').unwrap()].parse()
+    }
+
+    #[test]
+    fn test_finds_and_minimizes_panics() {
+        let crash = Fuzzer::new("fragile")
+            .regressions(scratch_dir("minimize"))
+            .seed_input("12,34")
+            .run_seeds_only()
+            .find_crash(fragile_parse);
+        assert!(crash.is_none());
+
+        let crash = Fuzzer::new("fragile")
+            .regressions(scratch_dir("minimize"))
+            .seed_input("12,34")
+            .find_crash(fragile_parse)
+            .unwrap();
+        assert_eq!(crash.input, b"");
+        assert!(crash.message.starts_with("panicked: "), "{}", crash.message);
+        assert_eq!(crash.regression, None);
+    }
+
+    #[test]
+    fn test_reports_hangs() {
+        let crash = Fuzzer::new("hang")
+            .regressions(scratch_dir("hang"))
+            .input_timeout(Duration::from_millis(50))
+            .find_crash(|bytes: &[u8]| {
+                while bytes.contains(&b'\n') {
+                    thread::sleep(Duration::from_millis(10));
+                }
+                Ok::<_, ()>(())
+            })
+            .unwrap();
+        assert!(crash.input.contains(&b'\n'));
+        assert_eq!(crash.message, "still running after 50ms");
+    }
+
+    #[test]
+    fn test_saved_crashes_are_replayed_first() {
+        let dir = scratch_dir("replay");
+        let fuzzer = Fuzzer::new("fragile").regressions(&dir).run_seeds_only();
+        let path = fuzzer.save(b"no comma").unwrap();
+        assert_eq!(path.file_name().unwrap().to_str().unwrap().len(), "crash-".len() + 16);
+
+        let crash = fuzzer.find_crash(fragile_parse).unwrap();
+        assert_eq!(crash.input, b"no comma");
+        assert_eq!(crash.regression, Some(path));
+        assert_eq!(crash.inputs_tried, 1);
+
+        assert!(fuzzer.find_crash(|bytes: &[u8]| Ok::<_, ()>(bytes.len())).is_none());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_mutations_stay_bounded() {
+        let fuzzer = Fuzzer::new("bounded").seed_input("move 1 from 2 to 3").max_len(16);
+        let mut rng = Rng::new(3);
+        for _ in 0..2000 {
+            assert!(fuzzer.next_input(&mut rng).len() <= 18 * 4);
+        }
+    }
+
+    impl Fuzzer {
+        fn run_seeds_only(self) -> Self {
+            self.budget(Duration::ZERO)
+        }
+    }
+}
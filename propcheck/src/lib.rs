@@ -0,0 +1,216 @@
+//! A small property testing layer: generate inputs from a seeded PRNG, run a
+//! property on them and shrink the first failing input to a minimal one.
+//!
+//! ```ignore
+//! propcheck::check_against(
+//!     "fast sum matches naive sum",
+//!     &propcheck::vecs(propcheck::ints(-10, 10), 0, 20),
+//!     |values| fast_sum(values),
+//!     |values| values.iter().sum::<i64>(),
+//! );
+//! ```
+//!
+//! [`Fuzzer`] does the same for parsers with random and mutated bytes, it
+//! only checks that they return.
+
+use std::any::Any;
+use std::env;
+use std::fmt::Debug;
+use std::panic::{self, AssertUnwindSafe};
+
+mod fuzz;
+mod strategy;
+
+pub use advent_rng::Rng;
+pub use fuzz::{Crash, Fuzzer};
+pub use strategy::{bools, ints, strings, vecs, Bools, Ints, Strategy, Strings, Vecs};
+
+/// How many cases to run and where to start. The seed can be overridden with
+/// the `PROPCHECK_SEED` environment variable to replay a failure.
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+    pub cases: usize,
+    pub seed: u64,
+    /// Upper bound on the shrink candidates tried for one failure.
+    pub max_shrink_steps: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let seed = env::var("PROPCHECK_SEED").ok().and_then(|seed| seed.parse().ok()).unwrap_or(0x5eed);
+        Config { cases: 500, seed, max_shrink_steps: 2000 }
+    }
+}
+
+/// A failing input after shrinking.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Counterexample<V> {
+    pub value: V,
+    /// What went wrong for `value`.
+    pub message: String,
+    /// 1-based number of the case that failed first.
+    pub case: usize,
+    pub shrinks: usize,
+}
+
+impl Config {
+    pub fn cases(self, cases: usize) -> Self {
+        Config { cases, ..self }
+    }
+
+    /// Runs `failure` on generated values, it returns a description of the
+    /// failure or `None` if the property holds. A panic counts as a failure.
+    pub fn find_counterexample<S, F>(&self, strategy: &S, failure: F) -> Option<Counterexample<S::Value>>
+    where
+        S: Strategy,
+        F: Fn(&S::Value) -> Option<String>,
+    {
+        let failure = |value: &S::Value| match panic::catch_unwind(AssertUnwindSafe(|| failure(value))) {
+            Ok(outcome) => outcome,
+            Err(payload) => Some(panic_message(payload)),
+        };
+
+        let mut rng = Rng::new(self.seed);
+        for case in 1..=self.cases {
+            let value = strategy.generate(&mut rng);
+            if let Some(message) = failure(&value) {
+                return Some(self.shrink(strategy, &failure, Counterexample { value, message, case, shrinks: 0 }));
+            }
+        }
+        None
+    }
+
+    /// Moves to the first simpler value that still fails until none does.
+    fn shrink<S, F>(&self, strategy: &S, failure: &F, mut found: Counterexample<S::Value>) -> Counterexample<S::Value>
+    where
+        S: Strategy,
+        F: Fn(&S::Value) -> Option<String>,
+    {
+        let mut steps = 0;
+        'simpler: while steps < self.max_shrink_steps {
+            for candidate in strategy.shrink(&found.value) {
+                steps += 1;
+                if let Some(message) = failure(&candidate) {
+                    found = Counterexample { value: candidate, message, shrinks: found.shrinks + 1, ..found };
+                    continue 'simpler;
+                }
+                if steps >= self.max_shrink_steps {
+                    break;
+                }
+            }
+            break;
+        }
+        found
+    }
+
+    /// Panics with a minimal counterexample if `property` doesn't hold.
+    pub fn check<S, P>(&self, name: &str, strategy: &S, property: P)
+    where
+        S: Strategy,
+        P: Fn(&S::Value) -> bool,
+    {
+        let found = self.find_counterexample(strategy, |value| (!property(value)).then(|| "property is false".to_owned()));
+        self.report(name, found);
+    }
+
+    /// Panics with a minimal counterexample if `solver` and `oracle` disagree.
+    pub fn check_against<S, T, A, B>(&self, name: &str, strategy: &S, solver: A, oracle: B)
+    where
+        S: Strategy,
+        T: PartialEq + Debug,
+        A: Fn(&S::Value) -> T,
+        B: Fn(&S::Value) -> T,
+    {
+        let found = self.find_counterexample(strategy, |value| {
+            let (solved, expected) = (solver(value), oracle(value));
+            (solved != expected).then(|| format!("solver gave {:?}, oracle {:?}", solved, expected))
+        });
+        self.report(name, found);
+    }
+
+    fn report<V: Debug>(&self, name: &str, found: Option<Counterexample<V>>) {
+        if let Some(found) = found {
+            panic!(
+                "property '{}' failed at case {} of {} (seed {}, set PROPCHECK_SEED to replay)\n\
+                 minimal counterexample after {} shrinks: {:?}\n{}",
+                name, found.case, self.cases, self.seed, found.shrinks, found.value, found.message
+            );
+        }
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    let reason = payload
+        .downcast_ref::<&str>()
+        .map(|reason| reason.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_default();
+    format!("panicked: {}", reason)
+}
+
+/// [`Config::check`] with the default configuration.
+pub fn check<S, P>(name: &str, strategy: &S, property: P)
+where
+    S: Strategy,
+    P: Fn(&S::Value) -> bool,
+{
+    Config::default().check(name, strategy, property)
+}
+
+/// [`Config::check_against`] with the default configuration.
+pub fn check_against<S, T, A, B>(name: &str, strategy: &S, solver: A, oracle: B)
+where
+    S: Strategy,
+    T: PartialEq + Debug,
+    A: Fn(&S::Value) -> T,
+    B: Fn(&S::Value) -> T,
+{
+    Config::default().check_against(name, strategy, solver, oracle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_passing_property() {
+        check("sorting keeps the length", &vecs(ints(-5, 5), 0, 10), |values| {
+            let mut sorted = values.clone();
+            sorted.sort();
+            sorted.len() == values.len()
+        });
+    }
+
+    #[test]
+    fn test_shrinks_to_minimal_counterexample() {
+        let found = Config::default()
+            .find_counterexample(&vecs(ints(0, 200), 0, 10), |values| {
+                values.iter().any(|&value| value >= 100).then(|| "too big".to_owned())
+            })
+            .unwrap();
+        assert_eq!(found.value, vec![100]);
+
+        let found = Config::default()
+            .find_counterexample(&strings("abc", 0, 12), |text| text.contains("cb").then(String::new))
+            .unwrap();
+        assert_eq!(found.value, "cb");
+    }
+
+    #[test]
+    fn test_panics_are_failures() {
+        let found = Config::default()
+            .find_counterexample(&(ints(0, 10), bools()), |&(value, flag)| {
+                assert!(!flag || value < 3, "boom");
+                None
+            })
+            .unwrap();
+        assert_eq!(found.value, (3, true));
+        assert_eq!(found.message, "panicked: boom");
+    }
+
+    #[test]
+    #[should_panic(expected = "minimal counterexample after")]
+    fn test_check_against_reports_disagreement() {
+        Config::default().cases(100).check_against("wrong abs", &ints(-10, 10), |&value| value, |&value| value.abs());
+    }
+}
@@ -0,0 +1,191 @@
+use std::fmt::Debug;
+
+use advent_rng::Rng;
+
+/// Generates values and proposes simpler versions of failing ones.
+pub trait Strategy {
+    type Value: Clone + Debug;
+
+    fn generate(&self, rng: &mut Rng) -> Self::Value;
+
+    /// Simpler values than `value`, most promising first. Shrinking stops
+    /// when none of them fails.
+    fn shrink(&self, _value: &Self::Value) -> Vec<Self::Value> {
+        Vec::new()
+    }
+}
+
+/// Integers in `low..=high`.
+#[derive(Debug, Clone, Copy)]
+pub struct Ints {
+    low: i64,
+    high: i64,
+}
+
+/// Integers in `low..=high`, shrinking towards 0 or the bound closest to it.
+pub fn ints(low: i64, high: i64) -> Ints {
+    assert!(low <= high, "empty range {}..={}", low, high);
+    Ints { low, high }
+}
+
+impl Strategy for Ints {
+    type Value = i64;
+
+    fn generate(&self, rng: &mut Rng) -> i64 {
+        rng.range(self.low, self.high)
+    }
+
+    /// The target itself, then halfway there, a quarter of the way and so
+    /// on down to one step closer.
+    fn shrink(&self, &value: &i64) -> Vec<i64> {
+        let target = 0.clamp(self.low, self.high);
+        let mut candidates = Vec::new();
+        let mut distance = value - target;
+        while distance != 0 {
+            candidates.push(value - distance);
+            distance /= 2;
+        }
+        candidates
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Bools;
+
+/// `true` or `false`, shrinking to `false`.
+pub fn bools() -> Bools {
+    Bools
+}
+
+impl Strategy for Bools {
+    type Value = bool;
+
+    fn generate(&self, rng: &mut Rng) -> bool {
+        rng.below(2) == 1
+    }
+
+    fn shrink(&self, &value: &bool) -> Vec<bool> {
+        if value { vec![false] } else { Vec::new() }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Vecs<S> {
+    element: S,
+    min_len: usize,
+    max_len: usize,
+}
+
+/// Vectors of `min_len..=max_len` elements, shrinking by dropping elements
+/// and then by shrinking the remaining ones.
+pub fn vecs<S: Strategy>(element: S, min_len: usize, max_len: usize) -> Vecs<S> {
+    assert!(min_len <= max_len, "empty length range {}..={}", min_len, max_len);
+    Vecs { element, min_len, max_len }
+}
+
+impl<S: Strategy> Strategy for Vecs<S> {
+    type Value = Vec<S::Value>;
+
+    fn generate(&self, rng: &mut Rng) -> Self::Value {
+        let len = rng.range(self.min_len as i64, self.max_len as i64) as usize;
+        (0..len).map(|_| self.element.generate(rng)).collect()
+    }
+
+    fn shrink(&self, value: &Self::Value) -> Vec<Self::Value> {
+        let mut candidates = Vec::new();
+        let half = value.len() / 2;
+        if half > 0 && value.len() - half >= self.min_len {
+            candidates.push(value[half..].to_vec());
+            candidates.push(value[..value.len() - half].to_vec());
+        }
+        if value.len() > self.min_len {
+            for index in 0..value.len() {
+                let mut shorter = value.clone();
+                shorter.remove(index);
+                candidates.push(shorter);
+            }
+        }
+        for (index, element) in value.iter().enumerate() {
+            for simpler in self.element.shrink(element) {
+                let mut candidate = value.clone();
+                candidate[index] = simpler;
+                candidates.push(candidate);
+            }
+        }
+        candidates
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Strings {
+    alphabet: Vec<char>,
+    min_len: usize,
+    max_len: usize,
+}
+
+/// Strings of `min_len..=max_len` characters from `alphabet`, shrinking by
+/// dropping characters and by replacing them with the first letter.
+pub fn strings(alphabet: &str, min_len: usize, max_len: usize) -> Strings {
+    assert!(!alphabet.is_empty(), "empty alphabet");
+    Strings { alphabet: alphabet.chars().collect(), min_len, max_len }
+}
+
+impl Strategy for Strings {
+    type Value = String;
+
+    fn generate(&self, rng: &mut Rng) -> String {
+        let len = rng.range(self.min_len as i64, self.max_len as i64) as usize;
+        (0..len).map(|_| self.alphabet[rng.below(self.alphabet.len() as u64) as usize]).collect()
+    }
+
+    fn shrink(&self, value: &String) -> Vec<String> {
+        let chars: Vec<char> = value.chars().collect();
+        let mut candidates = Vec::new();
+        if chars.len() > self.min_len {
+            let half = chars.len() / 2;
+            if half > 0 && chars.len() - half >= self.min_len {
+                candidates.push(chars[half..].iter().collect());
+                candidates.push(chars[..chars.len() - half].iter().collect());
+            }
+            for index in 0..chars.len() {
+                candidates.push(chars.iter().enumerate().filter(|&(i, _)| i != index).map(|(_, &ch)| ch).collect());
+            }
+        }
+        let first = self.alphabet[0];
+        for index in (0..chars.len()).filter(|&index| chars[index] != first) {
+            let mut simpler = chars.clone();
+            simpler[index] = first;
+            candidates.push(simpler.into_iter().collect());
+        }
+        candidates
+    }
+}
+
+macro_rules! tuple_strategy {
+    ($($name:ident $index:tt),+) => {
+        /// Tuples of independent values, shrinking one component at a time.
+        impl<$($name: Strategy),+> Strategy for ($($name,)+) {
+            type Value = ($($name::Value,)+);
+
+            fn generate(&self, rng: &mut Rng) -> Self::Value {
+                ($(self.$index.generate(rng),)+)
+            }
+
+            fn shrink(&self, value: &Self::Value) -> Vec<Self::Value> {
+                let mut candidates = Vec::new();
+                $(
+                    for simpler in self.$index.shrink(&value.$index) {
+                        let mut candidate = value.clone();
+                        candidate.$index = simpler;
+                        candidates.push(candidate);
+                    }
+                )+
+                candidates
+            }
+        }
+    };
+}
+
+tuple_strategy!(A 0, B 1);
+tuple_strategy!(A 0, B 1, C 2);
+tuple_strategy!(A 0, B 1, C 2, D 3);